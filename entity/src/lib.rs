@@ -1,9 +1,16 @@
 pub mod prelude;
 
+pub mod login_attempts;
+pub mod order_errors;
 pub mod orders;
+pub mod password_histories;
+pub mod refresh_tokens;
+pub mod risk_rules;
 pub mod signal_records;
 pub mod strategies;
+pub mod strategy_stats;
 pub mod subscribes;
 pub mod symbols;
+pub mod user_sessions;
+pub mod user_totps;
 pub mod users;
-pub mod order_errors;
\ No newline at end of file
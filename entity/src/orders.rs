@@ -12,6 +12,8 @@ pub struct Model {
     pub symbol: String,
     pub price: f64,
     pub qty: f64,
+    pub exec_qty: f64,
+    pub avg_price: f64,
     pub order_type: String,
     pub reduce_only: Option<i8>,
     pub kline_time: Option<i32>,
@@ -22,6 +24,7 @@ pub struct Model {
     pub strategy_name: String,
     pub action: i8,
     pub state: i8,
+    pub reconcile_failures: i32,
     pub created_at: DateTimeLocal,
     pub updated_at: DateTimeLocal,
 }
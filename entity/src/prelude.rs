@@ -1,9 +1,16 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 
+pub use super::login_attempts::Entity as LoginAttempts;
+pub use super::order_errors::Entity as OrderErrors;
 pub use super::orders::Entity as Orders;
+pub use super::password_histories::Entity as PasswordHistories;
+pub use super::refresh_tokens::Entity as RefreshTokens;
+pub use super::risk_rules::Entity as RiskRules;
 pub use super::signal_records::Entity as SignalRecords;
 pub use super::strategies::Entity as Strategies;
+pub use super::strategy_stats::Entity as StrategyStats;
 pub use super::subscribes::Entity as Subscribes;
 pub use super::symbols::Entity as Symbols;
+pub use super::user_sessions::Entity as UserSessions;
+pub use super::user_totps::Entity as UserTotps;
 pub use super::users::Entity as Users;
-pub use super::order_errors::Entity as OrderErrors;
\ No newline at end of file
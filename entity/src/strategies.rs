@@ -10,6 +10,7 @@ pub struct Model {
     pub symbol_name: String,
     pub state: i8,
     pub side: i8,
+    pub secret: String,
     #[sea_orm(column_name = "Remark")]
     pub remark: String,
     pub created_at: DateTimeLocal,
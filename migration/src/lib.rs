@@ -7,6 +7,20 @@ mod m20220813_000002_create_strategies_table;
 mod m20220813_000003_create_subscribes_table;
 mod m20220813_000004_create_symbols_table;
 mod m20220813_000005_create_order_errors_table;
+mod m20220820_000001_add_secret_to_strategies;
+mod m20220821_000001_encrypt_user_secret_key;
+mod m20220822_000001_create_user_sessions_table;
+mod m20220822_000002_create_refresh_tokens_table;
+mod m20220823_000001_create_password_histories_table;
+mod m20220823_000002_create_login_attempts_table;
+mod m20220824_000001_create_user_totps_table;
+mod m20220825_000001_create_strategy_stats_table;
+mod m20220826_000001_create_risk_rules_table;
+mod m20220827_000001_add_unique_account_to_users;
+mod m20220828_000001_add_fill_to_orders;
+mod m20220829_000001_add_reconcile_failures_to_orders;
+mod m20220830_000001_add_unique_open_signal;
+mod m20220831_000001_add_unique_subscribe;
 
 pub struct Migrator;
 
@@ -21,6 +35,20 @@ impl MigratorTrait for Migrator {
             Box::new(m20220813_000003_create_subscribes_table::Migration),
             Box::new(m20220813_000004_create_symbols_table::Migration),
             Box::new(m20220813_000005_create_order_errors_table::Migration),
+            Box::new(m20220820_000001_add_secret_to_strategies::Migration),
+            Box::new(m20220821_000001_encrypt_user_secret_key::Migration),
+            Box::new(m20220822_000001_create_user_sessions_table::Migration),
+            Box::new(m20220822_000002_create_refresh_tokens_table::Migration),
+            Box::new(m20220823_000001_create_password_histories_table::Migration),
+            Box::new(m20220823_000002_create_login_attempts_table::Migration),
+            Box::new(m20220824_000001_create_user_totps_table::Migration),
+            Box::new(m20220825_000001_create_strategy_stats_table::Migration),
+            Box::new(m20220826_000001_create_risk_rules_table::Migration),
+            Box::new(m20220827_000001_add_unique_account_to_users::Migration),
+            Box::new(m20220828_000001_add_fill_to_orders::Migration),
+            Box::new(m20220829_000001_add_reconcile_failures_to_orders::Migration),
+            Box::new(m20220830_000001_add_unique_open_signal::Migration),
+            Box::new(m20220831_000001_add_unique_subscribe::Migration),
         ]
     }
 }
@@ -0,0 +1,27 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        ALTER TABLE `users`
+            MODIFY COLUMN `secret_key` varchar(255) NOT NULL DEFAULT '' COMMENT 'bybit secret key (AES-256-GCM加密)'
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        ALTER TABLE `users`
+            MODIFY COLUMN `secret_key` varchar(100) NOT NULL DEFAULT '' COMMENT 'bybit secret key'
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
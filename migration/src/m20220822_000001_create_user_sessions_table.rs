@@ -0,0 +1,32 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `user_sessions` (
+            `id` bigint NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            `sid` varchar(36) NOT NULL COMMENT 'session id, 寫入jwt的sid',
+            `user_account` varchar(30) NOT NULL COMMENT '用戶帳號',
+            `device` varchar(255) NOT NULL DEFAULT '' COMMENT '登入裝置(user agent)',
+            `revoked_at` datetime DEFAULT NULL COMMENT '撤銷時間',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            `updated_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            UNIQUE INDEX (sid),
+            INDEX (user_account)
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `user_sessions`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
@@ -0,0 +1,32 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `refresh_tokens` (
+            `id` bigint NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            `sid` varchar(36) NOT NULL COMMENT 'session id',
+            `token_hash` char(64) NOT NULL COMMENT 'refresh token sha256',
+            `expired_at` datetime NOT NULL COMMENT '過期時間',
+            `used_at` datetime DEFAULT NULL COMMENT '已換發時間, 再次使用視為重放',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            `updated_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            UNIQUE INDEX (token_hash),
+            INDEX (sid)
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `refresh_tokens`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
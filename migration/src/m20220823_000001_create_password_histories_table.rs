@@ -0,0 +1,28 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `password_histories` (
+            `id` bigint NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            `user_account` varchar(30) NOT NULL COMMENT '用戶帳號',
+            `password` varchar(255) NOT NULL COMMENT '舊密碼bcrypt hash',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            INDEX (user_account)
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `password_histories`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
@@ -0,0 +1,31 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `login_attempts` (
+            `id` bigint NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            `attempt_key` varchar(100) NOT NULL COMMENT 'account:帳號 或 ip:位址',
+            `failures` int NOT NULL DEFAULT 0 COMMENT '連續失敗次數',
+            `blocked_until` datetime DEFAULT NULL COMMENT '此時間前不可再嘗試',
+            `last_failed_at` datetime NOT NULL COMMENT '最後失敗時間',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            `updated_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            UNIQUE INDEX (attempt_key)
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `login_attempts`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
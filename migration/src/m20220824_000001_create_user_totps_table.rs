@@ -0,0 +1,35 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `user_totps` (
+            `id` bigint NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            `user_account` varchar(30) NOT NULL COMMENT '用戶帳號',
+            `secret` varchar(255) NOT NULL COMMENT 'totp secret, 以帳號為aad加密',
+            `confirmed_at` datetime DEFAULT NULL COMMENT '完成綁定時間, NULL為尚未確認',
+            `last_used_step` bigint NOT NULL DEFAULT 0 COMMENT '最後使用的time step, 防止重放',
+            `recovery_codes` varchar(1000) NOT NULL DEFAULT '' COMMENT '未使用的recovery code sha256, 逗號分隔',
+            `challenge_hash` char(64) DEFAULT NULL COMMENT '登入第二步的token sha256',
+            `challenge_expired_at` datetime DEFAULT NULL COMMENT '登入第二步的期限',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            `updated_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            UNIQUE INDEX (user_account),
+            INDEX (challenge_hash)
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `user_totps`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
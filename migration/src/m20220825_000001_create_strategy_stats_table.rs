@@ -0,0 +1,37 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `strategy_stats` (
+            `strategy_name` varchar(30) PRIMARY KEY NOT NULL COMMENT '策略名稱',
+            `trades` int NOT NULL DEFAULT 0 COMMENT '已平倉訊號數',
+            `wins` int NOT NULL DEFAULT 0 COMMENT '獲利次數',
+            `losses` int NOT NULL DEFAULT 0 COMMENT '虧損次數',
+            `gross_profit` double NOT NULL DEFAULT 0 COMMENT '獲利合計(%)',
+            `gross_loss` double NOT NULL DEFAULT 0 COMMENT '虧損合計(%)',
+            `equity` double NOT NULL DEFAULT 1 COMMENT '複利淨值, 起始為1',
+            `return_sum` double NOT NULL DEFAULT 0 COMMENT '報酬率合計',
+            `return_sq_sum` double NOT NULL DEFAULT 0 COMMENT '報酬率平方合計',
+            `losing_streak` int NOT NULL DEFAULT 0 COMMENT '目前連續虧損次數',
+            `longest_losing_streak` int NOT NULL DEFAULT 0 COMMENT '最長連續虧損次數',
+            `last_record_id` bigint NOT NULL DEFAULT 0 COMMENT '最後計入的signal_records.id',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            `updated_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `strategy_stats`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        CREATE TABLE IF NOT EXISTS `risk_rules` (
+            `id` bigint NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            `scope` tinyint(1) NOT NULL COMMENT '1 => 用戶 2 => 策略',
+            `target` varchar(30) NOT NULL COMMENT '用戶帳號或策略名稱',
+            `max_open_positions` int COMMENT '最多持倉數',
+            `max_notional` double COMMENT '單一合約最大名目價值',
+            `max_leverage` int COMMENT '最大槓桿, 不超過symbols.max_leverage',
+            `daily_loss_limit` double COMMENT '當日最大虧損',
+            `kill_switch` tinyint(1) NOT NULL DEFAULT 0 COMMENT '1 => 禁止開倉',
+            `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            `updated_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            UNIQUE KEY `risk_rules_scope_target` (`scope`, `target`)
+        )"#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "DROP TABLE `risk_rules`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
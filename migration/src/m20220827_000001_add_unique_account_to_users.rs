@@ -0,0 +1,35 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //舊版admin bootstrap可能已重複建立帳號, 保留id最小的一筆
+        //其餘改名為 帳號#id 並標記刪除, 關聯資料仍指向原帳號
+        let dedupe = r#"
+        UPDATE `users` u
+            INNER JOIN `users` k ON u.account = k.account AND u.id > k.id
+        SET u.account = CONCAT(LEFT(u.account, 29 - CHAR_LENGTH(u.id)), '#', u.id),
+            u.deleted_at = COALESCE(u.deleted_at, NOW())
+        "#;
+        let sql = r#"
+        ALTER TABLE `users`
+            ADD UNIQUE INDEX `users_account_unique` (`account`)
+        "#;
+        for sql in [dedupe, sql] {
+            let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+            manager.get_connection().execute(stmt).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "ALTER TABLE `users` DROP INDEX `users_account_unique`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
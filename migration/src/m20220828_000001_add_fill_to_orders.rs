@@ -0,0 +1,25 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        ALTER TABLE `orders`
+            ADD COLUMN `exec_qty` double NOT NULL DEFAULT 0 COMMENT '已成交數量' AFTER `qty`,
+            ADD COLUMN `avg_price` double NOT NULL DEFAULT 0 COMMENT '成交均價' AFTER `exec_qty`
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "ALTER TABLE `orders` DROP COLUMN `exec_qty`, DROP COLUMN `avg_price`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
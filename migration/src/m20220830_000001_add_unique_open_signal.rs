@@ -0,0 +1,32 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //MySQL沒有partial index, 以只在持倉中才有值的generated column做唯一索引
+        let sql = r#"
+        ALTER TABLE `signal_records`
+            ADD COLUMN `open_strategy_name` varchar(30)
+                GENERATED ALWAYS AS (IF(`state` = 1, `strategy_name`, NULL)) VIRTUAL
+                COMMENT '持倉中的策略名稱, 每個策略只能有一筆' AFTER `strategy_name`,
+            ADD UNIQUE INDEX `signal_records_open_unique` (`open_strategy_name`)
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+        ALTER TABLE `signal_records`
+            DROP INDEX `signal_records_open_unique`,
+            DROP COLUMN `open_strategy_name`
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
@@ -0,0 +1,35 @@
+use sea_orm::ConnectionTrait;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //同時送出的訂閱可能已重複寫入, 保留id最小的一筆
+        let dedupe = r#"
+        DELETE s1 FROM `subscribes` s1
+            INNER JOIN `subscribes` s2
+                ON s1.user_account = s2.user_account
+                AND s1.strategy_name = s2.strategy_name
+                AND s1.id > s2.id
+        "#;
+        let sql = r#"
+        ALTER TABLE `subscribes`
+            ADD UNIQUE INDEX `subscribes_account_strategy_unique` (`user_account`, `strategy_name`)
+        "#;
+        for sql in [dedupe, sql] {
+            let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+            manager.get_connection().execute(stmt).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = "ALTER TABLE `subscribes` DROP INDEX `subscribes_account_strategy_unique`";
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
@@ -0,0 +1,220 @@
+use crate::domain::{
+    is_open_position, KillSwitch, OrderContainer, OrderErrorQuery, OrderErrorSort, OrderExport,
+    OrderQuery, OrderSort, PnlQuery, RiskScope, SaveRiskRule,
+};
+use axum::{
+    extract::{Extension, Path, Query},
+    response::IntoResponse,
+};
+use futures::StreamExt;
+use pkg::{
+    error::{AppError, AppResult},
+    export::{self, ExportQuery},
+    extract::ValidatedJson,
+    jwt::Claims,
+    page::PageQuery,
+    rbac::{Permission, Role},
+    responder::{ok, paged, Detail},
+};
+use std::sync::Arc;
+use validator::Validate;
+
+/**
+ * 是否可查看所有用戶的訂單
+ */
+fn can_read_all(claims: &Claims) -> bool {
+    matches!(Role::from_i8(claims.role), Some(role) if role.has(Permission::OrdersReadAll))
+}
+
+/**
+ * 一般用戶只能查自己的訂單, admin可指定account或查全部
+ * account為查詢條件的帳號欄位
+ */
+fn restrict_account(account: &mut Option<String>, claims: Claims) -> AppResult<()> {
+    if can_read_all(&claims) {
+        return Ok(());
+    }
+    match account.as_deref() {
+        Some(requested) if requested != claims.account => {
+            Err(AppError::forbidden("Permission error"))
+        }
+        _ => {
+            *account = Some(claims.account);
+            Ok(())
+        }
+    }
+}
+
+/**
+ * 訂單列表
+ */
+pub async fn list_orders(
+    page: PageQuery<OrderSort>,
+    Query(mut query): Query<OrderQuery>,
+    claims: Claims,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    restrict_account(&mut query.account, claims)?;
+
+    let list = c.order_ucase.list(query, page).await?;
+
+    Ok(paged(list))
+}
+
+/**
+ * 匯出訂單 CSV / XLSX, 篩選條件同訂單列表
+ */
+pub async fn export_orders(
+    Query(mut query): Query<OrderQuery>,
+    Query(export): Query<ExportQuery>,
+    claims: Claims,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    export.validate()?;
+    restrict_account(&mut query.account, claims)?;
+
+    let rows = c.order_ucase.export(query).map(|row| row.map(OrderExport));
+    export::respond(rows, &export, "orders").await
+}
+
+/**
+ * 訂單明細, 含對應的開倉/平倉單
+ */
+pub async fn get_order(
+    Path(order_link_id): Path<String>,
+    claims: Claims,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let detail = c
+        .order_ucase
+        .get_detail(order_link_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Order not found"))?;
+
+    //不透露其他用戶的訂單是否存在
+    if detail.order.user_account != claims.account && !can_read_all(&claims) {
+        return Err(AppError::not_found("Order not found"));
+    }
+
+    Ok(ok(detail))
+}
+
+/**
+ * 持倉的未實現盈虧, 由呼叫端提供標記價格
+ */
+pub async fn get_unrealised_pnl(
+    Path(order_link_id): Path<String>,
+    Query(query): Query<PnlQuery>,
+    claims: Claims,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+
+    let order = c
+        .order_ucase
+        .get_by_link_id(order_link_id)
+        .await?
+        .filter(|order| order.user_account == claims.account || can_read_all(&claims))
+        .ok_or_else(|| AppError::not_found("Order not found"))?;
+
+    if !is_open_position(&order) {
+        return Err(AppError::validation("Order is not an open position"));
+    }
+
+    let res = c
+        .order_ucase
+        .unrealised_pnl(order, query.mark_price)
+        .await?;
+
+    Ok(ok(res))
+}
+
+/**
+ * 下單錯誤與風控拒絕紀錄, 一般用戶只能查自己的
+ */
+pub async fn list_order_errors(
+    page: PageQuery<OrderErrorSort>,
+    Query(mut query): Query<OrderErrorQuery>,
+    claims: Claims,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    restrict_account(&mut query.account, claims)?;
+
+    let list = c.order_ucase.list_errors(query, page).await?;
+
+    Ok(paged(list))
+}
+
+/**
+ * 風控規則列表
+ */
+pub async fn list_risk_rules(
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let list = c.risk_ucase.list_rules().await?;
+    Ok(ok(list))
+}
+
+/**
+ * 確認規則的用戶或策略存在
+ */
+async fn check_target(c: &OrderContainer, scope: RiskScope, target: String) -> AppResult<()> {
+    if c.risk_ucase.target_exists(scope, target).await? {
+        return Ok(());
+    }
+    match scope {
+        RiskScope::User => Err(AppError::not_found("User not found")),
+        RiskScope::Strategy => Err(AppError::not_found("Strategy not found")),
+    }
+}
+
+/**
+ * 新增或覆蓋用戶/策略的風控規則
+ */
+pub async fn save_risk_rule(
+    Path((scope, target)): Path<(RiskScope, String)>,
+    ValidatedJson(payload): ValidatedJson<SaveRiskRule>,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    check_target(&c, scope, target.clone()).await?;
+
+    let rule = c.risk_ucase.save_rule(scope, target, payload).await?;
+    Ok(ok(rule))
+}
+
+/**
+ * 啟用/解除kill switch, 啟用後不再開新倉, 平倉不受影響
+ */
+pub async fn set_kill_switch(
+    Path((scope, target)): Path<(RiskScope, String)>,
+    ValidatedJson(payload): ValidatedJson<KillSwitch>,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    check_target(&c, scope, target.clone()).await?;
+
+    let rule = c
+        .risk_ucase
+        .set_kill_switch(scope, target, payload.enabled)
+        .await?;
+    Ok(ok(rule))
+}
+
+/**
+ * 刪除風控規則
+ */
+pub async fn delete_risk_rule(
+    Path((scope, target)): Path<(RiskScope, String)>,
+    Extension(c): Extension<Arc<OrderContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let model = c
+        .risk_ucase
+        .get_rule(scope, target)
+        .await?
+        .ok_or_else(|| AppError::not_found("Risk rule not found"))?;
+
+    c.risk_ucase.delete_rule(model).await?;
+    Ok(ok(Detail("Risk rule deleted".to_owned())))
+}
@@ -0,0 +1,1330 @@
+use anyhow::{anyhow, Result};
+use axum::async_trait;
+use chrono::{DateTime, Local};
+use chrono_tz::Tz;
+use entity::{
+    order_errors::{self, ActiveModel as OrderErrorActiveModel, Model as OrderErrorModel},
+    orders::{self, ActiveModel as OrderActiveModel, Model as OrderModel},
+    risk_rules::{ActiveModel as RiskRuleActiveModel, Model as RiskRuleModel},
+    subscribes::Model as SubscribeModel,
+    symbols::Model as SymbolModel,
+    users::Model as UserModel,
+};
+use pkg::{
+    exchange::{OrderStatus, OrderType, Side},
+    export::{format_dt, Cell, ExportRow, RowReceiver, RowSender},
+    eztime::{self, validate_date},
+    page::{Page, PageQuery, SortField},
+    pnl::{self, Fill},
+    responder::Data,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use validator::{Validate, ValidationError};
+
+/**
+ * Traits
+ */
+#[async_trait]
+pub trait OrderRepository: Send + Sync {
+    async fn list_subscribers(&self, strategy_name: String) -> Result<Vec<SubscribeModel>>;
+    async fn get_user(&self, account: String) -> Result<Option<UserModel>>;
+    async fn save_secret_key(&self, account: String, secret_key: String) -> Result<()>;
+    async fn get_symbol(&self, name: String) -> Result<Option<SymbolModel>>;
+    async fn get_open_order(
+        &self,
+        account: String,
+        strategy_name: String,
+    ) -> Result<Option<OrderModel>>;
+    async fn get_by_link_id(&self, order_link_id: String) -> Result<Option<OrderModel>>;
+    async fn list_pending(&self) -> Result<Vec<OrderModel>>;
+    async fn create(&self, active: OrderActiveModel) -> Result<OrderModel>;
+    async fn create_error(&self, active: OrderErrorActiveModel) -> Result<()>;
+    async fn transition(&self, changes: Vec<StateChange>) -> Result<()>;
+    async fn list(&self, query: OrderQuery, page: PageQuery<OrderSort>)
+        -> Result<Page<OrderModel>>;
+    async fn get_counterpart(&self, model: &OrderModel) -> Result<Option<OrderModel>>;
+    async fn save_pnl(&self, order_link_ids: Vec<String>, pnl: f64) -> Result<()>;
+    async fn save_order_id(&self, order_link_id: String, order_id: String) -> Result<()>;
+    async fn save_fill(&self, order_link_id: String, exec_qty: f64, avg_price: f64) -> Result<()>;
+    async fn save_reconcile_failures(&self, order_link_id: String, failures: i32) -> Result<()>;
+    async fn export(&self, query: OrderQuery, tx: RowSender<OrderModel>) -> Result<()>;
+    async fn list_errors(
+        &self,
+        query: OrderErrorQuery,
+        page: PageQuery<OrderErrorSort>,
+    ) -> Result<Page<OrderErrorModel>>;
+}
+
+#[async_trait]
+pub trait PnlUsecase: Send + Sync {
+    async fn realise(&self, close: &OrderModel) -> Result<Option<Decimal>>;
+    async fn unrealised(&self, open: &OrderModel, mark_price: Decimal) -> Result<Decimal>;
+}
+
+#[async_trait]
+pub trait RiskRepository: Send + Sync {
+    async fn list_rules(&self) -> Result<Vec<RiskRuleModel>>;
+    async fn get_rule(&self, scope: RiskScope, target: String) -> Result<Option<RiskRuleModel>>;
+    async fn get_rules_for(
+        &self,
+        account: String,
+        strategy_name: String,
+    ) -> Result<Vec<RiskRuleModel>>;
+    async fn save_rule(&self, active: RiskRuleActiveModel) -> Result<RiskRuleModel>;
+    async fn delete_rule(&self, model: RiskRuleModel) -> Result<()>;
+    async fn target_exists(&self, scope: RiskScope, target: String) -> Result<bool>;
+    async fn list_open_positions(&self, account: String) -> Result<Vec<OrderModel>>;
+    async fn list_closed_since(
+        &self,
+        account: String,
+        since: DateTime<Local>,
+    ) -> Result<Vec<OrderModel>>;
+}
+
+#[async_trait]
+pub trait RiskUsecase: Send + Sync {
+    async fn check(&self, intent: &OrderIntent) -> Result<Option<Rejection>>;
+    async fn list_rules(&self) -> Result<Vec<RiskRuleInfo>>;
+    async fn get_rule(&self, scope: RiskScope, target: String) -> Result<Option<RiskRuleModel>>;
+    async fn target_exists(&self, scope: RiskScope, target: String) -> Result<bool>;
+    async fn save_rule(
+        &self,
+        scope: RiskScope,
+        target: String,
+        payload: SaveRiskRule,
+    ) -> Result<RiskRuleInfo>;
+    async fn set_kill_switch(
+        &self,
+        scope: RiskScope,
+        target: String,
+        enabled: bool,
+    ) -> Result<RiskRuleInfo>;
+    async fn delete_rule(&self, model: RiskRuleModel) -> Result<()>;
+}
+
+#[async_trait]
+pub trait OrderUsecase: Send + Sync {
+    async fn dispatch(&self, signal: Signal) -> Result<DispatchReport>;
+    async fn reconcile(
+        &self,
+        timeout: chrono::Duration,
+        max_failures: i32,
+    ) -> Result<ReconcileReport>;
+    async fn list(&self, query: OrderQuery, page: PageQuery<OrderSort>) -> Result<Page<OrderInfo>>;
+    async fn get_detail(&self, order_link_id: String) -> Result<Option<OrderDetail>>;
+    async fn unrealised_pnl(&self, order: OrderModel, mark_price: f64) -> Result<UnrealisedPnl>;
+    async fn get_by_link_id(&self, order_link_id: String) -> Result<Option<OrderModel>>;
+    fn export(&self, query: OrderQuery) -> RowReceiver<OrderModel>;
+    async fn list_errors(
+        &self,
+        query: OrderErrorQuery,
+        page: PageQuery<OrderErrorSort>,
+    ) -> Result<Page<OrderErrorInfo>>;
+}
+
+/**
+ * Extension container
+ */
+pub struct OrderContainer {
+    pub order_ucase: Arc<dyn OrderUsecase>,
+    pub risk_ucase: Arc<dyn RiskUsecase>,
+}
+
+impl OrderContainer {
+    pub fn new(
+        order_ucase: Arc<dyn OrderUsecase>,
+        risk_ucase: Arc<dyn RiskUsecase>,
+    ) -> Arc<OrderContainer> {
+        Arc::new(OrderContainer {
+            order_ucase,
+            risk_ucase,
+        })
+    }
+}
+
+/**
+ * order action
+ */
+pub const ACTION_OPEN: i8 = 1;
+pub const ACTION_CLOSE: i8 = 2;
+
+/**
+ * 訂單狀態 0=>排隊中 1=>部分持倉 2=>全部持倉 3=>已平倉 4=>資料已確認 5=>超時.取消訂單
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Queued = 0,
+    PartiallyFilled = 1,
+    Filled = 2,
+    Closed = 3,
+    Confirmed = 4,
+    Cancelled = 5,
+}
+
+impl OrderState {
+    pub fn from_i8(state: i8) -> Option<OrderState> {
+        match state {
+            0 => Some(OrderState::Queued),
+            1 => Some(OrderState::PartiallyFilled),
+            2 => Some(OrderState::Filled),
+            3 => Some(OrderState::Closed),
+            4 => Some(OrderState::Confirmed),
+            5 => Some(OrderState::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn to_i8(&self) -> i8 {
+        *self as i8
+    }
+
+    /**
+     * 對應交易所的訂單狀態, 過渡中的狀態回傳None
+     */
+    pub fn from_exchange(status: &OrderStatus) -> Option<OrderState> {
+        match status {
+            OrderStatus::Created | OrderStatus::New | OrderStatus::Untriggered => {
+                Some(OrderState::Queued)
+            }
+            OrderStatus::PartiallyFilled => Some(OrderState::PartiallyFilled),
+            OrderStatus::Filled => Some(OrderState::Filled),
+            OrderStatus::Cancelled | OrderStatus::Rejected | OrderStatus::Deactivated => {
+                Some(OrderState::Cancelled)
+            }
+            _ => None,
+        }
+    }
+
+    /**
+     * 是否已結束, 不需要再向交易所查詢
+     */
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderState::Confirmed | OrderState::Cancelled)
+    }
+
+    /**
+     * 合法的狀態轉換
+     */
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        use OrderState::*;
+        matches!(
+            (self, next),
+            (Queued, PartiallyFilled)
+                | (Queued, Filled)
+                | (Queued, Cancelled)
+                | (PartiallyFilled, Filled)
+                | (PartiallyFilled, Cancelled)
+                | (PartiallyFilled, Closed)
+                | (Filled, Closed)
+                | (Filled, Confirmed)
+                | (Closed, Confirmed)
+        )
+    }
+
+    pub fn transition(&self, next: OrderState) -> Result<OrderState> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(anyhow!(
+                "illegal order state transition {:?} -> {:?}",
+                self,
+                next
+            ))
+        }
+    }
+}
+
+/**
+ * 狀態變更, 以from做為樂觀鎖
+ */
+#[derive(Debug, Clone)]
+pub struct StateChange {
+    pub order_link_id: String,
+    pub from: OrderState,
+    pub to: OrderState,
+}
+
+impl StateChange {
+    pub fn new(order_link_id: &str, from: OrderState, to: OrderState) -> Result<StateChange> {
+        from.transition(to)?;
+        Ok(StateChange {
+            order_link_id: order_link_id.to_owned(),
+            from,
+            to,
+        })
+    }
+}
+
+/**
+ * 策略訊號, 由signal模組傳入
+ */
+#[derive(Debug, Clone)]
+pub struct Signal {
+    pub strategy_name: String,
+    pub symbol: String,
+    pub action: i8,
+    pub side: i8,
+    pub price: f64,
+}
+
+/**
+ * 下單結果統計
+ */
+#[derive(Debug, Default)]
+pub struct DispatchReport {
+    pub placed: usize,
+    pub skipped: usize,
+    pub rejected: usize,
+    pub failed: usize,
+}
+
+/**
+ * 對帳結果統計
+ */
+#[derive(Debug, Default)]
+pub struct ReconcileReport {
+    pub checked: usize,
+    pub advanced: usize,
+    pub cancelled: usize,
+    pub failed: usize,
+}
+
+/**
+ * 訂單查詢條件, account為None時查全部(需要 orders:read_all 權限)
+ * start_date / end_date 可為 Y-m-d 或 Y-m-d H:M:S, 只有日期時end_date包含當天
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct OrderQuery {
+    #[validate(length(min = 4, max = 30))]
+    pub account: Option<String>,
+    #[validate(length(min = 1, max = 30))]
+    pub symbol: Option<String>,
+    #[validate(length(min = 1, max = 30))]
+    pub strategy_name: Option<String>,
+    #[validate(range(min = 1, max = 2))]
+    pub side: Option<i8>,
+    #[validate(range(min = 1, max = 2))]
+    pub action: Option<i8>,
+    #[validate(range(min = 0, max = 5))]
+    pub state: Option<i8>,
+    #[validate(custom = "validate_date")]
+    pub start_date: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+}
+
+impl OrderQuery {
+    /**
+     * 建立時間區間 [start, end)
+     */
+    pub fn created_range(&self) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        eztime::range(self.start_date.as_deref(), self.end_date.as_deref())
+    }
+}
+
+/**
+ * 訂單列表可排序的欄位
+ */
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Symbol,
+    StrategyName,
+    State,
+}
+
+impl SortField<orders::Entity> for OrderSort {
+    fn column(&self) -> orders::Column {
+        match self {
+            OrderSort::CreatedAt => orders::Column::CreatedAt,
+            OrderSort::UpdatedAt => orders::Column::UpdatedAt,
+            OrderSort::Symbol => orders::Column::Symbol,
+            OrderSort::StrategyName => orders::Column::StrategyName,
+            OrderSort::State => orders::Column::State,
+        }
+    }
+}
+
+/**
+ * Order info
+ */
+#[derive(Serialize, Debug)]
+pub struct OrderInfo {
+    pub order_link_id: String,
+    pub order_id: String,
+    pub user_account: String,
+    pub strategy_name: String,
+    pub symbol: String,
+    pub side: i8,
+    pub action: i8,
+    pub state: i8,
+    pub price: f64,
+    pub qty: f64,
+    pub exec_qty: f64,
+    pub avg_price: f64,
+    pub order_type: String,
+    pub reduce_only: bool,
+    pub profit_and_loss: f64,
+    pub rel_order_link_id: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Data for OrderInfo {}
+
+impl From<OrderModel> for OrderInfo {
+    fn from(model: OrderModel) -> Self {
+        OrderInfo {
+            order_link_id: model.order_link_id,
+            order_id: model.order_id,
+            user_account: model.user_account,
+            strategy_name: model.strategy_name,
+            symbol: model.symbol,
+            side: model.side,
+            action: model.action,
+            state: model.state,
+            price: model.price,
+            qty: model.qty,
+            exec_qty: model.exec_qty,
+            avg_price: model.avg_price,
+            order_type: model.order_type,
+            reduce_only: model.reduce_only == Some(1),
+            profit_and_loss: model.profit_and_loss,
+            rel_order_link_id: model.rel_order_link_id,
+            created_at: model.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            updated_at: model.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+/**
+ * 訂單匯出的資料列
+ */
+pub struct OrderExport(pub OrderModel);
+
+impl ExportRow for OrderExport {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "order_link_id",
+            "order_id",
+            "user_account",
+            "strategy_name",
+            "symbol",
+            "side",
+            "action",
+            "state",
+            "order_type",
+            "price",
+            "qty",
+            "exec_qty",
+            "avg_price",
+            "reduce_only",
+            "profit_and_loss",
+            "rel_order_link_id",
+            "created_at",
+            "updated_at",
+        ]
+    }
+
+    fn cells(&self, tz: &Tz) -> Vec<Cell> {
+        let order = &self.0;
+        let side = Side::from_i8(order.side).map(|side| side.to_string());
+        let action = match order.action {
+            ACTION_OPEN => "Open",
+            ACTION_CLOSE => "Close",
+            _ => "",
+        };
+        let state = OrderState::from_i8(order.state).map(|state| format!("{:?}", state));
+        vec![
+            order.order_link_id.as_str().into(),
+            order.order_id.as_str().into(),
+            order.user_account.as_str().into(),
+            order.strategy_name.as_str().into(),
+            order.symbol.as_str().into(),
+            side.unwrap_or_default().into(),
+            action.into(),
+            state.unwrap_or_default().into(),
+            order.order_type.as_str().into(),
+            order.price.into(),
+            order.qty.into(),
+            order.exec_qty.into(),
+            order.avg_price.into(),
+            (order.reduce_only == Some(1)).to_string().into(),
+            order.profit_and_loss.into(),
+            order.rel_order_link_id.as_str().into(),
+            format_dt(&order.created_at, tz).into(),
+            format_dt(&order.updated_at, tz).into(),
+        ]
+    }
+}
+
+/**
+ * 訂單明細, 含對應的開倉/平倉單
+ */
+#[derive(Serialize, Debug)]
+pub struct OrderDetail {
+    #[serde(flatten)]
+    pub order: OrderInfo,
+    pub linked_order: Option<OrderInfo>,
+}
+
+impl Data for OrderDetail {}
+
+/**
+ * 是否為持倉中的開倉單
+ */
+pub fn is_open_position(model: &OrderModel) -> bool {
+    model.action == ACTION_OPEN
+        && matches!(
+            OrderState::from_i8(model.state),
+            Some(OrderState::PartiallyFilled | OrderState::Filled)
+        )
+}
+
+/**
+ * 訂單的成交資料, 限價單以maker費率計算, 其餘以taker費率計算
+ * 以交易所回報的成交數量與均價為準, 尚未回報時使用下單的價格與數量
+ */
+pub fn fill_of(order: &OrderModel, symbol: &SymbolModel) -> Fill {
+    let fee_rate = if order.order_type == OrderType::Limit.to_string() {
+        pnl::fee_rate(&symbol.maker_fee)
+    } else {
+        pnl::fee_rate(&symbol.taker_fee)
+    };
+    if order.exec_qty > 0.0 {
+        let price = if order.avg_price > 0.0 {
+            order.avg_price
+        } else {
+            order.price
+        };
+        return Fill::new(price, order.exec_qty, fee_rate);
+    }
+    Fill::new(order.price, order.qty, fee_rate)
+}
+
+/**
+ * 未實現盈虧查詢
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct PnlQuery {
+    #[validate(custom = "validate_positive")]
+    pub mark_price: f64,
+}
+
+fn validate_positive(price: f64) -> Result<(), ValidationError> {
+    if price > 0.0 && price.is_finite() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive"))
+    }
+}
+
+/**
+ * 未實現盈虧
+ */
+#[derive(Serialize, Debug)]
+pub struct UnrealisedPnl {
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: i8,
+    pub qty: f64,
+    pub entry_price: f64,
+    pub mark_price: f64,
+    pub unrealised_pnl: f64,
+}
+
+impl Data for UnrealisedPnl {}
+
+/**
+ * 風控拒絕寫入order_errors時func的前綴, 例如 risk::max_leverage
+ */
+pub const RISK_FUNC_PREFIX: &str = "risk::";
+
+/**
+ * 下單錯誤查詢條件, rejected => 只查風控拒絕
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct OrderErrorQuery {
+    #[validate(length(min = 4, max = 30))]
+    pub account: Option<String>,
+    #[validate(length(min = 1, max = 200))]
+    pub func: Option<String>,
+    pub rejected: Option<bool>,
+    #[validate(custom = "validate_date")]
+    pub start_date: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+}
+
+impl OrderErrorQuery {
+    /**
+     * 建立時間區間 [start, end)
+     */
+    pub fn created_range(&self) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        eztime::range(self.start_date.as_deref(), self.end_date.as_deref())
+    }
+}
+
+/**
+ * 下單錯誤列表可排序的欄位
+ */
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderErrorSort {
+    #[default]
+    CreatedAt,
+    Func,
+}
+
+impl SortField<order_errors::Entity> for OrderErrorSort {
+    fn column(&self) -> order_errors::Column {
+        match self {
+            OrderErrorSort::CreatedAt => order_errors::Column::CreatedAt,
+            OrderErrorSort::Func => order_errors::Column::Func,
+        }
+    }
+}
+
+/**
+ * Order error info
+ */
+#[derive(Serialize, Debug)]
+pub struct OrderErrorInfo {
+    pub id: i64,
+    pub action: i8,
+    pub func: String,
+    pub msg: String,
+    pub user_account: Option<String>,
+    pub rejected: bool,
+    pub created_at: String,
+}
+
+impl Data for OrderErrorInfo {}
+
+impl From<OrderErrorModel> for OrderErrorInfo {
+    fn from(model: OrderErrorModel) -> Self {
+        OrderErrorInfo {
+            id: model.id,
+            action: model.action,
+            rejected: model.func.starts_with(RISK_FUNC_PREFIX),
+            func: model.func,
+            msg: model.msg,
+            user_account: model.user_account,
+            created_at: model.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+/**
+ * 風控規則範圍 1 => 用戶 2 => 策略
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskScope {
+    User = 1,
+    Strategy = 2,
+}
+
+impl RiskScope {
+    pub fn from_i8(scope: i8) -> Option<RiskScope> {
+        match scope {
+            1 => Some(RiskScope::User),
+            2 => Some(RiskScope::Strategy),
+            _ => None,
+        }
+    }
+
+    pub fn to_i8(&self) -> i8 {
+        *self as i8
+    }
+}
+
+impl fmt::Display for RiskScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RiskScope::User => write!(f, "user"),
+            RiskScope::Strategy => write!(f, "strategy"),
+        }
+    }
+}
+
+/**
+ * 新增/更新風控規則, 未指定的限制表示不限制
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct SaveRiskRule {
+    #[validate(range(min = 1, max = 1000))]
+    pub max_open_positions: Option<i32>,
+    #[validate(custom = "validate_positive")]
+    pub max_notional: Option<f64>,
+    #[validate(range(min = 1, max = 125))]
+    pub max_leverage: Option<i32>,
+    #[validate(custom = "validate_positive")]
+    pub daily_loss_limit: Option<f64>,
+    #[serde(default)]
+    pub kill_switch: bool,
+}
+
+/**
+ * 切換kill switch
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct KillSwitch {
+    pub enabled: bool,
+}
+
+/**
+ * Risk rule info
+ */
+#[derive(Serialize, Debug)]
+pub struct RiskRuleInfo {
+    pub scope: String,
+    pub target: String,
+    pub max_open_positions: Option<i32>,
+    pub max_notional: Option<f64>,
+    pub max_leverage: Option<i32>,
+    pub daily_loss_limit: Option<f64>,
+    pub kill_switch: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Data for RiskRuleInfo {}
+
+impl From<RiskRuleModel> for RiskRuleInfo {
+    fn from(model: RiskRuleModel) -> Self {
+        RiskRuleInfo {
+            scope: RiskScope::from_i8(model.scope)
+                .map(|scope| scope.to_string())
+                .unwrap_or_default(),
+            target: model.target,
+            max_open_positions: model.max_open_positions,
+            max_notional: model.max_notional,
+            max_leverage: model.max_leverage,
+            daily_loss_limit: model.daily_loss_limit,
+            kill_switch: model.kill_switch == 1,
+            created_at: model.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            updated_at: model.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+/**
+ * 用戶與策略規則合併後的限制, 同一項限制取較嚴格的值
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RiskLimits {
+    pub max_open_positions: Option<i32>,
+    pub max_notional: Option<f64>,
+    pub max_leverage: Option<i32>,
+    pub daily_loss_limit: Option<f64>,
+    //啟用kill switch的規則
+    pub kill_switch: Option<(RiskScope, String)>,
+}
+
+fn stricter<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b < a { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+impl RiskLimits {
+    pub fn merge(rules: &[RiskRuleModel]) -> RiskLimits {
+        rules.iter().fold(RiskLimits::default(), |limits, rule| {
+            let kill_switch = match RiskScope::from_i8(rule.scope) {
+                Some(scope) if rule.kill_switch == 1 => Some((scope, rule.target.clone())),
+                _ => None,
+            };
+            RiskLimits {
+                max_open_positions: stricter(limits.max_open_positions, rule.max_open_positions),
+                max_notional: stricter(limits.max_notional, rule.max_notional),
+                max_leverage: stricter(limits.max_leverage, rule.max_leverage),
+                daily_loss_limit: stricter(limits.daily_loss_limit, rule.daily_loss_limit),
+                kill_switch: limits.kill_switch.or(kill_switch),
+            }
+        })
+    }
+
+    /**
+     * 是否需要查詢持倉
+     */
+    pub fn needs_positions(&self) -> bool {
+        self.max_open_positions.is_some() || self.max_notional.is_some()
+    }
+}
+
+/**
+ * 準備送出的開倉單
+ */
+#[derive(Debug, Clone)]
+pub struct OrderIntent {
+    pub account: String,
+    pub strategy_name: String,
+    pub symbol: String,
+    pub price: f64,
+    pub qty: f64,
+    pub leverage: i16,
+    //symbols.max_leverage
+    pub symbol_max_leverage: i32,
+}
+
+/**
+ * 用戶目前的曝險
+ */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Exposure {
+    pub open_positions: usize,
+    //同一合約未平倉的名目價值
+    pub symbol_notional: Decimal,
+    //當日已實現盈虧
+    pub daily_pnl: Decimal,
+}
+
+impl Exposure {
+    /**
+     * positions => 未平倉的開倉單, closes => 當日的平倉單
+     */
+    pub fn new(symbol: &str, positions: &[OrderModel], closes: &[OrderModel]) -> Exposure {
+        let symbol_notional = positions
+            .iter()
+            .filter(|order| order.symbol == symbol)
+            .map(Exposure::notional_of)
+            .sum();
+        let daily_pnl = closes
+            .iter()
+            .map(|order| pnl::decimal(order.profit_and_loss))
+            .sum();
+
+        Exposure {
+            open_positions: positions.len(),
+            symbol_notional,
+            daily_pnl,
+        }
+    }
+
+    /**
+     * 有成交時以實際成交計算, 部分成交剩餘未成交的數量不計
+     * 尚未回報成交時, 排隊中的訂單以下單的價格與數量計算, 已結束的訂單不計
+     */
+    fn notional_of(order: &OrderModel) -> Decimal {
+        if order.exec_qty > 0.0 {
+            return pnl::decimal(order.avg_price) * pnl::decimal(order.exec_qty);
+        }
+        match OrderState::from_i8(order.state) {
+            Some(OrderState::Queued | OrderState::PartiallyFilled | OrderState::Filled) => {
+                pnl::decimal(order.price) * pnl::decimal(order.qty)
+            }
+            _ => Decimal::ZERO,
+        }
+    }
+}
+
+/**
+ * 風控拒絕原因
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    KillSwitch {
+        scope: RiskScope,
+        target: String,
+    },
+    MaxLeverage {
+        leverage: i16,
+        limit: i32,
+    },
+    MaxOpenPositions {
+        current: usize,
+        limit: i32,
+    },
+    MaxNotional {
+        symbol: String,
+        notional: f64,
+        limit: f64,
+    },
+    DailyLossLimit {
+        loss: f64,
+        limit: f64,
+    },
+}
+
+impl Rejection {
+    /**
+     * 觸發的規則名稱, 寫入order_errors.func
+     */
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Rejection::KillSwitch { .. } => "kill_switch",
+            Rejection::MaxLeverage { .. } => "max_leverage",
+            Rejection::MaxOpenPositions { .. } => "max_open_positions",
+            Rejection::MaxNotional { .. } => "max_notional",
+            Rejection::DailyLossLimit { .. } => "daily_loss_limit",
+        }
+    }
+
+    pub fn func(&self) -> String {
+        format!("{}{}", RISK_FUNC_PREFIX, self.rule())
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::KillSwitch { scope, target } => {
+                write!(f, "kill switch is enabled for {} {}", scope, target)
+            }
+            Rejection::MaxLeverage { leverage, limit } => {
+                write!(f, "leverage {}x exceeds the limit {}x", leverage, limit)
+            }
+            Rejection::MaxOpenPositions { current, limit } => {
+                write!(f, "open positions {} reached the limit {}", current, limit)
+            }
+            Rejection::MaxNotional {
+                symbol,
+                notional,
+                limit,
+            } => write!(
+                f,
+                "notional {} on {} exceeds the limit {}",
+                notional, symbol, limit
+            ),
+            Rejection::DailyLossLimit { loss, limit } => {
+                write!(f, "daily loss {} reached the limit {}", loss, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+/**
+ * 開倉前檢查, 依序為 kill switch, 槓桿, 持倉數, 名目價值, 當日虧損
+ * 槓桿上限取規則與symbols.max_leverage較小者
+ */
+pub fn evaluate(
+    limits: &RiskLimits,
+    intent: &OrderIntent,
+    exposure: &Exposure,
+) -> Option<Rejection> {
+    if let Some((scope, target)) = &limits.kill_switch {
+        return Some(Rejection::KillSwitch {
+            scope: *scope,
+            target: target.clone(),
+        });
+    }
+
+    let symbol_max = Some(intent.symbol_max_leverage).filter(|max| *max > 0);
+    if let Some(limit) = stricter(limits.max_leverage, symbol_max) {
+        if intent.leverage as i32 > limit {
+            return Some(Rejection::MaxLeverage {
+                leverage: intent.leverage,
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = limits.max_open_positions {
+        if exposure.open_positions >= limit.max(0) as usize {
+            return Some(Rejection::MaxOpenPositions {
+                current: exposure.open_positions,
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = limits.max_notional {
+        let notional =
+            exposure.symbol_notional + pnl::decimal(intent.price) * pnl::decimal(intent.qty);
+        if notional > pnl::decimal(limit) {
+            return Some(Rejection::MaxNotional {
+                symbol: intent.symbol.clone(),
+                notional: pnl::to_f64(notional),
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = limits.daily_loss_limit {
+        let loss = -exposure.daily_pnl;
+        if loss >= pnl::decimal(limit) {
+            return Some(Rejection::DailyLossLimit {
+                loss: pnl::to_f64(loss),
+                limit,
+            });
+        }
+    }
+
+    None
+}
+
+/**
+ * 依最小單位取整, floor => 無條件捨去, 否則四捨五入
+ */
+pub fn round_step(value: f64, step: f64, floor: bool) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    let steps = value / step;
+    //避免浮點誤差, 例如 0.3 / 0.1 = 2.9999999999999996
+    let steps = if floor {
+        (steps + 1e-9).floor()
+    } else {
+        steps.round()
+    };
+    let decimals = decimals_of(step);
+    let factor = 10f64.powi(decimals);
+    (steps * step * factor).round() / factor
+}
+
+/**
+ * 計算最小單位的小數位數
+ */
+fn decimals_of(step: f64) -> i32 {
+    let mut decimals = 0;
+    let mut value = step;
+    while decimals < 12 && (value - value.round()).abs() > 1e-9 {
+        value *= 10.0;
+        decimals += 1;
+    }
+    decimals
+}
+
+//預設訊號價格與標記價格最多相差2%
+const DEFAULT_PRICE_TOLERANCE: f64 = 0.02;
+
+/**
+ * 訊號價格可偏離標記價格的比例, 由ORDER_PRICE_TOLERANCE設定
+ */
+pub fn price_tolerance() -> f64 {
+    std::env::var("ORDER_PRICE_TOLERANCE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|v: &f64| *v > 0.0)
+        .unwrap_or(DEFAULT_PRICE_TOLERANCE)
+}
+
+/**
+ * 檢查訊號價格與標記價格的差距, 過期或偽造的價格不可下單
+ */
+pub fn check_signal_price(signal_price: f64, mark_price: f64, tolerance: f64) -> Result<()> {
+    if mark_price <= 0.0 {
+        anyhow::bail!("invalid mark price {}", mark_price);
+    }
+    let deviation = (signal_price - mark_price).abs() / mark_price;
+    if deviation > tolerance {
+        anyhow::bail!(
+            "signal price {} deviates {:.2}% from mark price {}",
+            signal_price,
+            deviation * 100.0,
+            mark_price
+        );
+    }
+    Ok(())
+}
+
+/**
+ * 以金額與槓桿計算下單數量
+ */
+pub fn calc_qty(amount: f64, leverage: i16, price: f64, symbol: &SymbolModel) -> Result<f64> {
+    if price <= 0.0 {
+        anyhow::bail!("invalid price {}", price);
+    }
+    let raw = amount * leverage as f64 / price;
+    let qty = round_step(raw, symbol.qty_step, true).min(symbol.max_trading_qty);
+    if qty < symbol.min_trading_qty || qty <= 0.0 {
+        anyhow::bail!(
+            "qty {} is below the minimum trading qty {} of {}",
+            qty,
+            symbol.min_trading_qty,
+            symbol.name
+        );
+    }
+    Ok(qty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_query_range() {
+        let query = OrderQuery {
+            start_date: Some("2022-08-01".to_owned()),
+            end_date: Some("2022-08-31".to_owned()),
+            ..Default::default()
+        };
+        let (start, end) = query.created_range();
+        assert_eq!(
+            start.unwrap().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2022-08-01 00:00:00"
+        );
+        assert_eq!(
+            end.unwrap().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2022-09-01 00:00:00"
+        );
+    }
+
+    fn symbol() -> SymbolModel {
+        SymbolModel {
+            name: "BTCUSDT".to_owned(),
+            alias: "BTCUSDT".to_owned(),
+            status: "Trading".to_owned(),
+            base_currency: "BTC".to_owned(),
+            quote_currency: "USDT".to_owned(),
+            price_scale: 2,
+            taker_fee: "0.0006".to_owned(),
+            maker_fee: "0.0001".to_owned(),
+            funding_interval: 480,
+            max_trading_qty: 100.0,
+            min_trading_qty: 0.001,
+            qty_step: 0.001,
+            post_only_max_trading_qty: "1000".to_owned(),
+            min_price: "0.5".to_owned(),
+            max_price: "999999".to_owned(),
+            tick_size: "0.5".to_owned(),
+            min_leverage: 1,
+            max_leverage: 100,
+            leverage_step: "0.01".to_owned(),
+        }
+    }
+
+    #[test]
+    fn order_state_transitions() {
+        use OrderState::*;
+        assert!(Queued.can_transition_to(Filled));
+        assert!(Queued.can_transition_to(Cancelled));
+        assert!(Filled.can_transition_to(Closed));
+        assert!(Closed.can_transition_to(Confirmed));
+        assert!(!Cancelled.can_transition_to(Filled));
+        assert!(!Confirmed.can_transition_to(Queued));
+        assert!(!Filled.can_transition_to(Queued));
+        assert!(StateChange::new("id", Closed, Filled).is_err());
+        assert_eq!(OrderState::from_i8(5), Some(Cancelled));
+        assert_eq!(
+            OrderState::from_exchange(&OrderStatus::Rejected),
+            Some(Cancelled)
+        );
+    }
+
+    #[test]
+    fn round_to_step() {
+        assert_eq!(round_step(0.3, 0.1, true), 0.3);
+        assert_eq!(round_step(0.0129, 0.001, true), 0.012);
+        assert_eq!(round_step(20000.26, 0.5, false), 20000.5);
+        assert_eq!(round_step(20000.24, 0.5, false), 20000.0);
+    }
+
+    #[test]
+    fn qty_from_amount() {
+        //100 USDT * 10x / 20000 = 0.05 BTC
+        assert_eq!(calc_qty(100.0, 10, 20000.0, &symbol()).unwrap(), 0.05);
+        //too small
+        assert!(calc_qty(1.0, 1, 20000.0, &symbol()).is_err());
+    }
+
+    fn rule(scope: RiskScope, target: &str) -> RiskRuleModel {
+        RiskRuleModel {
+            id: 1,
+            scope: scope.to_i8(),
+            target: target.to_owned(),
+            max_open_positions: None,
+            max_notional: None,
+            max_leverage: None,
+            daily_loss_limit: None,
+            kill_switch: 0,
+            created_at: Local::now(),
+            updated_at: Local::now(),
+        }
+    }
+
+    fn order(symbol: &str, price: f64, qty: f64, pnl: f64) -> OrderModel {
+        OrderModel {
+            order_link_id: "id".to_owned(),
+            order_id: "id".to_owned(),
+            side: 1,
+            symbol: symbol.to_owned(),
+            price,
+            qty,
+            exec_qty: 0.0,
+            avg_price: 0.0,
+            order_type: "Limit".to_owned(),
+            reduce_only: Some(0),
+            kline_time: None,
+            profit_and_loss: pnl,
+            rel_order_id: String::new(),
+            rel_order_link_id: String::new(),
+            user_account: "user01".to_owned(),
+            strategy_name: "btc_1h".to_owned(),
+            action: ACTION_OPEN,
+            state: OrderState::Filled.to_i8(),
+            reconcile_failures: 0,
+            created_at: Local::now(),
+            updated_at: Local::now(),
+        }
+    }
+
+    fn intent(leverage: i16) -> OrderIntent {
+        OrderIntent {
+            account: "user01".to_owned(),
+            strategy_name: "btc_1h".to_owned(),
+            symbol: "BTCUSDT".to_owned(),
+            price: 20000.0,
+            qty: 0.05,
+            leverage,
+            symbol_max_leverage: 100,
+        }
+    }
+
+    #[test]
+    fn signal_price_deviation() {
+        assert!(check_signal_price(20100.0, 20000.0, 0.02).is_ok());
+        assert!(check_signal_price(19600.0, 20000.0, 0.02).is_ok());
+        assert!(check_signal_price(20401.0, 20000.0, 0.02).is_err());
+        assert!(check_signal_price(1.0, 20000.0, 0.02).is_err());
+        assert!(check_signal_price(20000.0, 0.0, 0.02).is_err());
+    }
+
+    #[test]
+    fn fill_from_execution() {
+        //尚未回報成交時使用下單的價格與數量
+        let placed = order("BTCUSDT", 20000.0, 0.05, 0.0);
+        let fill = fill_of(&placed, &symbol());
+        assert_eq!(pnl::to_f64(fill.qty), 0.05);
+        assert_eq!(pnl::to_f64(fill.fee_rate), 0.0001);
+
+        let filled = OrderModel {
+            exec_qty: 0.03,
+            avg_price: 20010.5,
+            ..placed
+        };
+        let fill = fill_of(&filled, &symbol());
+        assert_eq!(pnl::to_f64(fill.qty), 0.03);
+        assert_eq!(pnl::to_f64(fill.price), 20010.5);
+    }
+
+    #[test]
+    fn merge_risk_rules() {
+        let user = RiskRuleModel {
+            max_open_positions: Some(3),
+            max_leverage: Some(20),
+            ..rule(RiskScope::User, "user01")
+        };
+        let strategy = RiskRuleModel {
+            max_leverage: Some(10),
+            daily_loss_limit: Some(100.0),
+            kill_switch: 1,
+            ..rule(RiskScope::Strategy, "btc_1h")
+        };
+
+        let limits = RiskLimits::merge(&[user, strategy]);
+        assert_eq!(limits.max_open_positions, Some(3));
+        assert_eq!(limits.max_leverage, Some(10));
+        assert_eq!(limits.daily_loss_limit, Some(100.0));
+        assert_eq!(limits.max_notional, None);
+        assert_eq!(
+            limits.kill_switch,
+            Some((RiskScope::Strategy, "btc_1h".to_owned()))
+        );
+        assert_eq!(RiskLimits::merge(&[]), RiskLimits::default());
+    }
+
+    #[test]
+    fn evaluate_risk_limits() {
+        let positions = vec![
+            order("BTCUSDT", 20000.0, 0.02, 0.0),
+            order("ETHUSDT", 1500.0, 1.0, 0.0),
+        ];
+        let closes = vec![
+            order("BTCUSDT", 20000.0, 0.01, -60.0),
+            order("BTCUSDT", 20000.0, 0.01, 20.0),
+        ];
+        let exposure = Exposure::new("BTCUSDT", &positions, &closes);
+        assert_eq!(exposure.open_positions, 2);
+        assert_eq!(pnl::to_f64(exposure.symbol_notional), 400.0);
+        assert_eq!(pnl::to_f64(exposure.daily_pnl), -40.0);
+
+        //部分成交只計算已成交的部分, 已取消的不計
+        let partial = OrderModel {
+            exec_qty: 0.01,
+            avg_price: 20100.0,
+            state: OrderState::PartiallyFilled.to_i8(),
+            ..order("BTCUSDT", 20000.0, 0.05, 0.0)
+        };
+        let cancelled = OrderModel {
+            state: OrderState::Cancelled.to_i8(),
+            ..order("BTCUSDT", 20000.0, 0.05, 0.0)
+        };
+        let filled = Exposure::new("BTCUSDT", &[partial, cancelled], &[]);
+        assert_eq!(pnl::to_f64(filled.symbol_notional), 201.0);
+
+        //沒有規則時只檢查合約的槓桿上限
+        let none = RiskLimits::default();
+        assert_eq!(evaluate(&none, &intent(20), &exposure), None);
+        assert_eq!(
+            evaluate(&none, &intent(125), &exposure),
+            Some(Rejection::MaxLeverage {
+                leverage: 125,
+                limit: 100
+            })
+        );
+
+        let kill = RiskLimits {
+            kill_switch: Some((RiskScope::User, "user01".to_owned())),
+            ..Default::default()
+        };
+        let rejection = evaluate(&kill, &intent(1), &exposure).unwrap();
+        assert_eq!(rejection.func(), "risk::kill_switch");
+        assert_eq!(
+            rejection.to_string(),
+            "kill switch is enabled for user user01"
+        );
+
+        let positions_limit = RiskLimits {
+            max_open_positions: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            evaluate(&positions_limit, &intent(10), &exposure).map(|r| r.rule()),
+            Some("max_open_positions")
+        );
+
+        //400 + 20000 * 0.05 = 1400
+        let notional = RiskLimits {
+            max_notional: Some(1000.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            evaluate(&notional, &intent(10), &exposure),
+            Some(Rejection::MaxNotional {
+                symbol: "BTCUSDT".to_owned(),
+                notional: 1400.0,
+                limit: 1000.0
+            })
+        );
+        let notional = RiskLimits {
+            max_notional: Some(1400.0),
+            ..Default::default()
+        };
+        assert_eq!(evaluate(&notional, &intent(10), &exposure), None);
+
+        let loss = RiskLimits {
+            daily_loss_limit: Some(40.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            evaluate(&loss, &intent(10), &exposure).unwrap().to_string(),
+            "daily loss 40 reached the limit 40"
+        );
+        let loss = RiskLimits {
+            daily_loss_limit: Some(50.0),
+            ..Default::default()
+        };
+        assert_eq!(evaluate(&loss, &intent(10), &exposure), None);
+    }
+}
@@ -0,0 +1,126 @@
+mod delivery;
+pub mod domain;
+mod repository;
+pub mod usecase;
+
+pub mod router {
+    use crate::{
+        delivery::http::handler::{
+            delete_risk_rule, export_orders, get_order, get_unrealised_pnl, list_order_errors,
+            list_orders, list_risk_rules, save_risk_rule, set_kill_switch,
+        },
+        domain::{OrderContainer, OrderUsecase},
+        repository::mysql::risk_repo::RiskRepo,
+        usecase::risk_ucase::RiskUcase,
+    };
+    use axum::{
+        extract::Extension,
+        middleware::from_extractor,
+        routing::{get, put},
+        Router,
+    };
+    use pkg::{
+        db::ORM,
+        rbac::{perm, RequirePermission},
+    };
+    use std::sync::Arc;
+
+    /**
+     * new handler, 與訂單引擎共用同一個usecase
+     */
+    pub fn new(orm: Arc<dyn ORM>, order_ucase: Arc<dyn OrderUsecase>) -> Router {
+        let risk_ucase = RiskUcase::new(RiskRepo::new(orm));
+        let order_container = OrderContainer::new(order_ucase, risk_ucase);
+
+        let order_router = Router::new()
+            .route("/", get(list_orders))
+            .route("/export", get(export_orders))
+            .route("/errors", get(list_order_errors))
+            .route("/:order_link_id", get(get_order))
+            .route("/:order_link_id/pnl", get(get_unrealised_pnl));
+
+        //需要 risk:manage 權限
+        let risk_router = Router::new()
+            .route("/rules", get(list_risk_rules))
+            .route(
+                "/rules/:scope/:target",
+                put(save_risk_rule).delete(delete_risk_rule),
+            )
+            .route("/rules/:scope/:target/kill_switch", put(set_kill_switch))
+            .route_layer(from_extractor::<RequirePermission<perm::RiskManage>>());
+
+        Router::new()
+            .nest("/v1/orders", order_router)
+            .nest("/v1/risk", risk_router)
+            .layer(Extension(order_container))
+    }
+}
+
+pub mod engine {
+    use crate::{
+        domain::OrderUsecase,
+        repository::mysql::{order_repo::OrderRepo, risk_repo::RiskRepo},
+        usecase::{order_ucase::OrderUcase, pnl_ucase::PnlUcase, risk_ucase::RiskUcase},
+    };
+
+    use pkg::{db::ORM, exchange::ExchangeFactory};
+    use std::sync::Arc;
+
+    /**
+     * new order engine
+     */
+    pub fn new(orm: Arc<dyn ORM>, exchange: Arc<dyn ExchangeFactory>) -> Arc<dyn OrderUsecase> {
+        let order_repo = OrderRepo::new(orm.clone());
+        let pnl_ucase = PnlUcase::new(order_repo.clone());
+        let risk_ucase = RiskUcase::new(RiskRepo::new(orm));
+        OrderUcase::new(order_repo, pnl_ucase, risk_ucase, exchange)
+    }
+}
+
+pub mod worker {
+    use crate::domain::OrderUsecase;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+
+    //預設每10秒對帳一次
+    const DEFAULT_POLL_INTERVAL: u64 = 10;
+    //預設限價單排隊5分鐘未成交即取消
+    const DEFAULT_ORDER_TIMEOUT: i64 = 300;
+    //預設連續對帳失敗30次即向交易所取消訂單
+    const DEFAULT_RECONCILE_MAX_FAILURES: i32 = 30;
+
+    /**
+     * 背景對帳worker, 間隔與超時秒數由ORDER_POLL_INTERVAL / ORDER_TIMEOUT設定
+     * 連續失敗上限由ORDER_RECONCILE_MAX_FAILURES設定
+     */
+    pub fn spawn(order_ucase: Arc<dyn OrderUsecase>) -> JoinHandle<()> {
+        let interval = std::env::var("ORDER_POLL_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_POLL_INTERVAL);
+        let timeout = std::env::var("ORDER_TIMEOUT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_ORDER_TIMEOUT);
+        let timeout = chrono::Duration::seconds(timeout);
+        let max_failures = std::env::var("ORDER_RECONCILE_MAX_FAILURES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RECONCILE_MAX_FAILURES);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            loop {
+                ticker.tick().await;
+                match order_ucase.reconcile(timeout, max_failures).await {
+                    Ok(report) if report.checked > 0 => {
+                        tracing::debug!("order reconcile: {:?}", report)
+                    }
+                    Ok(_) => (),
+                    Err(e) => tracing::error!("order reconcile failed: {}", e),
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,2 @@
+pub mod order_repo;
+pub mod risk_repo;
@@ -0,0 +1,342 @@
+use crate::domain::{
+    OrderErrorQuery, OrderErrorSort, OrderQuery, OrderRepository, OrderSort, StateChange,
+    ACTION_CLOSE, ACTION_OPEN, RISK_FUNC_PREFIX,
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use entity::{order_errors, orders, prelude::*, subscribes, symbols, users};
+use futures::{SinkExt, StreamExt};
+use pkg::{
+    db::ORM,
+    export::RowSender,
+    page::{paginate, Page, PageQuery},
+};
+use sea_orm::{prelude::*, sea_query::Expr, QueryOrder, TransactionTrait};
+use std::sync::Arc;
+
+/**
+ * 訂單列表與匯出共用的篩選條件
+ */
+fn filter(query: OrderQuery) -> Select<Orders> {
+    let (start, end) = query.created_range();
+    let mut select = Orders::find();
+    if let Some(account) = query.account {
+        select = select.filter(orders::Column::UserAccount.eq(account));
+    }
+    if let Some(symbol) = query.symbol {
+        select = select.filter(orders::Column::Symbol.eq(symbol));
+    }
+    if let Some(strategy_name) = query.strategy_name {
+        select = select.filter(orders::Column::StrategyName.eq(strategy_name));
+    }
+    if let Some(side) = query.side {
+        select = select.filter(orders::Column::Side.eq(side));
+    }
+    if let Some(action) = query.action {
+        select = select.filter(orders::Column::Action.eq(action));
+    }
+    if let Some(state) = query.state {
+        select = select.filter(orders::Column::State.eq(state));
+    }
+    if let Some(start) = start {
+        select = select.filter(orders::Column::CreatedAt.gte(start));
+    }
+    if let Some(end) = end {
+        select = select.filter(orders::Column::CreatedAt.lt(end));
+    }
+    select
+}
+
+pub struct OrderRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl OrderRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn OrderRepository> {
+        Arc::new(OrderRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl OrderRepository for OrderRepo {
+    async fn list_subscribers(
+        &self,
+        strategy_name: String,
+    ) -> anyhow::Result<Vec<subscribes::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = Subscribes::find()
+            .filter(subscribes::Column::StrategyName.eq(strategy_name))
+            .all(db)
+            .await?;
+
+        Ok(models)
+    }
+
+    async fn get_user(&self, account: String) -> anyhow::Result<Option<users::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Users::find()
+            .filter(users::Column::Account.eq(account))
+            .one(db)
+            .await?;
+
+        Ok(model)
+    }
+
+    async fn save_secret_key(&self, account: String, secret_key: String) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        Users::update_many()
+            .col_expr(users::Column::SecretKey, Expr::value(secret_key))
+            .filter(users::Column::Account.eq(account))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_symbol(&self, name: String) -> anyhow::Result<Option<symbols::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Symbols::find_by_id(name).one(db).await?;
+        Ok(model)
+    }
+
+    /**
+     * 取得用戶在此策略尚未平倉的開倉單
+     */
+    async fn get_open_order(
+        &self,
+        account: String,
+        strategy_name: String,
+    ) -> anyhow::Result<Option<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        let open = Orders::find()
+            .filter(orders::Column::UserAccount.eq(account))
+            .filter(orders::Column::StrategyName.eq(strategy_name))
+            .filter(orders::Column::Action.eq(ACTION_OPEN))
+            .filter(orders::Column::State.is_in([0, 1, 2]))
+            .order_by_desc(orders::Column::CreatedAt)
+            .one(db)
+            .await?;
+
+        let open = match open {
+            Some(open) => open,
+            None => return Ok(None),
+        };
+
+        //已有對應的平倉單
+        let closed = Orders::find()
+            .filter(orders::Column::Action.eq(ACTION_CLOSE))
+            .filter(orders::Column::RelOrderLinkId.eq(open.order_link_id.clone()))
+            .count(db)
+            .await?;
+
+        if closed > 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(open))
+    }
+
+    async fn get_by_link_id(&self, order_link_id: String) -> anyhow::Result<Option<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Orders::find_by_id(order_link_id).one(db).await?;
+        Ok(model)
+    }
+
+    /**
+     * 尚未成交完成的訂單
+     */
+    async fn list_pending(&self) -> anyhow::Result<Vec<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = Orders::find()
+            .filter(orders::Column::State.is_in([0, 1]))
+            .order_by_asc(orders::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(models)
+    }
+
+    async fn create(&self, active: orders::ActiveModel) -> anyhow::Result<orders::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.insert(db).await?;
+        Ok(model)
+    }
+
+    async fn create_error(&self, active: order_errors::ActiveModel) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        active.insert(db).await?;
+        Ok(())
+    }
+
+    /**
+     * 在同一個transaction內變更狀態, 任一筆的狀態已被變更則全部rollback
+     */
+    async fn transition(&self, changes: Vec<StateChange>) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        let txn = db.begin().await?;
+
+        for change in changes {
+            let res = Orders::update_many()
+                .col_expr(orders::Column::State, Expr::value(change.to.to_i8()))
+                .filter(orders::Column::OrderLinkId.eq(change.order_link_id.clone()))
+                .filter(orders::Column::State.eq(change.from.to_i8()))
+                .exec(&txn)
+                .await?;
+
+            if res.rows_affected != 1 {
+                txn.rollback().await?;
+                return Err(anyhow!(
+                    "order {} is no longer in state {:?}",
+                    change.order_link_id,
+                    change.from
+                ));
+            }
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        query: OrderQuery,
+        page: PageQuery<OrderSort>,
+    ) -> anyhow::Result<Page<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        paginate(db, filter(query), &page).await
+    }
+
+    /**
+     * 平倉單 => rel_order_link_id 指向的開倉單, 開倉單 => 指向它的平倉單
+     */
+    async fn get_counterpart(
+        &self,
+        model: &orders::Model,
+    ) -> anyhow::Result<Option<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        if model.action == ACTION_CLOSE {
+            if model.rel_order_link_id.is_empty() {
+                return Ok(None);
+            }
+            let res = Orders::find_by_id(model.rel_order_link_id.clone())
+                .one(db)
+                .await?;
+            return Ok(res);
+        }
+
+        let res = Orders::find()
+            .filter(orders::Column::Action.eq(ACTION_CLOSE))
+            .filter(orders::Column::RelOrderLinkId.eq(model.order_link_id.clone()))
+            .order_by_desc(orders::Column::CreatedAt)
+            .one(db)
+            .await?;
+        Ok(res)
+    }
+
+    /**
+     * 已實現盈虧寫回開倉單與平倉單
+     */
+    async fn save_pnl(&self, order_link_ids: Vec<String>, pnl: f64) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        Orders::update_many()
+            .col_expr(orders::Column::ProfitAndLoss, Expr::value(pnl))
+            .filter(orders::Column::OrderLinkId.is_in(order_link_ids))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn save_order_id(&self, order_link_id: String, order_id: String) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        Orders::update_many()
+            .col_expr(orders::Column::OrderId, Expr::value(order_id))
+            .filter(orders::Column::OrderLinkId.eq(order_link_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn save_fill(
+        &self,
+        order_link_id: String,
+        exec_qty: f64,
+        avg_price: f64,
+    ) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        Orders::update_many()
+            .col_expr(orders::Column::ExecQty, Expr::value(exec_qty))
+            .col_expr(orders::Column::AvgPrice, Expr::value(avg_price))
+            .filter(orders::Column::OrderLinkId.eq(order_link_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn save_reconcile_failures(
+        &self,
+        order_link_id: String,
+        failures: i32,
+    ) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        Orders::update_many()
+            .col_expr(orders::Column::ReconcileFailures, Expr::value(failures))
+            .filter(orders::Column::OrderLinkId.eq(order_link_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /**
+     * 逐筆讀取送出, 下載中斷時停止
+     */
+    async fn export(
+        &self,
+        query: OrderQuery,
+        mut tx: RowSender<orders::Model>,
+    ) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        let mut stream = filter(query)
+            .order_by_asc(orders::Column::CreatedAt)
+            .stream(db)
+            .await?;
+
+        while let Some(model) = stream.next().await {
+            if tx.send(Ok(model?)).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_errors(
+        &self,
+        query: OrderErrorQuery,
+        page: PageQuery<OrderErrorSort>,
+    ) -> anyhow::Result<Page<order_errors::Model>> {
+        let db = self.mysql.get_db().await;
+        let (start, end) = query.created_range();
+        let mut select = OrderErrors::find();
+        if let Some(account) = query.account {
+            select = select.filter(order_errors::Column::UserAccount.eq(account));
+        }
+        if let Some(func) = query.func {
+            select = select.filter(order_errors::Column::Func.eq(func));
+        }
+        match query.rejected {
+            Some(true) => {
+                select = select.filter(order_errors::Column::Func.starts_with(RISK_FUNC_PREFIX))
+            }
+            Some(false) => {
+                let pattern = format!("{}%", RISK_FUNC_PREFIX);
+                select = select.filter(order_errors::Column::Func.not_like(&pattern))
+            }
+            None => (),
+        }
+        if let Some(start) = start {
+            select = select.filter(order_errors::Column::CreatedAt.gte(start));
+        }
+        if let Some(end) = end {
+            select = select.filter(order_errors::Column::CreatedAt.lt(end));
+        }
+        paginate(db, select, &page).await
+    }
+}
@@ -0,0 +1,135 @@
+use crate::domain::{RiskRepository, RiskScope, ACTION_CLOSE, ACTION_OPEN};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use entity::{orders, prelude::*, risk_rules, users};
+use pkg::db::ORM;
+use sea_orm::{prelude::*, Condition, QueryOrder};
+use std::sync::Arc;
+
+pub struct RiskRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl RiskRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn RiskRepository> {
+        Arc::new(RiskRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl RiskRepository for RiskRepo {
+    async fn list_rules(&self) -> anyhow::Result<Vec<risk_rules::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = RiskRules::find()
+            .order_by_asc(risk_rules::Column::Scope)
+            .order_by_asc(risk_rules::Column::Target)
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+
+    async fn get_rule(
+        &self,
+        scope: RiskScope,
+        target: String,
+    ) -> anyhow::Result<Option<risk_rules::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = RiskRules::find()
+            .filter(risk_rules::Column::Scope.eq(scope.to_i8()))
+            .filter(risk_rules::Column::Target.eq(target))
+            .one(db)
+            .await?;
+        Ok(model)
+    }
+
+    /**
+     * 用戶規則與策略規則
+     */
+    async fn get_rules_for(
+        &self,
+        account: String,
+        strategy_name: String,
+    ) -> anyhow::Result<Vec<risk_rules::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = RiskRules::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(risk_rules::Column::Scope.eq(RiskScope::User.to_i8()))
+                            .add(risk_rules::Column::Target.eq(account)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(risk_rules::Column::Scope.eq(RiskScope::Strategy.to_i8()))
+                            .add(risk_rules::Column::Target.eq(strategy_name)),
+                    ),
+            )
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+
+    async fn save_rule(
+        &self,
+        active: risk_rules::ActiveModel,
+    ) -> anyhow::Result<risk_rules::Model> {
+        let db = self.mysql.get_db().await;
+        let model = if active.id.is_unchanged() {
+            active.update(db).await?
+        } else {
+            active.insert(db).await?
+        };
+        Ok(model)
+    }
+
+    async fn delete_rule(&self, model: risk_rules::Model) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        model.delete(db).await?;
+        Ok(())
+    }
+
+    async fn target_exists(&self, scope: RiskScope, target: String) -> anyhow::Result<bool> {
+        let db = self.mysql.get_db().await;
+        let count = match scope {
+            RiskScope::User => {
+                Users::find()
+                    .filter(users::Column::Account.eq(target))
+                    .filter(users::Column::DeletedAt.is_null())
+                    .count(db)
+                    .await?
+            }
+            RiskScope::Strategy => Strategies::find_by_id(target).count(db).await?,
+        };
+        Ok(count > 0)
+    }
+
+    /**
+     * 用戶所有未平倉(含排隊中)的開倉單
+     */
+    async fn list_open_positions(&self, account: String) -> anyhow::Result<Vec<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = Orders::find()
+            .filter(orders::Column::UserAccount.eq(account))
+            .filter(orders::Column::Action.eq(ACTION_OPEN))
+            .filter(orders::Column::State.is_in([0, 1, 2]))
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+
+    async fn list_closed_since(
+        &self,
+        account: String,
+        since: DateTime<Local>,
+    ) -> anyhow::Result<Vec<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = Orders::find()
+            .filter(orders::Column::UserAccount.eq(account))
+            .filter(orders::Column::Action.eq(ACTION_CLOSE))
+            .filter(orders::Column::CreatedAt.gte(since))
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod order_ucase;
+pub mod pnl_ucase;
+pub mod risk_ucase;
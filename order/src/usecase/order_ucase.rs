@@ -0,0 +1,749 @@
+use crate::domain::{
+    calc_qty, check_signal_price, price_tolerance, round_step, DispatchReport, OrderDetail,
+    OrderErrorInfo, OrderErrorQuery, OrderErrorSort, OrderInfo as OrderView, OrderIntent,
+    OrderQuery, OrderRepository, OrderSort, OrderState, OrderUsecase, PnlUsecase, ReconcileReport,
+    Rejection, RiskUsecase, Signal, StateChange, UnrealisedPnl, ACTION_CLOSE, ACTION_OPEN,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{Duration, Local};
+use entity::{order_errors, orders, subscribes};
+use futures::future::join_all;
+use pkg::{
+    crypto,
+    exchange::{Exchange, ExchangeFactory, OrderInfo, OrderType, PlaceOrder, Side},
+    export::{self, RowReceiver},
+    page::{Page, PageQuery},
+    pnl,
+};
+use sea_orm::ActiveValue::Set;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/**
+ * 單筆對帳結果
+ */
+enum Outcome {
+    Unchanged,
+    Advanced,
+    Cancelled,
+}
+
+pub struct OrderUcase {
+    order_repo: Arc<dyn OrderRepository>,
+    pnl_ucase: Arc<dyn PnlUsecase>,
+    risk_ucase: Arc<dyn RiskUsecase>,
+    exchange: Arc<dyn ExchangeFactory>,
+}
+
+impl OrderUcase {
+    pub fn new(
+        order_repo: Arc<dyn OrderRepository>,
+        pnl_ucase: Arc<dyn PnlUsecase>,
+        risk_ucase: Arc<dyn RiskUsecase>,
+        exchange: Arc<dyn ExchangeFactory>,
+    ) -> Arc<dyn OrderUsecase> {
+        Arc::new(OrderUcase {
+            order_repo,
+            pnl_ucase,
+            risk_ucase,
+            exchange,
+        })
+    }
+
+    /**
+     * 取得用戶的交易所client, 停用或已刪除的用戶回傳None
+     */
+    async fn client(&self, account: &str) -> Result<Option<Arc<dyn Exchange>>> {
+        let user = self
+            .order_repo
+            .get_user(account.to_owned())
+            .await?
+            .ok_or_else(|| anyhow!("user {} not found", account))?;
+
+        if user.state == 0 || user.deleted_at.is_some() {
+            return Ok(None);
+        }
+
+        if user.api_key.is_empty() || user.secret_key.is_empty() {
+            return Err(anyhow!("user {} has no exchange api key", account));
+        }
+
+        //secret以用戶帳號為aad加密保存, 加密上線前的明文使用後改存密文
+        let secret_key = if crypto::is_sealed(&user.secret_key) {
+            crypto::decrypt(&user.secret_key, &user.account)
+                .map_err(|e| anyhow!("user {} secret key cannot be decrypted: {}", account, e))?
+        } else {
+            self.seal_legacy_secret(&user.account, &user.secret_key)
+                .await;
+            user.secret_key
+        };
+
+        let client = self.exchange.client(&user.api_key, &secret_key);
+        Ok(Some(client))
+    }
+
+    /**
+     * 將明文的secret加密後寫回, 失敗只記錄log, 下次讀取再試
+     */
+    async fn seal_legacy_secret(&self, account: &str, secret_key: &str) {
+        let res = match crypto::encrypt(secret_key, account) {
+            Ok(sealed) => {
+                self.order_repo
+                    .save_secret_key(account.to_owned(), sealed)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = res {
+            tracing::error!("encrypt legacy secret key of {} failed: {}", account, e);
+        }
+    }
+
+    /**
+     * 開倉: 檢查訊號價格並通過風控後設定槓桿與倉位模式, 以限價單下單
+     */
+    async fn open(&self, signal: &Signal, sub: &subscribes::Model) -> Result<Option<OrderInfo>> {
+        let client = match self.client(&sub.user_account).await? {
+            Some(client) => client,
+            None => return Ok(None),
+        };
+
+        let symbol = self
+            .order_repo
+            .get_symbol(signal.symbol.clone())
+            .await?
+            .ok_or_else(|| anyhow!("symbol {} not found", signal.symbol))?;
+
+        let side =
+            Side::from_i8(signal.side).ok_or_else(|| anyhow!("invalid side {}", signal.side))?;
+        //數量以交易所的標記價格計算, 訊號價格只做為限價且不可偏離太多
+        let mark_price = client.mark_price(&symbol.name).await?;
+        check_signal_price(signal.price, mark_price, price_tolerance())?;
+
+        let tick_size: f64 = symbol.tick_size.parse().unwrap_or(0.0);
+        let price = round_step(signal.price, tick_size, false);
+        let qty = calc_qty(sub.amount, sub.leverage, mark_price, &symbol)?;
+
+        //風控檢查, 拒絕時不呼叫交易所
+        let intent = OrderIntent {
+            account: sub.user_account.clone(),
+            strategy_name: signal.strategy_name.clone(),
+            symbol: symbol.name.clone(),
+            price,
+            qty,
+            leverage: sub.leverage,
+            symbol_max_leverage: symbol.max_leverage,
+        };
+        if let Some(rejection) = self.risk_ucase.check(&intent).await? {
+            return Err(rejection.into());
+        }
+
+        let is_isolated = sub.is_isolated.unwrap_or(0) == 1;
+        client
+            .switch_isolated(&symbol.name, is_isolated, sub.leverage, sub.leverage)
+            .await?;
+        client
+            .set_leverage(&symbol.name, sub.leverage, sub.leverage)
+            .await?;
+
+        let order_link_id = Uuid::new_v4().to_string();
+        let active = orders::ActiveModel {
+            order_link_id: Set(order_link_id.clone()),
+            //下單成功後才有交易所的order_id
+            order_id: Set(String::new()),
+            side: Set(side.to_i8()),
+            symbol: Set(symbol.name.clone()),
+            price: Set(price),
+            qty: Set(qty),
+            order_type: Set(OrderType::Limit.to_string()),
+            reduce_only: Set(Some(0)),
+            user_account: Set(sub.user_account.clone()),
+            strategy_name: Set(signal.strategy_name.clone()),
+            action: Set(ACTION_OPEN),
+            state: Set(OrderState::Queued.to_i8()),
+            ..Default::default()
+        };
+        let req = PlaceOrder {
+            symbol: symbol.name,
+            side,
+            order_type: OrderType::Limit,
+            qty,
+            price: Some(price),
+            reduce_only: false,
+            order_link_id,
+        };
+        let info = self.place_tracked(&client, active, req).await?;
+
+        Ok(Some(info))
+    }
+
+    /**
+     * 先寫入排隊中的訂單再呼叫交易所, 交易所已成交的訂單一定有紀錄可對帳
+     * order_link_id做為冪等鍵: 下單失敗時以它查詢, 交易所沒有這筆訂單才改為取消
+     */
+    async fn place_tracked(
+        &self,
+        client: &Arc<dyn Exchange>,
+        active: orders::ActiveModel,
+        req: PlaceOrder,
+    ) -> Result<OrderInfo> {
+        self.order_repo.create(active).await?;
+
+        let (symbol, order_link_id) = (req.symbol.clone(), req.order_link_id.clone());
+        let info = match client.place_order(req).await {
+            Ok(info) => info,
+            Err(e) => {
+                //逾時等錯誤時交易所可能已收到訂單, 留給對帳處理
+                if client.query_order(&symbol, &order_link_id).await.is_ok() {
+                    tracing::warn!(
+                        "place order {} failed but it exists on the exchange: {}",
+                        order_link_id,
+                        e
+                    );
+                } else {
+                    self.order_repo
+                        .transition(vec![StateChange::new(
+                            &order_link_id,
+                            OrderState::Queued,
+                            OrderState::Cancelled,
+                        )?])
+                        .await?;
+                }
+                return Err(e);
+            }
+        };
+
+        //order_id只做為參考, 寫入失敗時仍可用order_link_id對帳
+        if let Err(e) = self
+            .order_repo
+            .save_order_id(order_link_id.clone(), info.order_id.clone())
+            .await
+        {
+            tracing::error!("save order id of {} failed: {}", order_link_id, e);
+        }
+
+        Ok(info)
+    }
+
+    /**
+     * 平倉前處理開倉單: 尚未完全成交時先取消剩餘數量, 回傳已成交的數量
+     * 沒有成交 => 開倉單改為已取消, 有成交 => 改為全部持倉, 不會再被對帳成取消
+     */
+    async fn settle_open(&self, client: &Arc<dyn Exchange>, open: &orders::Model) -> Result<f64> {
+        let current = OrderState::from_i8(open.state)
+            .ok_or_else(|| anyhow!("invalid order state {}", open.state))?;
+
+        let mut info = client
+            .query_order(&open.symbol, &open.order_link_id)
+            .await?;
+        if matches!(
+            OrderState::from_exchange(&info.order_status),
+            Some(OrderState::Queued | OrderState::PartiallyFilled)
+        ) {
+            client
+                .cancel_order(&open.symbol, &open.order_link_id)
+                .await?;
+            //取消後重新查詢最終的成交數量
+            info = client
+                .query_order(&open.symbol, &open.order_link_id)
+                .await?;
+        }
+
+        let next = if info.cum_exec_qty > 0.0 {
+            OrderState::Filled
+        } else {
+            OrderState::Cancelled
+        };
+        if next != current {
+            self.order_repo
+                .transition(vec![StateChange::new(&open.order_link_id, current, next)?])
+                .await?;
+        }
+        if info.cum_exec_qty > 0.0 {
+            self.order_repo
+                .save_fill(
+                    open.order_link_id.clone(),
+                    info.cum_exec_qty,
+                    info.avg_price(),
+                )
+                .await?;
+        }
+
+        Ok(info.cum_exec_qty)
+    }
+
+    /**
+     * 平倉: 取消開倉單未成交的部分, 以市價單關閉已成交的數量
+     */
+    async fn close(&self, signal: &Signal, sub: &subscribes::Model) -> Result<Option<OrderInfo>> {
+        let open = match self
+            .order_repo
+            .get_open_order(sub.user_account.clone(), signal.strategy_name.clone())
+            .await?
+        {
+            Some(open) => open,
+            None => return Ok(None),
+        };
+
+        let client = match self.client(&sub.user_account).await? {
+            Some(client) => client,
+            None => return Ok(None),
+        };
+        let side = Side::from_i8(open.side)
+            .ok_or_else(|| anyhow!("invalid side {}", open.side))?
+            .opposite();
+
+        let qty = self.settle_open(&client, &open).await?;
+        if qty <= 0.0 {
+            return Ok(None);
+        }
+
+        let order_link_id = Uuid::new_v4().to_string();
+        let req = PlaceOrder {
+            symbol: open.symbol.clone(),
+            side,
+            order_type: OrderType::Market,
+            qty,
+            price: None,
+            reduce_only: true,
+            order_link_id: order_link_id.clone(),
+        };
+        let active = orders::ActiveModel {
+            order_link_id: Set(order_link_id),
+            order_id: Set(String::new()),
+            side: Set(side.to_i8()),
+            symbol: Set(open.symbol.clone()),
+            price: Set(signal.price),
+            qty: Set(qty),
+            order_type: Set(OrderType::Market.to_string()),
+            reduce_only: Set(Some(1)),
+            rel_order_id: Set(open.order_id),
+            rel_order_link_id: Set(open.order_link_id),
+            user_account: Set(sub.user_account.clone()),
+            strategy_name: Set(signal.strategy_name.clone()),
+            action: Set(ACTION_CLOSE),
+            state: Set(OrderState::Queued.to_i8()),
+            ..Default::default()
+        };
+        let info = self.place_tracked(&client, active, req).await?;
+
+        Ok(Some(info))
+    }
+
+    /**
+     * 對帳失敗, 只在第一次失敗時寫入order_errors, 避免每次輪詢都寫一筆
+     * 連續失敗max_failures次後向交易所取消訂單, 交易所確認取消才結束對帳
+     */
+    async fn reconcile_failed(
+        &self,
+        order: &orders::Model,
+        e: &anyhow::Error,
+        max_failures: i32,
+    ) -> Result<Outcome> {
+        let failures = order.reconcile_failures.saturating_add(1);
+        let func = "order_worker::reconcile";
+        self.order_repo
+            .save_reconcile_failures(order.order_link_id.clone(), failures)
+            .await?;
+
+        if failures >= max_failures {
+            match self.cancel_on_exchange(order).await {
+                Ok(Some(outcome)) => {
+                    let e = anyhow!(
+                        "gave up after {} failures, order cancelled on exchange: {}",
+                        failures,
+                        e
+                    );
+                    self.save_error(order.action, func, &order.user_account, &e)
+                        .await;
+                    return Ok(outcome);
+                }
+                //交易所未確認取消, 保留為未完成, 到達上限時記錄一次
+                Ok(None) if failures == max_failures => {
+                    let e = anyhow!(
+                        "gave up after {} failures, exchange did not confirm cancel: {}",
+                        failures,
+                        e
+                    );
+                    self.save_error(order.action, func, &order.user_account, &e)
+                        .await;
+                    return Ok(Outcome::Unchanged);
+                }
+                Err(cancel_err) if failures == max_failures => {
+                    let e = anyhow!(
+                        "gave up after {} failures, cancel failed: {}: {}",
+                        failures,
+                        cancel_err,
+                        e
+                    );
+                    self.save_error(order.action, func, &order.user_account, &e)
+                        .await;
+                    return Ok(Outcome::Unchanged);
+                }
+                _ => (),
+            }
+        }
+
+        if failures == 1 {
+            self.save_error(order.action, func, &order.user_account, e)
+                .await;
+        } else {
+            tracing::warn!(
+                "{} failed {} times for {}: {}",
+                func,
+                failures,
+                order.order_link_id,
+                e
+            );
+        }
+        Ok(Outcome::Unchanged)
+    }
+
+    /**
+     * 向交易所取消訂單並重新查詢, 交易所回報已取消才結束對帳
+     * 取消前已有部分成交則視為持倉(Filled), 讓後續平倉訊號能找到, 否則改為取消
+     * 交易所未確認取消時回傳None
+     */
+    async fn cancel_on_exchange(&self, order: &orders::Model) -> Result<Option<Outcome>> {
+        let client = self
+            .client(&order.user_account)
+            .await?
+            .ok_or_else(|| anyhow!("exchange client unavailable"))?;
+
+        //已成交或已取消的訂單取消會失敗, 以重新查詢的結果為準
+        if let Err(e) = client
+            .cancel_order(&order.symbol, &order.order_link_id)
+            .await
+        {
+            tracing::warn!("cancel order {} failed: {}", order.order_link_id, e);
+        }
+        let info = client
+            .query_order(&order.symbol, &order.order_link_id)
+            .await?;
+        if OrderState::from_exchange(&info.order_status) != Some(OrderState::Cancelled) {
+            return Ok(None);
+        }
+
+        let current = OrderState::from_i8(order.state)
+            .ok_or_else(|| anyhow!("invalid order state {}", order.state))?;
+        let (next, outcome) = if info.cum_exec_qty > 0.0 {
+            (OrderState::Filled, Outcome::Advanced)
+        } else {
+            (OrderState::Cancelled, Outcome::Cancelled)
+        };
+        self.order_repo
+            .transition(vec![StateChange::new(&order.order_link_id, current, next)?])
+            .await?;
+        if info.cum_exec_qty > 0.0 {
+            self.order_repo
+                .save_fill(
+                    order.order_link_id.clone(),
+                    info.cum_exec_qty,
+                    info.avg_price(),
+                )
+                .await?;
+        }
+        Ok(Some(outcome))
+    }
+
+    /**
+     * 寫入order_errors
+     */
+    async fn save_error(&self, action: i8, func: &str, account: &str, e: &anyhow::Error) {
+        tracing::error!("{} failed for {}: {}", func, account, e);
+        self.create_error(action, func, account, e.to_string())
+            .await;
+    }
+
+    /**
+     * 風控拒絕寫入order_errors, func為 risk::<規則>
+     */
+    async fn save_rejection(&self, signal: &Signal, account: &str, rejection: &Rejection) {
+        tracing::warn!(
+            "order rejected for {} on {}: {}",
+            account,
+            signal.strategy_name,
+            rejection
+        );
+        let msg = format!(
+            "order rejected by risk rule: {} (strategy {}, symbol {})",
+            rejection, signal.strategy_name, signal.symbol
+        );
+        self.create_error(signal.action, &rejection.func(), account, msg)
+            .await;
+    }
+
+    async fn create_error(&self, action: i8, func: &str, account: &str, msg: String) {
+        let active = order_errors::ActiveModel {
+            action: Set(action),
+            msg: Set(msg),
+            func: Set(func.to_owned()),
+            user_account: Set(Some(account.to_owned())),
+            ..Default::default()
+        };
+        if let Err(e) = self.order_repo.create_error(active).await {
+            tracing::error!("save order error failed: {}", e);
+        }
+    }
+
+    /**
+     * 單一用戶下單, 失敗或被風控拒絕時寫入order_errors
+     */
+    async fn execute(&self, signal: &Signal, sub: &subscribes::Model) -> Result<Option<OrderInfo>> {
+        let (res, func) = match signal.action {
+            ACTION_CLOSE => (self.close(signal, sub).await, "order_engine::close"),
+            _ => (self.open(signal, sub).await, "order_engine::open"),
+        };
+
+        if let Err(e) = &res {
+            match e.downcast_ref::<Rejection>() {
+                Some(rejection) => {
+                    self.save_rejection(signal, &sub.user_account, rejection)
+                        .await
+                }
+                None => {
+                    self.save_error(signal.action, func, &sub.user_account, e)
+                        .await
+                }
+            }
+        }
+
+        res
+    }
+
+    /**
+     * 單筆訂單對帳: 依交易所狀態推進訂單狀態, 排隊超時的限價單取消
+     */
+    async fn reconcile_order(&self, order: &orders::Model, timeout: Duration) -> Result<Outcome> {
+        let current = OrderState::from_i8(order.state)
+            .ok_or_else(|| anyhow!("invalid order state {}", order.state))?;
+
+        let client = match self.client(&order.user_account).await? {
+            Some(client) => client,
+            None => return Ok(Outcome::Unchanged),
+        };
+
+        let mut info = client
+            .query_order(&order.symbol, &order.order_link_id)
+            .await?;
+        let mut next = OrderState::from_exchange(&info.order_status).unwrap_or(current);
+
+        //限價單排隊超時, 取消訂單
+        let expired = Local::now() - order.created_at > timeout;
+        if next == OrderState::Queued && order.order_type == OrderType::Limit.to_string() && expired
+        {
+            info = client
+                .cancel_order(&order.symbol, &order.order_link_id)
+                .await?;
+            next = OrderState::from_exchange(&info.order_status).unwrap_or(current);
+        }
+
+        if next == current {
+            return Ok(Outcome::Unchanged);
+        }
+
+        let mut changes = vec![StateChange::new(&order.order_link_id, current, next)?];
+
+        //平倉單成交: 平倉單 => 資料已確認, 開倉單 => 已平倉
+        if next == OrderState::Filled && order.action == ACTION_CLOSE {
+            changes.push(StateChange::new(
+                &order.order_link_id,
+                OrderState::Filled,
+                OrderState::Confirmed,
+            )?);
+
+            if let Some(open) = self
+                .order_repo
+                .get_by_link_id(order.rel_order_link_id.clone())
+                .await?
+            {
+                let open_state = OrderState::from_i8(open.state)
+                    .ok_or_else(|| anyhow!("invalid order state {}", open.state))?;
+                changes.push(StateChange::new(
+                    &open.order_link_id,
+                    open_state,
+                    OrderState::Closed,
+                )?);
+            }
+        }
+
+        self.order_repo.transition(changes).await?;
+
+        //保存成交數量與均價, 盈虧以實際成交計算
+        let mut order = order.clone();
+        if info.cum_exec_qty > 0.0 {
+            order.exec_qty = info.cum_exec_qty;
+            order.avg_price = info.avg_price();
+            self.order_repo
+                .save_fill(order.order_link_id.clone(), order.exec_qty, order.avg_price)
+                .await?;
+        }
+
+        //平倉單成交後結算盈虧, 失敗不影響狀態推進
+        if next == OrderState::Filled && order.action == ACTION_CLOSE {
+            if let Err(e) = self.pnl_ucase.realise(&order).await {
+                self.save_error(order.action, "order_worker::pnl", &order.user_account, &e)
+                    .await;
+            }
+        }
+
+        if next == OrderState::Cancelled {
+            Ok(Outcome::Cancelled)
+        } else {
+            Ok(Outcome::Advanced)
+        }
+    }
+}
+
+#[async_trait]
+impl OrderUsecase for OrderUcase {
+    /**
+     * 將訊號分派給所有訂閱者, 個別用戶失敗不影響其他人
+     */
+    async fn dispatch(&self, signal: Signal) -> Result<DispatchReport> {
+        let subs = self
+            .order_repo
+            .list_subscribers(signal.strategy_name.clone())
+            .await?;
+
+        let results = join_all(subs.iter().map(|sub| self.execute(&signal, sub))).await;
+
+        let mut report = DispatchReport::default();
+        for res in results {
+            match res {
+                Ok(Some(_)) => report.placed += 1,
+                Ok(None) => report.skipped += 1,
+                Err(e) if e.is::<Rejection>() => report.rejected += 1,
+                Err(_) => report.failed += 1,
+            }
+        }
+
+        tracing::info!(
+            "signal {} action {} dispatched: {:?}",
+            signal.strategy_name,
+            signal.action,
+            report
+        );
+
+        Ok(report)
+    }
+
+    /**
+     * 對帳所有未完成的訂單
+     */
+    async fn reconcile(&self, timeout: Duration, max_failures: i32) -> Result<ReconcileReport> {
+        let orders = self.order_repo.list_pending().await?;
+
+        let func = "order_worker::reconcile";
+        let mut report = ReconcileReport::default();
+        for order in orders.iter() {
+            report.checked += 1;
+            //單筆訂單的DB錯誤只記錄, 不中斷其他訂單的對帳
+            let outcome = match self.reconcile_order(order, timeout).await {
+                Ok(outcome) => {
+                    if order.reconcile_failures > 0 {
+                        if let Err(e) = self
+                            .order_repo
+                            .save_reconcile_failures(order.order_link_id.clone(), 0)
+                            .await
+                        {
+                            self.save_error(order.action, func, &order.user_account, &e)
+                                .await;
+                        }
+                    }
+                    outcome
+                }
+                Err(e) => {
+                    report.failed += 1;
+                    match self.reconcile_failed(order, &e, max_failures).await {
+                        Ok(outcome) => outcome,
+                        Err(e) => {
+                            self.save_error(order.action, func, &order.user_account, &e)
+                                .await;
+                            continue;
+                        }
+                    }
+                }
+            };
+            match outcome {
+                Outcome::Unchanged => (),
+                Outcome::Advanced => report.advanced += 1,
+                Outcome::Cancelled => report.cancelled += 1,
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn list(&self, query: OrderQuery, page: PageQuery<OrderSort>) -> Result<Page<OrderView>> {
+        let res = self.order_repo.list(query, page).await?;
+        Ok(res.map(OrderView::from))
+    }
+
+    /**
+     * 取得訂單與對應的開倉/平倉單
+     */
+    async fn get_detail(&self, order_link_id: String) -> Result<Option<OrderDetail>> {
+        let model = match self.order_repo.get_by_link_id(order_link_id).await? {
+            Some(model) => model,
+            None => return Ok(None),
+        };
+        let linked = self.order_repo.get_counterpart(&model).await?;
+
+        Ok(Some(OrderDetail {
+            order: OrderView::from(model),
+            linked_order: linked.map(OrderView::from),
+        }))
+    }
+
+    /**
+     * 以標記價格計算持倉的未實現盈虧
+     */
+    async fn unrealised_pnl(&self, order: orders::Model, mark_price: f64) -> Result<UnrealisedPnl> {
+        let res = self
+            .pnl_ucase
+            .unrealised(&order, pnl::decimal(mark_price))
+            .await?;
+
+        Ok(UnrealisedPnl {
+            order_link_id: order.order_link_id,
+            symbol: order.symbol,
+            side: order.side,
+            qty: if order.exec_qty > 0.0 {
+                order.exec_qty
+            } else {
+                order.qty
+            },
+            entry_price: if order.avg_price > 0.0 {
+                order.avg_price
+            } else {
+                order.price
+            },
+            mark_price,
+            unrealised_pnl: pnl::to_f64(res),
+        })
+    }
+
+    async fn get_by_link_id(&self, order_link_id: String) -> Result<Option<orders::Model>> {
+        let res = self.order_repo.get_by_link_id(order_link_id).await?;
+        Ok(res)
+    }
+
+    /**
+     * 背景串流讀取訂單, 回傳的channel依下載速度取用
+     */
+    fn export(&self, query: OrderQuery) -> RowReceiver<orders::Model> {
+        let order_repo = self.order_repo.clone();
+        export::spawn(move |tx| async move { order_repo.export(query, tx).await })
+    }
+
+    async fn list_errors(
+        &self,
+        query: OrderErrorQuery,
+        page: PageQuery<OrderErrorSort>,
+    ) -> Result<Page<OrderErrorInfo>> {
+        let res = self.order_repo.list_errors(query, page).await?;
+        Ok(res.map(OrderErrorInfo::from))
+    }
+}
@@ -0,0 +1,73 @@
+use crate::domain::{fill_of, OrderRepository, PnlUsecase, ACTION_CLOSE};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use entity::{orders, symbols};
+use pkg::pnl;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+pub struct PnlUcase {
+    order_repo: Arc<dyn OrderRepository>,
+}
+
+impl PnlUcase {
+    pub fn new(order_repo: Arc<dyn OrderRepository>) -> Arc<dyn PnlUsecase> {
+        Arc::new(PnlUcase { order_repo })
+    }
+
+    async fn get_symbol(&self, name: &str) -> Result<symbols::Model> {
+        self.order_repo
+            .get_symbol(name.to_owned())
+            .await?
+            .ok_or_else(|| anyhow!("symbol {} not found", name))
+    }
+}
+
+#[async_trait]
+impl PnlUsecase for PnlUcase {
+    /**
+     * 平倉單成交後計算已實現盈虧, 寫回平倉單與對應的開倉單
+     */
+    async fn realise(&self, close: &orders::Model) -> Result<Option<Decimal>> {
+        if close.action != ACTION_CLOSE {
+            return Ok(None);
+        }
+
+        let open = match self
+            .order_repo
+            .get_by_link_id(close.rel_order_link_id.clone())
+            .await?
+        {
+            Some(open) => open,
+            None => return Ok(None),
+        };
+
+        let symbol = self.get_symbol(&open.symbol).await?;
+        let res = pnl::realised(
+            open.side,
+            &fill_of(&open, &symbol),
+            &fill_of(close, &symbol),
+        );
+
+        self.order_repo
+            .save_pnl(
+                vec![open.order_link_id, close.order_link_id.clone()],
+                pnl::to_f64(res),
+            )
+            .await?;
+
+        Ok(Some(res))
+    }
+
+    /**
+     * 持倉中的開倉單依標記價格計算未實現盈虧
+     */
+    async fn unrealised(&self, open: &orders::Model, mark_price: Decimal) -> Result<Decimal> {
+        let symbol = self.get_symbol(&open.symbol).await?;
+        Ok(pnl::unrealised(
+            open.side,
+            &fill_of(open, &symbol),
+            mark_price,
+        ))
+    }
+}
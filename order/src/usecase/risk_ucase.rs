@@ -0,0 +1,130 @@
+use crate::domain::{
+    evaluate, Exposure, OrderIntent, Rejection, RiskLimits, RiskRepository, RiskRuleInfo,
+    RiskScope, RiskUsecase, SaveRiskRule,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use entity::risk_rules;
+use pkg::eztime;
+use sea_orm::{ActiveValue::Set, IntoActiveModel};
+use std::sync::Arc;
+
+pub struct RiskUcase {
+    risk_repo: Arc<dyn RiskRepository>,
+}
+
+impl RiskUcase {
+    pub fn new(risk_repo: Arc<dyn RiskRepository>) -> Arc<dyn RiskUsecase> {
+        Arc::new(RiskUcase { risk_repo })
+    }
+
+    /**
+     * 取得既有規則, 沒有時建立新規則
+     */
+    async fn active_rule(
+        &self,
+        scope: RiskScope,
+        target: String,
+    ) -> Result<risk_rules::ActiveModel> {
+        let active = match self.risk_repo.get_rule(scope, target.clone()).await? {
+            Some(model) => model.into_active_model(),
+            None => risk_rules::ActiveModel {
+                scope: Set(scope.to_i8()),
+                target: Set(target),
+                ..Default::default()
+            },
+        };
+        Ok(active)
+    }
+}
+
+#[async_trait]
+impl RiskUsecase for RiskUcase {
+    /**
+     * 開倉前檢查用戶與策略的規則, 只查詢有設定的限制需要的資料
+     */
+    async fn check(&self, intent: &OrderIntent) -> Result<Option<Rejection>> {
+        let rules = self
+            .risk_repo
+            .get_rules_for(intent.account.clone(), intent.strategy_name.clone())
+            .await?;
+        let limits = RiskLimits::merge(&rules);
+
+        let positions = if limits.needs_positions() {
+            self.risk_repo
+                .list_open_positions(intent.account.clone())
+                .await?
+        } else {
+            vec![]
+        };
+        let closes = if limits.daily_loss_limit.is_some() {
+            self.risk_repo
+                .list_closed_since(intent.account.clone(), eztime::today_start())
+                .await?
+        } else {
+            vec![]
+        };
+
+        let exposure = Exposure::new(&intent.symbol, &positions, &closes);
+        Ok(evaluate(&limits, intent, &exposure))
+    }
+
+    async fn list_rules(&self) -> Result<Vec<RiskRuleInfo>> {
+        let list = self.risk_repo.list_rules().await?;
+        Ok(list.into_iter().map(RiskRuleInfo::from).collect())
+    }
+
+    async fn get_rule(
+        &self,
+        scope: RiskScope,
+        target: String,
+    ) -> Result<Option<risk_rules::Model>> {
+        let res = self.risk_repo.get_rule(scope, target).await?;
+        Ok(res)
+    }
+
+    async fn target_exists(&self, scope: RiskScope, target: String) -> Result<bool> {
+        let res = self.risk_repo.target_exists(scope, target).await?;
+        Ok(res)
+    }
+
+    /**
+     * 新增或覆蓋規則, 未指定的限制會被清除
+     */
+    async fn save_rule(
+        &self,
+        scope: RiskScope,
+        target: String,
+        payload: SaveRiskRule,
+    ) -> Result<RiskRuleInfo> {
+        let mut active = self.active_rule(scope, target).await?;
+        active.max_open_positions = Set(payload.max_open_positions);
+        active.max_notional = Set(payload.max_notional);
+        active.max_leverage = Set(payload.max_leverage);
+        active.daily_loss_limit = Set(payload.daily_loss_limit);
+        active.kill_switch = Set(payload.kill_switch as i8);
+
+        let model = self.risk_repo.save_rule(active).await?;
+        Ok(RiskRuleInfo::from(model))
+    }
+
+    /**
+     * 只切換kill switch, 保留其他限制
+     */
+    async fn set_kill_switch(
+        &self,
+        scope: RiskScope,
+        target: String,
+        enabled: bool,
+    ) -> Result<RiskRuleInfo> {
+        let mut active = self.active_rule(scope, target).await?;
+        active.kill_switch = Set(enabled as i8);
+
+        let model = self.risk_repo.save_rule(active).await?;
+        Ok(RiskRuleInfo::from(model))
+    }
+
+    async fn delete_rule(&self, model: risk_rules::Model) -> Result<()> {
+        self.risk_repo.delete_rule(model).await
+    }
+}
@@ -0,0 +1,180 @@
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng, Payload},
+    AeadCore, Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+
+//密文格式版本, 之後換演算法或金鑰時可依前綴判斷
+const VERSION: &str = "v1";
+const NONCE_LEN: usize = 12;
+
+/**
+ * 加密金鑰, 由API_KEY_ENCRYPTION_KEY讀取 (32 bytes hex)
+ */
+static CIPHER: Lazy<Cipher> = Lazy::new(|| {
+    let key = std::env::var("API_KEY_ENCRYPTION_KEY").expect("API_KEY_ENCRYPTION_KEY must be set");
+    Cipher::from_hex(&key).expect("API_KEY_ENCRYPTION_KEY must be 32 bytes hex")
+});
+
+/**
+ * 以環境變數的金鑰加密, aad綁定資料擁有者(例如用戶帳號)
+ */
+pub fn encrypt(plain: &str, aad: &str) -> Result<String> {
+    CIPHER.encrypt(plain, aad)
+}
+
+/**
+ * 以環境變數的金鑰解密
+ */
+pub fn decrypt(sealed: &str, aad: &str) -> Result<String> {
+    CIPHER.decrypt(sealed, aad)
+}
+
+/**
+ * 是否為加密後的格式, 加密功能上線前保存的secret為明文
+ */
+pub fn is_sealed(value: &str) -> bool {
+    value
+        .strip_prefix(VERSION)
+        .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/**
+ * AES-256-GCM
+ */
+pub struct Cipher {
+    aead: Aes256Gcm,
+}
+
+impl Cipher {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let aead = Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("invalid key length"))?;
+        Ok(Cipher { aead })
+    }
+
+    pub fn from_hex(key: &str) -> Result<Self> {
+        let key = hex::decode(key.trim())?;
+        Self::new(&key)
+    }
+
+    /**
+     * 回傳 v1:hex(nonce + ciphertext)
+     */
+    pub fn encrypt(&self, plain: &str, aad: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plain.as_bytes(),
+            aad: aad.as_bytes(),
+        };
+        let ciphertext = self
+            .aead
+            .encrypt(&nonce, payload)
+            .map_err(|_| anyhow!("encrypt failed"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}:{}", VERSION, hex::encode(sealed)))
+    }
+
+    pub fn decrypt(&self, sealed: &str, aad: &str) -> Result<String> {
+        let data = sealed
+            .strip_prefix(VERSION)
+            .and_then(|s| s.strip_prefix(':'))
+            .ok_or_else(|| anyhow!("unsupported ciphertext format"))?;
+        let data = hex::decode(data)?;
+        if data.len() <= NONCE_LEN {
+            return Err(anyhow!("ciphertext too short"));
+        }
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: aad.as_bytes(),
+        };
+        let plain = self
+            .aead
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| anyhow!("decrypt failed"))?;
+
+        Ok(String::from_utf8(plain)?)
+    }
+}
+
+/**
+ * 產生隨機token (hex)
+ */
+pub fn random_token(len: usize) -> String {
+    let mut buf = vec![0u8; len];
+    OsRng.fill_bytes(&mut buf);
+    hex::encode(buf)
+}
+
+/**
+ * sha256 hex, 用於保存不可逆的token
+ */
+pub fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+/**
+ * 遮蔽字串, 只留前後4碼
+ */
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}****{}", head, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn encrypt_then_decrypt() {
+        let cipher = Cipher::from_hex(KEY).unwrap();
+        let sealed = cipher
+            .encrypt("t7T0YlFnYXk0Fx3JswQsDrViLg1Gh3DUU5Mr", "alice")
+            .unwrap();
+        assert!(sealed.starts_with("v1:"));
+        assert!(is_sealed(&sealed));
+        assert!(!is_sealed("t7T0YlFnYXk0Fx3JswQsDrViLg1Gh3DUU5Mr"));
+        assert!(!sealed.contains("t7T0YlFn"));
+        assert_eq!(
+            cipher.decrypt(&sealed, "alice").unwrap(),
+            "t7T0YlFnYXk0Fx3JswQsDrViLg1Gh3DUU5Mr"
+        );
+
+        //不同用戶或被竄改的密文不能解開
+        assert!(cipher.decrypt(&sealed, "bob").is_err());
+        let mut tampered = sealed.clone();
+        let last = tampered.pop().unwrap();
+        tampered.push(if last == '0' { '1' } else { '0' });
+        assert!(cipher.decrypt(&tampered, "alice").is_err());
+        assert!(cipher.decrypt("plain-secret", "alice").is_err());
+        assert!(Cipher::from_hex("0011").is_err());
+    }
+
+    #[test]
+    fn token_hash() {
+        assert_eq!(random_token(32).len(), 64);
+        assert_ne!(random_token(32), random_token(32));
+        assert_eq!(
+            sha256_hex("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn mask_value() {
+        assert_eq!(mask("B2Rou0PLPpGqcU0Vu2"), "B2Ro****0Vu2");
+        assert_eq!(mask("short"), "*****");
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::{Database, DatabaseConnection, DbErr};
 
 #[async_trait]
 pub trait ORM: Sync + Send {
@@ -26,3 +26,30 @@ impl Mysql {
         Ok(mdb)
     }
 }
+
+/**
+ * 是否為違反唯一索引的錯誤 (MySQL 1062 Duplicate entry)
+ */
+pub fn is_duplicate_key(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<DbErr>(),
+        Some(DbErr::Exec(msg) | DbErr::Query(msg)) if msg.contains("Duplicate entry")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_key() {
+        let e = anyhow::Error::new(DbErr::Exec(
+            "error returned from database: 1062 (23000): Duplicate entry 'btc_1h' for key 'signal_records_open_unique'".to_owned(),
+        ));
+        assert!(is_duplicate_key(&e));
+        assert!(!is_duplicate_key(&anyhow::Error::new(DbErr::Exec(
+            "Lock wait timeout exceeded".to_owned()
+        ))));
+        assert!(!is_duplicate_key(&anyhow::anyhow!("Duplicate entry")));
+    }
+}
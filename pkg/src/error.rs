@@ -0,0 +1,264 @@
+use crate::responder::{failed, Data, Detail, StatusCode as RespCode};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::DbErr;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+pub type AppResult<T> = Result<T, AppError>;
+
+/**
+ * 統一的錯誤回應, 對應http status與業務狀態碼
+ */
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    Validation(String),
+    NotFound(String),
+    Duplicate(String),
+    Forbidden(String),
+    Unauthorized(String),
+    InvalidFields(Vec<FieldError>),
+    Internal(anyhow::Error),
+}
+
+/**
+ * 欄位驗證錯誤, 巢狀欄位以 a.b / a[0].b 表示
+ */
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub params: BTreeMap<String, Value>,
+}
+
+impl Data for FieldError {}
+
+impl FieldError {
+    /**
+     * 攤平ValidationErrors, 依欄位名稱排序
+     */
+    pub fn collect(errors: &ValidationErrors) -> Vec<FieldError> {
+        let mut fields = Vec::new();
+        flatten(errors, "", &mut fields);
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        fields
+    }
+}
+
+fn flatten(errors: &ValidationErrors, prefix: &str, fields: &mut Vec<FieldError>) {
+    for (name, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match kind {
+            ValidationErrorsKind::Field(errs) => {
+                for err in errs {
+                    let params = err
+                        .params
+                        .iter()
+                        //value為使用者送出的值(可能是密碼), 不回傳
+                        .filter(|(k, _)| *k != "value")
+                        .map(|(k, v)| (k.to_string(), v.clone()))
+                        .collect();
+                    fields.push(FieldError {
+                        field: path.clone(),
+                        code: err.code.to_string(),
+                        message: err.message.as_ref().map(|m| m.to_string()),
+                        params,
+                    });
+                }
+            }
+            ValidationErrorsKind::Struct(inner) => flatten(inner, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (idx, inner) in items {
+                    flatten(inner, &format!("{}[{}]", path, idx), fields);
+                }
+            }
+        }
+    }
+}
+
+impl AppError {
+    pub fn bad_request(msg: impl Into<String>) -> Self {
+        AppError::BadRequest(msg.into())
+    }
+
+    pub fn validation(msg: impl Into<String>) -> Self {
+        AppError::Validation(msg.into())
+    }
+
+    pub fn not_found(msg: impl Into<String>) -> Self {
+        AppError::NotFound(msg.into())
+    }
+
+    pub fn duplicate(msg: impl Into<String>) -> Self {
+        AppError::Duplicate(msg.into())
+    }
+
+    pub fn forbidden(msg: impl Into<String>) -> Self {
+        AppError::Forbidden(msg.into())
+    }
+
+    pub fn unauthorized(msg: impl Into<String>) -> Self {
+        AppError::Unauthorized(msg.into())
+    }
+
+    /**
+     * http status & 業務狀態碼
+     */
+    pub fn status(&self) -> (StatusCode, RespCode) {
+        match self {
+            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, RespCode::StatusBadReq),
+            AppError::Validation(_) => (StatusCode::BAD_REQUEST, RespCode::StatusValidation),
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, RespCode::StatusNotFound),
+            AppError::Duplicate(_) => (StatusCode::BAD_REQUEST, RespCode::StatusDuplicate),
+            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, RespCode::StatusForbidden),
+            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, RespCode::StatusUnauthorized),
+            AppError::InvalidFields(_) => (StatusCode::BAD_REQUEST, RespCode::StatusValidation),
+            AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, RespCode::StatusInternal),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::BadRequest(msg)
+            | AppError::Validation(msg)
+            | AppError::NotFound(msg)
+            | AppError::Duplicate(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Unauthorized(msg) => write!(f, "{}", msg),
+            AppError::InvalidFields(fields) => {
+                let names: Vec<&str> = fields.iter().map(|e| e.field.as_str()).collect();
+                write!(f, "invalid fields: {}", names.join(", "))
+            }
+            AppError::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (code, status) = self.status();
+        let msg = match self {
+            //欄位錯誤回傳每個欄位的規則與參數
+            AppError::InvalidFields(fields) => {
+                let (_, resp) = failed(status, fields);
+                return (code, Json(resp)).into_response();
+            }
+            //內部錯誤只記錄log, 不回傳細節
+            AppError::Internal(e) => {
+                tracing::error!("internal error: {:?}", e);
+                status.to_string()
+            }
+            other => other.to_string(),
+        };
+
+        let (_, resp) = failed(status, Detail(msg));
+        (code, Json(resp)).into_response()
+    }
+}
+
+/**
+ * usecase回傳的anyhow error, 若內含AppError或DbErr則還原
+ */
+impl From<anyhow::Error> for AppError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<AppError>() {
+            Ok(app) => return app,
+            Err(e) => e,
+        };
+        match e.downcast::<DbErr>() {
+            Ok(db) => AppError::from(db),
+            Err(e) => AppError::Internal(e),
+        }
+    }
+}
+
+impl From<DbErr> for AppError {
+    fn from(e: DbErr) -> Self {
+        match e {
+            DbErr::RecordNotFound(msg) => AppError::NotFound(msg),
+            e => AppError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(e: ValidationErrors) -> Self {
+        AppError::InvalidFields(FieldError::collect(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn convert_errors() {
+        let e = AppError::from(anyhow::Error::new(AppError::not_found("User not found")));
+        assert!(matches!(e, AppError::NotFound(ref msg) if msg == "User not found"));
+
+        let e = AppError::from(anyhow::Error::new(DbErr::RecordNotFound(
+            "users".to_owned(),
+        )));
+        assert_eq!(e.status().1.to_int(), 4004);
+
+        let e = AppError::from(anyhow::Error::new(DbErr::Conn("refused".to_owned())));
+        assert_eq!(e.status().0, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let e = AppError::from(anyhow!("boom"));
+        assert_eq!(e.status().1.to_int(), 5000);
+        assert_eq!(
+            e.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        assert_eq!(
+            AppError::forbidden("Permission error")
+                .into_response()
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn collect_field_errors() {
+        use validator::ValidationError;
+
+        let mut errors = ValidationErrors::new();
+        let mut err = ValidationError::new("length");
+        err.add_param("min".into(), &6);
+        err.add_param("max".into(), &50);
+        err.add_param("value".into(), &"12345");
+        errors.add("password", err);
+        errors.add("account", ValidationError::new("required"));
+
+        let fields = FieldError::collect(&errors);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].field, "account");
+        assert_eq!(fields[1].field, "password");
+        assert_eq!(fields[1].code, "length");
+        assert_eq!(fields[1].params.get("min"), Some(&Value::from(6)));
+        //不回傳使用者送出的值
+        assert!(!fields[1].params.contains_key("value"));
+
+        let e = AppError::from(errors);
+        assert_eq!(e.status().1.to_int(), 4001);
+    }
+}
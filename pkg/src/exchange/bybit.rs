@@ -0,0 +1,587 @@
+use super::{Exchange, ExchangeFactory, OrderInfo, PlaceOrder, Position, SymbolInfo};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const DEFAULT_BASE_URL: &str = "https://api.bybit.com";
+const RECV_WINDOW: i64 = 5000;
+
+//leverage not modified
+const LEVERAGE_NOT_MODIFIED: i64 = 34036;
+//isolated not modified
+const ISOLATED_NOT_MODIFIED: i64 = 130056;
+
+/**
+ * bybit USDT永續合約 client
+ */
+pub struct Bybit {
+    base_url: String,
+    api_key: String,
+    secret_key: String,
+    client: reqwest::Client,
+}
+
+/**
+ * bybit response
+ */
+#[derive(Deserialize)]
+struct Resp<T> {
+    ret_code: i64,
+    ret_msg: String,
+    result: Option<T>,
+}
+
+impl Bybit {
+    pub fn new(base_url: &str, api_key: &str, secret_key: &str) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url, api_key, secret_key)
+    }
+
+    fn with_client(
+        client: reqwest::Client,
+        base_url: &str,
+        api_key: &str,
+        secret_key: &str,
+    ) -> Self {
+        Bybit {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            api_key: api_key.to_owned(),
+            secret_key: secret_key.to_owned(),
+            client,
+        }
+    }
+
+    /**
+     * 產生簽名: 參數依key排序後組成query string, 以secret做HMAC-SHA256
+     */
+    pub fn sign(secret_key: &str, params: &BTreeMap<String, Value>) -> String {
+        let payload = to_query(params);
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /**
+     * 加上api_key, timestamp並簽名
+     */
+    fn signed(&self, mut params: BTreeMap<String, Value>) -> BTreeMap<String, Value> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        params.insert("api_key".to_owned(), json!(self.api_key));
+        params.insert("recv_window".to_owned(), json!(RECV_WINDOW));
+        params.insert("timestamp".to_owned(), json!(timestamp));
+        let sign = Self::sign(&self.secret_key, &params);
+        params.insert("sign".to_owned(), json!(sign));
+        params
+    }
+
+    async fn public_get<T: DeserializeOwned>(&self, path: &str) -> Result<Resp<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let resp = self.client.get(url).send().await?.json().await?;
+        Ok(resp)
+    }
+
+    async fn private_get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: BTreeMap<String, Value>,
+    ) -> Result<Resp<T>> {
+        let query = to_query(&self.signed(params));
+        let url = format!("{}{}?{}", self.base_url, path, query);
+        let resp = self.client.get(url).send().await?.json().await?;
+        Ok(resp)
+    }
+
+    async fn private_post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: BTreeMap<String, Value>,
+    ) -> Result<Resp<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let resp = self
+            .client
+            .post(url)
+            .json(&self.signed(params))
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+}
+
+/**
+ * 組成 k=v&k=v 的字串 (BTreeMap已排序)
+ */
+fn to_query(params: &BTreeMap<String, Value>) -> String {
+    params
+        .iter()
+        .map(|(k, v)| match v {
+            Value::String(s) => format!("{}={}", k, s),
+            _ => format!("{}={}", k, v),
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/**
+ * 檢查ret_code並取出result
+ */
+fn into_result<T>(resp: Resp<T>) -> Result<T> {
+    if resp.ret_code != 0 {
+        return Err(anyhow!("bybit error {}: {}", resp.ret_code, resp.ret_msg));
+    }
+    resp.result
+        .ok_or_else(|| anyhow!("bybit error: empty result"))
+}
+
+/**
+ * 允許的ret_code視為成功
+ */
+fn ensure_ok<T>(resp: Resp<T>, allowed: i64) -> Result<()> {
+    if resp.ret_code != 0 && resp.ret_code != allowed {
+        return Err(anyhow!("bybit error {}: {}", resp.ret_code, resp.ret_msg));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct RawTicker {
+    mark_price: String,
+}
+
+#[derive(Deserialize)]
+struct RawLeverageFilter {
+    min_leverage: i32,
+    max_leverage: i32,
+    leverage_step: String,
+}
+
+#[derive(Deserialize)]
+struct RawPriceFilter {
+    min_price: String,
+    max_price: String,
+    tick_size: String,
+}
+
+#[derive(Deserialize)]
+struct RawLotSizeFilter {
+    max_trading_qty: f64,
+    min_trading_qty: f64,
+    qty_step: f64,
+    #[serde(default)]
+    post_only_max_trading_qty: String,
+}
+
+#[derive(Deserialize)]
+struct RawSymbol {
+    name: String,
+    alias: String,
+    status: String,
+    base_currency: String,
+    quote_currency: String,
+    price_scale: i32,
+    taker_fee: String,
+    maker_fee: String,
+    #[serde(default)]
+    funding_interval: i32,
+    leverage_filter: RawLeverageFilter,
+    price_filter: RawPriceFilter,
+    lot_size_filter: RawLotSizeFilter,
+}
+
+impl From<RawSymbol> for SymbolInfo {
+    fn from(raw: RawSymbol) -> Self {
+        SymbolInfo {
+            name: raw.name,
+            alias: raw.alias,
+            status: raw.status,
+            base_currency: raw.base_currency,
+            quote_currency: raw.quote_currency,
+            price_scale: raw.price_scale,
+            taker_fee: raw.taker_fee,
+            maker_fee: raw.maker_fee,
+            funding_interval: raw.funding_interval,
+            max_trading_qty: raw.lot_size_filter.max_trading_qty,
+            min_trading_qty: raw.lot_size_filter.min_trading_qty,
+            qty_step: raw.lot_size_filter.qty_step,
+            post_only_max_trading_qty: raw.lot_size_filter.post_only_max_trading_qty,
+            min_price: raw.price_filter.min_price,
+            max_price: raw.price_filter.max_price,
+            tick_size: raw.price_filter.tick_size,
+            min_leverage: raw.leverage_filter.min_leverage,
+            max_leverage: raw.leverage_filter.max_leverage,
+            leverage_step: raw.leverage_filter.leverage_step,
+        }
+    }
+}
+
+#[async_trait]
+impl Exchange for Bybit {
+    /**
+     * 下單
+     */
+    async fn place_order(&self, req: PlaceOrder) -> Result<OrderInfo> {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!(req.symbol));
+        params.insert("side".to_owned(), json!(req.side.to_string()));
+        params.insert("order_type".to_owned(), json!(req.order_type.to_string()));
+        params.insert("qty".to_owned(), json!(req.qty));
+        if let Some(price) = req.price {
+            params.insert("price".to_owned(), json!(price));
+        }
+        params.insert("time_in_force".to_owned(), json!("GoodTillCancel"));
+        params.insert("reduce_only".to_owned(), json!(req.reduce_only));
+        params.insert("close_on_trigger".to_owned(), json!(false));
+        params.insert("order_link_id".to_owned(), json!(req.order_link_id));
+
+        let resp = self
+            .private_post("/private/linear/order/create", params)
+            .await?;
+        into_result(resp)
+    }
+
+    /**
+     * 取消訂單
+     */
+    async fn cancel_order(&self, symbol: &str, order_link_id: &str) -> Result<OrderInfo> {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!(symbol));
+        params.insert("order_link_id".to_owned(), json!(order_link_id));
+
+        let resp: Resp<Value> = self
+            .private_post("/private/linear/order/cancel", params)
+            .await?;
+        into_result(resp)?;
+
+        self.query_order(symbol, order_link_id).await
+    }
+
+    /**
+     * 查詢訂單
+     */
+    async fn query_order(&self, symbol: &str, order_link_id: &str) -> Result<OrderInfo> {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!(symbol));
+        params.insert("order_link_id".to_owned(), json!(order_link_id));
+
+        let resp = self
+            .private_get("/private/linear/order/search", params)
+            .await?;
+        into_result(resp)
+    }
+
+    /**
+     * 設定槓桿
+     */
+    async fn set_leverage(
+        &self,
+        symbol: &str,
+        buy_leverage: i16,
+        sell_leverage: i16,
+    ) -> Result<()> {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!(symbol));
+        params.insert("buy_leverage".to_owned(), json!(buy_leverage));
+        params.insert("sell_leverage".to_owned(), json!(sell_leverage));
+
+        let resp: Resp<Value> = self
+            .private_post("/private/linear/position/set-leverage", params)
+            .await?;
+        ensure_ok(resp, LEVERAGE_NOT_MODIFIED)
+    }
+
+    /**
+     * 切換全倉 / 逐倉
+     */
+    async fn switch_isolated(
+        &self,
+        symbol: &str,
+        is_isolated: bool,
+        buy_leverage: i16,
+        sell_leverage: i16,
+    ) -> Result<()> {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!(symbol));
+        params.insert("is_isolated".to_owned(), json!(is_isolated));
+        params.insert("buy_leverage".to_owned(), json!(buy_leverage));
+        params.insert("sell_leverage".to_owned(), json!(sell_leverage));
+
+        let resp: Resp<Value> = self
+            .private_post("/private/linear/position/switch-isolated", params)
+            .await?;
+        ensure_ok(resp, ISOLATED_NOT_MODIFIED)
+    }
+
+    /**
+     * 取得倉位
+     */
+    async fn get_position(&self, symbol: &str) -> Result<Vec<Position>> {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!(symbol));
+
+        let resp = self
+            .private_get("/private/linear/position/list", params)
+            .await?;
+        into_result(resp)
+    }
+
+    /**
+     * 取得合約列表
+     */
+    async fn list_symbols(&self) -> Result<Vec<SymbolInfo>> {
+        let resp: Resp<Vec<RawSymbol>> = self.public_get("/v2/public/symbols").await?;
+        let symbols = into_result(resp)?
+            .into_iter()
+            .map(SymbolInfo::from)
+            .collect();
+        Ok(symbols)
+    }
+
+    /**
+     * 取得合約目前的標記價格
+     */
+    async fn mark_price(&self, symbol: &str) -> Result<f64> {
+        let path = format!("/v2/public/tickers?symbol={}", symbol);
+        let resp: Resp<Vec<RawTicker>> = self.public_get(&path).await?;
+        let ticker = into_result(resp)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("bybit error: no ticker for {}", symbol))?;
+        let price: f64 = ticker.mark_price.parse()?;
+        if price <= 0.0 {
+            return Err(anyhow!("bybit error: invalid mark price {}", price));
+        }
+        Ok(price)
+    }
+
+    /**
+     * 以api key資訊確認key與簽名有效
+     */
+    async fn verify_credentials(&self) -> Result<()> {
+        let resp: Resp<Value> = self
+            .private_get("/v2/private/account/api-key", BTreeMap::new())
+            .await?;
+        into_result(resp)?;
+        Ok(())
+    }
+}
+
+/**
+ * bybit client factory
+ */
+pub struct BybitFactory {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl BybitFactory {
+    pub fn new(base_url: &str) -> Arc<dyn ExchangeFactory> {
+        Arc::new(BybitFactory {
+            base_url: base_url.to_owned(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /**
+     * 從環境變數BYBIT_BASE_URL讀取, 預設為正式環境
+     */
+    pub fn from_env() -> Arc<dyn ExchangeFactory> {
+        let base_url =
+            std::env::var("BYBIT_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_owned());
+        Self::new(&base_url)
+    }
+}
+
+impl ExchangeFactory for BybitFactory {
+    fn client(&self, api_key: &str, secret_key: &str) -> Arc<dyn Exchange> {
+        Arc::new(Bybit::with_client(
+            self.client.clone(),
+            &self.base_url,
+            api_key,
+            secret_key,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{OrderStatus, OrderType, Side};
+    use axum::{extract::Query, routing::get, Json, Router};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn sign_sorted_params() {
+        let mut params = BTreeMap::new();
+        params.insert("symbol".to_owned(), json!("BTCUSDT"));
+        params.insert("api_key".to_owned(), json!("B2Rou0PLPpGqcU0Vu2"));
+        params.insert("leverage".to_owned(), json!(100));
+        params.insert("timestamp".to_owned(), json!(1542434791000i64));
+
+        assert_eq!(
+            to_query(&params),
+            "api_key=B2Rou0PLPpGqcU0Vu2&leverage=100&symbol=BTCUSDT&timestamp=1542434791000"
+        );
+        assert_eq!(
+            Bybit::sign("t7T0YlFnYXk0Fx3JswQsDrViLg1Gh3DUU5Mr", &params),
+            "1d9b98d7a6af236b1b4f5fb9aae2659f65a64ca61960173b1188c3ba1bf3d49d"
+        );
+    }
+
+    /**
+     * 起一個假的bybit server
+     */
+    async fn mock_server() -> String {
+        let app = Router::new()
+            .route(
+                "/v2/public/symbols",
+                get(|| async {
+                    Json(json!({
+                        "ret_code": 0,
+                        "ret_msg": "OK",
+                        "result": [{
+                            "name": "BTCUSDT",
+                            "alias": "BTCUSDT",
+                            "status": "Trading",
+                            "base_currency": "BTC",
+                            "quote_currency": "USDT",
+                            "price_scale": 2,
+                            "taker_fee": "0.0006",
+                            "maker_fee": "0.0001",
+                            "funding_interval": 480,
+                            "leverage_filter": {"min_leverage": 1, "max_leverage": 100, "leverage_step": "0.01"},
+                            "price_filter": {"min_price": "0.5", "max_price": "999999", "tick_size": "0.5"},
+                            "lot_size_filter": {"max_trading_qty": 100.0, "min_trading_qty": 0.001, "qty_step": 0.001, "post_only_max_trading_qty": "1000"}
+                        }]
+                    }))
+                }),
+            )
+            .route(
+                "/v2/public/tickers",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    let result = match q.get("symbol").map(String::as_str) {
+                        Some("BTCUSDT") => json!([{"symbol": "BTCUSDT", "last_price": "20001.00", "mark_price": "20000.50"}]),
+                        _ => json!([]),
+                    };
+                    Json(json!({"ret_code": 0, "ret_msg": "OK", "result": result}))
+                }),
+            )
+            .route(
+                "/v2/private/account/api-key",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    if q.get("api_key").map(String::as_str) != Some("key") {
+                        return Json(json!({"ret_code": 10003, "ret_msg": "Invalid api_key.", "result": null}));
+                    }
+                    Json(json!({"ret_code": 0, "ret_msg": "OK", "result": [{"api_key": "key", "read_only": false}]}))
+                }),
+            )
+            .route(
+                "/private/linear/order/search",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    let signed = matches!(q.get("sign"), Some(s) if s.len() == 64)
+                        && q.get("api_key").map(String::as_str) == Some("key");
+                    if !signed {
+                        return Json(json!({"ret_code": 10004, "ret_msg": "error sign!", "result": null}));
+                    }
+                    Json(json!({
+                        "ret_code": 0,
+                        "ret_msg": "OK",
+                        "result": {
+                            "order_id": "e3f7662b",
+                            "order_link_id": q.get("order_link_id"),
+                            "symbol": "BTCUSDT",
+                            "side": "Buy",
+                            "order_type": "Limit",
+                            "price": 20000.5,
+                            "qty": 0.01,
+                            "cum_exec_qty": 0.005,
+                            "cum_exec_value": 100.0025,
+                            "order_status": "PartiallyFilled"
+                        }
+                    }))
+                }),
+            );
+
+        let listener = std::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn list_symbols_from_mock() {
+        let base_url = mock_server().await;
+        let client = BybitFactory::new(&base_url).client("key", "secret");
+
+        let symbols = client.list_symbols().await.unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "BTCUSDT");
+        assert_eq!(symbols[0].tick_size, "0.5");
+        assert_eq!(symbols[0].max_leverage, 100);
+        assert_eq!(symbols[0].qty_step, 0.001);
+    }
+
+    #[tokio::test]
+    async fn mark_price_from_mock() {
+        let base_url = mock_server().await;
+        let client = BybitFactory::new(&base_url).client("", "");
+
+        assert_eq!(client.mark_price("BTCUSDT").await.unwrap(), 20000.5);
+        assert!(client.mark_price("ETHUSDT").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn query_order_from_mock() {
+        let base_url = mock_server().await;
+
+        let order = BybitFactory::new(&base_url)
+            .client("key", "secret")
+            .query_order("BTCUSDT", "link-1")
+            .await
+            .unwrap();
+        assert_eq!(order.order_link_id, "link-1");
+        assert_eq!(order.side, Side::Buy);
+        assert_eq!(order.order_type, OrderType::Limit);
+        assert_eq!(order.order_status, OrderStatus::PartiallyFilled);
+        assert!((order.avg_price() - 20000.5).abs() < 1e-6);
+
+        let err = BybitFactory::new(&base_url)
+            .client("other", "secret")
+            .query_order("BTCUSDT", "link-1")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("10004"));
+    }
+
+    #[tokio::test]
+    async fn verify_credentials_from_mock() {
+        let base_url = mock_server().await;
+        let factory = BybitFactory::new(&base_url);
+
+        assert!(factory
+            .client("key", "secret")
+            .verify_credentials()
+            .await
+            .is_ok());
+        let err = factory
+            .client("wrong", "secret")
+            .verify_credentials()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("10003"));
+    }
+}
@@ -0,0 +1,206 @@
+pub mod bybit;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/**
+ * 交易所介面, 對應bybit USDT永續合約的操作
+ */
+#[async_trait]
+pub trait Exchange: Send + Sync {
+    async fn place_order(&self, req: PlaceOrder) -> Result<OrderInfo>;
+    async fn cancel_order(&self, symbol: &str, order_link_id: &str) -> Result<OrderInfo>;
+    async fn query_order(&self, symbol: &str, order_link_id: &str) -> Result<OrderInfo>;
+    async fn set_leverage(&self, symbol: &str, buy_leverage: i16, sell_leverage: i16)
+        -> Result<()>;
+    async fn switch_isolated(
+        &self,
+        symbol: &str,
+        is_isolated: bool,
+        buy_leverage: i16,
+        sell_leverage: i16,
+    ) -> Result<()>;
+    async fn get_position(&self, symbol: &str) -> Result<Vec<Position>>;
+    async fn list_symbols(&self) -> Result<Vec<SymbolInfo>>;
+    async fn mark_price(&self, symbol: &str) -> Result<f64>;
+    async fn verify_credentials(&self) -> Result<()>;
+}
+
+/**
+ * 依用戶的api key產生交易所client
+ */
+pub trait ExchangeFactory: Send + Sync {
+    fn client(&self, api_key: &str, secret_key: &str) -> Arc<dyn Exchange>;
+}
+
+/**
+ * 下單方向 1 => 多單(Buy), 2 => 空單(Sell)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn from_i8(side: i8) -> Option<Side> {
+        match side {
+            1 => Some(Side::Buy),
+            2 => Some(Side::Sell),
+            _ => None,
+        }
+    }
+
+    pub fn to_i8(&self) -> i8 {
+        match self {
+            Side::Buy => 1,
+            Side::Sell => 2,
+        }
+    }
+
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Side::Buy => write!(f, "Buy"),
+            Side::Sell => write!(f, "Sell"),
+        }
+    }
+}
+
+/**
+ * 訂單類型
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderType {
+    Limit,
+    Market,
+}
+
+impl fmt::Display for OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderType::Limit => write!(f, "Limit"),
+            OrderType::Market => write!(f, "Market"),
+        }
+    }
+}
+
+/**
+ * 交易所回傳的訂單狀態
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Created,
+    New,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+    PendingCancel,
+    Untriggered,
+    Triggered,
+    Deactivated,
+    #[serde(other)]
+    Unknown,
+}
+
+/**
+ * 下單參數
+ */
+#[derive(Debug, Clone)]
+pub struct PlaceOrder {
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub qty: f64,
+    pub price: Option<f64>,
+    pub reduce_only: bool,
+    pub order_link_id: String,
+}
+
+/**
+ * 訂單資料
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderInfo {
+    pub order_id: String,
+    #[serde(default)]
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    #[serde(default)]
+    pub price: f64,
+    #[serde(default)]
+    pub qty: f64,
+    #[serde(default)]
+    pub cum_exec_qty: f64,
+    #[serde(default)]
+    pub cum_exec_value: f64,
+    pub order_status: OrderStatus,
+}
+
+impl OrderInfo {
+    /**
+     * 成交均價 = 成交金額 / 成交數量, 尚未成交時為0
+     */
+    pub fn avg_price(&self) -> f64 {
+        if self.cum_exec_qty > 0.0 {
+            self.cum_exec_value / self.cum_exec_qty
+        } else {
+            0.0
+        }
+    }
+}
+
+/**
+ * 倉位資料
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,
+    pub entry_price: f64,
+    pub leverage: f64,
+    pub is_isolated: bool,
+    #[serde(default)]
+    pub unrealised_pnl: f64,
+}
+
+/**
+ * 合約規格, 欄位對應symbols table
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub alias: String,
+    pub status: String,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub price_scale: i32,
+    pub taker_fee: String,
+    pub maker_fee: String,
+    pub funding_interval: i32,
+    pub max_trading_qty: f64,
+    pub min_trading_qty: f64,
+    pub qty_step: f64,
+    pub post_only_max_trading_qty: String,
+    pub min_price: String,
+    pub max_price: String,
+    pub tick_size: String,
+    pub min_leverage: i32,
+    pub max_leverage: i32,
+    pub leverage_step: String,
+}
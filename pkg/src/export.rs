@@ -0,0 +1,330 @@
+use crate::error::AppError;
+use axum::{
+    body::{Bytes, StreamBody},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Local};
+use chrono_tz::Tz;
+use futures::{channel::mpsc, stream, Future, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use std::io::{Seek, SeekFrom};
+use tokio_util::io::ReaderStream;
+use validator::{Validate, ValidationError};
+
+//DB與response之間最多暫存的筆數
+const EXPORT_BUFFER: usize = 256;
+const DEFAULT_TIMEZONE: &str = "UTC";
+//讓Excel以UTF-8開啟CSV
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub type RowSender<T> = mpsc::Sender<anyhow::Result<T>>;
+pub type RowReceiver<T> = mpsc::Receiver<anyhow::Result<T>>;
+
+/**
+ * 匯出格式
+ */
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/**
+ * 匯出參數: ?format=csv|xlsx&tz=Asia/Taipei, 篩選條件另外用各模組的Query解析
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[validate(custom = "validate_timezone")]
+    pub tz: Option<String>,
+}
+
+impl ExportQuery {
+    /**
+     * 時間欄位使用的時區, 未指定時使用 EXPORT_TIMEZONE, 預設UTC
+     */
+    pub fn timezone(&self) -> Tz {
+        timezone(self.tz.as_deref())
+    }
+}
+
+pub fn validate_timezone(value: &str) -> Result<(), ValidationError> {
+    match value.parse::<Tz>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ValidationError::new("timezone")),
+    }
+}
+
+/**
+ * 解析時區, 無效時使用 EXPORT_TIMEZONE, 預設UTC
+ */
+pub fn timezone(tz: Option<&str>) -> Tz {
+    tz.and_then(|tz| tz.parse().ok())
+        .or_else(|| std::env::var("EXPORT_TIMEZONE").ok()?.parse().ok())
+        .unwrap_or_else(|| DEFAULT_TIMEZONE.parse().unwrap())
+}
+
+/**
+ * 轉換時區, 含UTC offset, 例如 2022-08-24 10:00:00 +08:00
+ */
+pub fn format_dt(dt: &DateTime<Local>, tz: &Tz) -> String {
+    dt.with_timezone(tz)
+        .format("%Y-%m-%d %H:%M:%S %:z")
+        .to_string()
+}
+
+/**
+ * 儲存格
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl Cell {
+    fn to_text(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => number.to_string(),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_owned())
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl From<i8> for Cell {
+    fn from(value: i8) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<u64> for Cell {
+    fn from(value: u64) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+/**
+ * 可匯出的資料列
+ */
+pub trait ExportRow: Send + 'static {
+    fn headers() -> Vec<&'static str>;
+    fn cells(&self, tz: &Tz) -> Vec<Cell>;
+}
+
+/**
+ * 在背景task產生資料列, 透過有上限的channel送出, 不會一次載入全部資料
+ * 下載中斷時send失敗, producer應停止讀取
+ */
+pub fn spawn<T, F, Fut>(produce: F) -> RowReceiver<T>
+where
+    T: Send + 'static,
+    F: FnOnce(RowSender<T>) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(EXPORT_BUFFER);
+    let mut err_tx = tx.clone();
+    let task = produce(tx);
+    tokio::spawn(async move {
+        if let Err(e) = task.await {
+            tracing::error!("export failed: {}", e);
+            let _ = err_tx.send(Err(e)).await;
+        }
+    });
+    rx
+}
+
+fn csv_line(cells: Vec<String>) -> anyhow::Result<Bytes> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(cells)?;
+    Ok(Bytes::from(writer.into_inner()?))
+}
+
+/**
+ * CSV: 逐筆轉換後直接寫入response
+ */
+fn csv_stream<T, S>(rows: S, tz: Tz) -> impl Stream<Item = anyhow::Result<Bytes>>
+where
+    T: ExportRow,
+    S: Stream<Item = anyhow::Result<T>>,
+{
+    let headers = T::headers().into_iter().map(String::from).collect();
+    let head = csv_line(headers).map(|line| Bytes::from([UTF8_BOM, &line[..]].concat()));
+
+    stream::once(async move { head }).chain(rows.map(move |row| {
+        let cells = row?.cells(&tz).iter().map(Cell::to_text).collect();
+        csv_line(cells)
+    }))
+}
+
+/**
+ * XLSX: 以constant memory模式逐筆寫入暫存檔, 完成後再串流檔案
+ */
+async fn xlsx_body<T, S>(
+    mut rows: S,
+    tz: Tz,
+) -> anyhow::Result<StreamBody<ReaderStream<tokio::fs::File>>>
+where
+    T: ExportRow,
+    S: Stream<Item = anyhow::Result<T>> + Send + Unpin + 'static,
+{
+    let file = tokio::task::spawn_blocking(move || -> anyhow::Result<std::fs::File> {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet_with_constant_memory();
+
+        for (col, header) in T::headers().into_iter().enumerate() {
+            sheet.write_string(0, col as u16, header)?;
+        }
+
+        let mut line = 1;
+        while let Some(row) = futures::executor::block_on(rows.next()) {
+            for (col, cell) in row?.cells(&tz).into_iter().enumerate() {
+                match cell {
+                    Cell::Text(text) => sheet.write_string(line, col as u16, text)?,
+                    Cell::Number(number) => sheet.write_number(line, col as u16, number)?,
+                };
+            }
+            line += 1;
+        }
+
+        let mut file = tempfile::tempfile()?;
+        workbook.save_to_writer(&mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(file)
+    })
+    .await??;
+
+    let file = tokio::fs::File::from_std(file);
+    Ok(StreamBody::new(ReaderStream::new(file)))
+}
+
+/**
+ * 以附件回應匯出的資料
+ */
+pub async fn respond<T, S>(rows: S, query: &ExportQuery, name: &str) -> Result<Response, AppError>
+where
+    T: ExportRow,
+    S: Stream<Item = anyhow::Result<T>> + Send + Unpin + 'static,
+{
+    let tz = query.timezone();
+    let disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        name,
+        query.format.extension()
+    );
+    let headers = [
+        (header::CONTENT_TYPE, query.format.content_type().to_owned()),
+        (header::CONTENT_DISPOSITION, disposition),
+    ];
+
+    let res = match query.format {
+        ExportFormat::Csv => (headers, StreamBody::new(csv_stream(rows, tz))).into_response(),
+        ExportFormat::Xlsx => (headers, xlsx_body(rows, tz).await?).into_response(),
+    };
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    struct Row(&'static str, f64);
+
+    impl ExportRow for Row {
+        fn headers() -> Vec<&'static str> {
+            vec!["name", "value"]
+        }
+
+        fn cells(&self, _tz: &Tz) -> Vec<Cell> {
+            vec![self.0.into(), self.1.into()]
+        }
+    }
+
+    #[test]
+    fn timezone_and_format() {
+        assert_eq!(timezone(Some("Asia/Taipei")), chrono_tz::Asia::Taipei);
+        assert!(validate_timezone("Mars/Base").is_err());
+
+        let dt = Local.timestamp_opt(1661306400, 0).unwrap();
+        assert_eq!(
+            format_dt(&dt, &chrono_tz::Asia::Taipei),
+            "2022-08-24 10:00:00 +08:00"
+        );
+        assert_eq!(
+            format_dt(&dt, &chrono_tz::UTC),
+            "2022-08-24 02:00:00 +00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_csv_rows() {
+        let rows = spawn(|mut tx| async move {
+            tx.send(Ok(Row("a,b", 1.5))).await?;
+            tx.send(Ok(Row("c", 2.0))).await?;
+            Ok(())
+        });
+
+        let chunks: Vec<Bytes> = csv_stream(rows, chrono_tz::UTC)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let bytes = chunks.concat();
+        assert_eq!(&bytes[..3], UTF8_BOM);
+        assert_eq!(
+            std::str::from_utf8(&bytes[3..]).unwrap(),
+            "name,value\n\"a,b\",1.5\nc,2\n"
+        );
+    }
+}
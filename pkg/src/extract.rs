@@ -0,0 +1,125 @@
+use crate::error::AppError;
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::{ConnectInfo, FromRequest, RequestParts},
+    BoxError, Json,
+};
+use serde::de::DeserializeOwned;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use validator::Validate;
+
+/**
+ * 解析json body後自動執行validate, 失敗時回傳各欄位的錯誤
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req)
+            .await
+            .map_err(|e| AppError::bad_request(e.to_string()))?;
+        value.validate()?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+/**
+ * 是否信任反向代理的X-Forwarded-For, 由TRUST_PROXY_HEADERS設定
+ */
+fn trust_proxy_headers() -> bool {
+    std::env::var("TRUST_PROXY_HEADERS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+/**
+ * 前面有幾層可信任的反向代理, 由TRUSTED_PROXY_HOPS設定, 預設1
+ */
+fn trusted_proxy_hops() -> usize {
+    std::env::var("TRUSTED_PROXY_HOPS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|hops| *hops > 0)
+        .unwrap_or(1)
+}
+
+/**
+ * 從X-Forwarded-For右邊往左數第hops個ip
+ * 左邊的值可由client任意偽造, 只有可信任代理附加在右邊的才可信
+ */
+fn forwarded_for(value: &str, hops: usize) -> Option<IpAddr> {
+    value
+        .split(',')
+        .rev()
+        .nth(hops.checked_sub(1)?)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/**
+ * client ip, 需以into_make_service_with_connect_info啟動server
+ * 取不到時為0.0.0.0
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+#[async_trait]
+impl<B> FromRequest<B> for ClientIp
+where
+    B: Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if trust_proxy_headers() {
+            let forwarded = req
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| forwarded_for(v, trusted_proxy_hops()));
+            if let Some(ip) = forwarded {
+                return Ok(ClientIp(ip));
+            }
+        }
+
+        let ip = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        Ok(ClientIp(ip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_forwarded_for() {
+        assert_eq!(
+            forwarded_for("1.2.3.4, 203.0.113.7", 1),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_for("1.2.3.4, 203.0.113.7, 10.0.0.1", 2),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(forwarded_for("::1", 1), Some("::1".parse().unwrap()));
+        assert_eq!(forwarded_for("203.0.113.7", 2), None);
+        assert_eq!(forwarded_for("203.0.113.7", 0), None);
+        assert_eq!(forwarded_for("unknown", 1), None);
+    }
+}
@@ -1,6 +1,8 @@
-use chrono::{DateTime, Local, ParseError};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, ParseError, TimeZone};
+use validator::ValidationError;
 
 const LAYOUT: &'static str = "%Y-%m-%d %H:%M:%S";
+const DATE_LAYOUT: &str = "%Y-%m-%d";
 
 //get current datetime
 pub fn current_dt() -> (DateTime<Local>, String) {
@@ -19,3 +21,77 @@ pub fn str_to_dt(dtstr: &str) -> Result<DateTime<Local>, ParseError> {
     let dt = dtstr.parse::<DateTime<Local>>();
     dt
 }
+
+//str(Y-m-d H:M:S 或 Y-m-d) to local dt, 只有日期時為當天00:00:00
+pub fn parse_local(dtstr: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(dtstr, LAYOUT)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(dtstr, DATE_LAYOUT)
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+//今天00:00:00(本地時間)
+pub fn today_start() -> DateTime<Local> {
+    let now = Local::now();
+    now.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .unwrap_or(now)
+}
+
+//是否只有日期
+pub fn is_date_only(dtstr: &str) -> bool {
+    NaiveDate::parse_from_str(dtstr, DATE_LAYOUT).is_ok()
+}
+
+//validator用, 日期格式同parse_local
+pub fn validate_date(value: &str) -> Result<(), ValidationError> {
+    match parse_local(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("date")),
+    }
+}
+
+//查詢區間 [start, end), end只有日期時包含當天
+pub fn range(
+    start: Option<&str>,
+    end: Option<&str>,
+) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+    let start = start.and_then(parse_local);
+    let end = end.and_then(|end| {
+        let dt = parse_local(end)?;
+        if is_date_only(end) {
+            Some(dt + Duration::days(1))
+        } else {
+            Some(dt + Duration::seconds(1))
+        }
+    });
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_and_datetime() {
+        let day = parse_local("2022-08-20").unwrap();
+        assert_eq!(day.format(LAYOUT).to_string(), "2022-08-20 00:00:00");
+        let dt = parse_local("2022-08-20 13:45:00").unwrap();
+        assert_eq!(dt.format(LAYOUT).to_string(), "2022-08-20 13:45:00");
+        assert!(parse_local("2022/08/20").is_none());
+        assert!(is_date_only("2022-08-20"));
+        assert!(!is_date_only("2022-08-20 13:45:00"));
+
+        let (start, end) = range(None, Some("2022-08-20 13:45:00"));
+        assert!(start.is_none());
+        assert_eq!(
+            end.unwrap().format(LAYOUT).to_string(),
+            "2022-08-20 13:45:01"
+        );
+    }
+}
@@ -2,62 +2,255 @@ use axum::{
     async_trait,
     extract::{FromRequest, RequestParts, TypedHeader},
     headers::{authorization::Bearer, Authorization},
-    http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use crate::error::AppError;
+use jsonwebtoken::{
+    decode, decode_header, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header,
+    TokenData, Validation,
+};
 use once_cell::sync::Lazy;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::default::Default;
+use rsa::{pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, PublicKeyParts, RsaPublicKey};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Arc;
 
 /**
  * make token
  */
 pub fn encode_token(claims: Claims) -> jsonwebtoken::errors::Result<String> {
-    encode(&Header::default(), &claims, &KEYS.encoding)
+    KEYS.encode(&claims)
 }
 
 /**
  * decode token
  */
 pub fn decode_token(token: &str) -> jsonwebtoken::errors::Result<TokenData<Claims>> {
-    decode::<Claims>(token, &KEYS.decoding, &Validation::default())
+    KEYS.decode(token)
+}
+
+/**
+ * 公開的驗證金鑰 (JWKS)
+ */
+pub fn jwks() -> Value {
+    KEYS.jwks()
+}
+
+/**
+ * 啟動時載入金鑰, 設定錯誤時直接中止
+ */
+pub fn init() {
+    Lazy::force(&KEYS);
+}
+
+/**
+ * GET /.well-known/jwks.json
+ */
+pub async fn jwks_handler() -> Json<Value> {
+    Json(jwks())
 }
 
 /**
  * Keys
  */
-static KEYS: Lazy<Keys> = Lazy::new(|| {
-    let secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    Keys::new(secret.as_bytes())
-});
+static KEYS: Lazy<Keys> = Lazy::new(|| Keys::from_env().expect("invalid jwt key config"));
+
+/**
+ * 簽章金鑰
+ */
+struct SigningKey {
+    kid: Option<String>,
+    alg: Algorithm,
+    key: EncodingKey,
+}
+
+/**
+ * 驗證金鑰
+ */
+struct VerifyingKey {
+    alg: Algorithm,
+    key: DecodingKey,
+}
 
 pub struct Keys {
-    encoding: EncodingKey,
-    decoding: DecodingKey,
+    signing: SigningKey,
+    //依kid選擇驗證金鑰, HS256不帶kid時以空字串為key
+    verifying: HashMap<String, VerifyingKey>,
+    jwks: Vec<Value>,
 }
 
 impl Keys {
     //encode & decode
     pub fn new(secret: &[u8]) -> Self {
+        let mut verifying = HashMap::new();
+        verifying.insert(
+            String::new(),
+            VerifyingKey {
+                alg: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret),
+            },
+        );
         Self {
-            encoding: EncodingKey::from_secret(secret),
-            decoding: DecodingKey::from_secret(secret),
+            signing: SigningKey {
+                kid: None,
+                alg: Algorithm::HS256,
+                key: EncodingKey::from_secret(secret),
+            },
+            verifying,
+            jwks: vec![],
+        }
+    }
+
+    /**
+     * RS256 / EdDSA, 以kid簽章, public_keys為所有仍有效的(kid, public pem)
+     */
+    pub fn asymmetric(
+        alg: Algorithm,
+        kid: &str,
+        private_pem: &[u8],
+        public_keys: &[(String, Vec<u8>)],
+    ) -> anyhow::Result<Self> {
+        let key = match alg {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(private_pem)?,
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(private_pem)?,
+            _ => anyhow::bail!("unsupported jwt algorithm {:?}", alg),
+        };
+
+        let mut verifying = HashMap::new();
+        let mut jwks = vec![];
+        for (pub_kid, pem) in public_keys {
+            let (key, jwk) = match alg {
+                Algorithm::RS256 => (DecodingKey::from_rsa_pem(pem)?, rsa_jwk(pub_kid, pem)?),
+                _ => (DecodingKey::from_ed_pem(pem)?, ed_jwk(pub_kid, pem)?),
+            };
+            verifying.insert(pub_kid.clone(), VerifyingKey { alg, key });
+            jwks.push(jwk);
+        }
+        if !verifying.contains_key(kid) {
+            anyhow::bail!("public key of signing kid {} is missing", kid);
+        }
+
+        Ok(Self {
+            signing: SigningKey {
+                kid: Some(kid.to_owned()),
+                alg,
+                key,
+            },
+            verifying,
+            jwks,
+        })
+    }
+
+    /**
+     * JWT_ALG: HS256(預設) => JWT_SECRET
+     *          RS256 / EdDSA => JWT_KID, JWT_PRIVATE_KEY, JWT_PUBLIC_KEYS (kid=path,kid=path)
+     */
+    pub fn from_env() -> anyhow::Result<Self> {
+        let alg = std::env::var("JWT_ALG").unwrap_or_else(|_| "HS256".to_owned());
+        let alg = match alg.as_str() {
+            "HS256" => {
+                let secret = std::env::var("JWT_SECRET")
+                    .map_err(|_| anyhow::anyhow!("JWT_SECRET must be set"))?;
+                return Ok(Self::new(secret.as_bytes()));
+            }
+            "RS256" => Algorithm::RS256,
+            "EdDSA" => Algorithm::EdDSA,
+            other => anyhow::bail!("unsupported JWT_ALG {}", other),
+        };
+
+        let env =
+            |name: &str| std::env::var(name).map_err(|_| anyhow::anyhow!("{} must be set", name));
+        let kid = env("JWT_KID")?;
+        let private_pem = std::fs::read(env("JWT_PRIVATE_KEY")?)?;
+        let mut public_keys = vec![];
+        for item in env("JWT_PUBLIC_KEYS")?.split(',').filter(|v| !v.is_empty()) {
+            let (pub_kid, path) = item
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("invalid JWT_PUBLIC_KEYS item {}", item))?;
+            public_keys.push((pub_kid.trim().to_owned(), std::fs::read(path.trim())?));
         }
+
+        Self::asymmetric(alg, &kid, &private_pem, &public_keys)
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> jsonwebtoken::errors::Result<String> {
+        let mut header = Header::new(self.signing.alg);
+        header.kid = self.signing.kid.clone();
+        encode(&header, claims, &self.signing.key)
+    }
+
+    /**
+     * 依header的kid選擇金鑰, 演算法必須與金鑰一致
+     */
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> jsonwebtoken::errors::Result<TokenData<T>> {
+        let header = decode_header(token)?;
+        let kid = header.kid.unwrap_or_default();
+        let key = self
+            .verifying
+            .get(&kid)
+            .ok_or_else(|| jsonwebtoken::errors::Error::from(ErrorKind::InvalidToken))?;
+        decode::<T>(token, &key.key, &Validation::new(key.alg))
+    }
+
+    pub fn jwks(&self) -> Value {
+        json!({ "keys": self.jwks })
     }
 }
 
+fn b64url(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/**
+ * RSA public key => JWK
+ */
+fn rsa_jwk(kid: &str, pem: &[u8]) -> anyhow::Result<Value> {
+    let pem = std::str::from_utf8(pem)?;
+    let key =
+        RsaPublicKey::from_public_key_pem(pem).or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))?;
+    Ok(json!({
+        "kty": "RSA",
+        "use": "sig",
+        "alg": "RS256",
+        "kid": kid,
+        "n": b64url(&key.n().to_bytes_be()),
+        "e": b64url(&key.e().to_bytes_be()),
+    }))
+}
+
+/**
+ * Ed25519 public key => JWK, SPKI DER的最後32 bytes為public key
+ */
+fn ed_jwk(kid: &str, pem: &[u8]) -> anyhow::Result<Value> {
+    let der = pem::parse(pem)?.contents;
+    if der.len() < 32 {
+        anyhow::bail!("invalid ed25519 public key");
+    }
+    Ok(json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "use": "sig",
+        "alg": "EdDSA",
+        "kid": kid,
+        "x": b64url(&der[der.len() - 32..]),
+    }))
+}
+
 /**
  * Claims
  */
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub account: String, //user account
-    pub role: i8,        //user role
+    pub role: i8,        //user role, 驗證時以DB目前的角色覆蓋
+    pub sid: String,     //session id
     pub exp: usize,      //ExpiresAt
 }
 
@@ -67,6 +260,17 @@ impl Display for Claims {
     }
 }
 
+/**
+ * session查詢, 由user模組實作並以Extension注入
+ */
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /**
+     * session未撤銷且用戶為啟用狀態時回傳用戶目前的角色, 否則為None
+     */
+    async fn active_role(&self, sid: &str, account: &str) -> anyhow::Result<Option<i8>>;
+}
+
 /**
  * Error handle
  */
@@ -76,6 +280,8 @@ pub enum AuthError {
     MissingCredentials,
     TokenCreation,
     InvalidToken,
+    RevokedToken,
+    SessionUnavailable,
 }
 
 /**
@@ -97,22 +303,144 @@ where
 
         // Decode the user data
         let token_data = decode_token(bearer.token()).map_err(|_| AuthError::InvalidToken)?;
+        let mut claims = token_data.claims;
+
+        // 檢查session是否已撤銷或用戶已停用, 角色以目前的為準(降權後舊token立即失去權限)
+        let store = req
+            .extensions()
+            .get::<Arc<dyn SessionStore>>()
+            .cloned()
+            .ok_or(AuthError::SessionUnavailable)?;
+        let role = store
+            .active_role(&claims.sid, &claims.account)
+            .await
+            .map_err(|e| {
+                tracing::error!("session check failed: {}", e);
+                AuthError::SessionUnavailable
+            })?;
+        claims.role = role.ok_or(AuthError::RevokedToken)?;
 
-        Ok(token_data.claims)
+        Ok(claims)
+    }
+}
+
+impl From<AuthError> for AppError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::WrongCredentials => AppError::unauthorized("Wrong credentials"),
+            AuthError::MissingCredentials => AppError::bad_request("Missing credentials"),
+            AuthError::TokenCreation => AppError::Internal(anyhow::anyhow!("Token creation error")),
+            AuthError::InvalidToken => AppError::unauthorized("Invalid token"),
+            AuthError::RevokedToken => AppError::unauthorized("Token revoked"),
+            AuthError::SessionUnavailable => {
+                AppError::Internal(anyhow::anyhow!("Session store unavailable"))
+            }
+        }
     }
 }
 
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AuthError::WrongCredentials => (StatusCode::UNAUTHORIZED, "Wrong credentials"),
-            AuthError::MissingCredentials => (StatusCode::BAD_REQUEST, "Missing credentials"),
-            AuthError::TokenCreation => (StatusCode::INTERNAL_SERVER_ERROR, "Token creation error"),
-            AuthError::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid token"),
-        };
-        let body = Json(json!({
-            "error": error_message,
-        }));
-        (status, body).into_response()
+        AppError::from(self).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims() -> Claims {
+        Claims {
+            account: "alice".to_owned(),
+            role: 1,
+            sid: "sid-1".to_owned(),
+            exp: (chrono::Utc::now().timestamp() + 60) as usize,
+        }
+    }
+
+    #[test]
+    fn hs256_round_trip() {
+        let keys = Keys::new(b"secret");
+        let token = keys.encode(&claims()).unwrap();
+        let data = keys.decode::<Claims>(&token).unwrap();
+        assert_eq!(data.claims.account, "alice");
+        assert!(data.header.kid.is_none());
+        assert!(Keys::new(b"other").decode::<Claims>(&token).is_err());
+        assert_eq!(keys.jwks(), json!({ "keys": [] }));
+    }
+
+    #[test]
+    fn rs256_rotation_and_jwks() {
+        let public = include_bytes!("../testdata/rs256_public.pem").to_vec();
+        let private = include_bytes!("../testdata/rs256_private.pem");
+        let keys = Keys::asymmetric(
+            Algorithm::RS256,
+            "2022-08",
+            private,
+            &[("2022-08".to_owned(), public.clone())],
+        )
+        .unwrap();
+
+        let token = keys.encode(&claims()).unwrap();
+        assert_eq!(
+            decode_header(&token).unwrap().kid.as_deref(),
+            Some("2022-08")
+        );
+        assert_eq!(keys.decode::<Claims>(&token).unwrap().claims.sid, "sid-1");
+
+        //舊kid移除後token失效
+        let rotated = Keys::asymmetric(
+            Algorithm::RS256,
+            "2022-09",
+            private,
+            &[("2022-09".to_owned(), public)],
+        )
+        .unwrap();
+        assert!(rotated.decode::<Claims>(&token).is_err());
+
+        let jwks = keys.jwks();
+        assert_eq!(jwks["keys"][0]["kty"], "RSA");
+        assert_eq!(jwks["keys"][0]["e"], "AQAB");
+        assert!(jwks["keys"][0]["n"]
+            .as_str()
+            .unwrap()
+            .starts_with("uFEKxZ7sFwtad"));
+
+        //HS256 token不能通過RS256金鑰驗證
+        let hs = Keys::new(b"secret").encode(&claims()).unwrap();
+        assert!(keys.decode::<Claims>(&hs).is_err());
+    }
+
+    #[test]
+    fn eddsa_round_trip_and_jwks() {
+        let keys = Keys::asymmetric(
+            Algorithm::EdDSA,
+            "ed-1",
+            include_bytes!("../testdata/ed25519_private.pem"),
+            &[(
+                "ed-1".to_owned(),
+                include_bytes!("../testdata/ed25519_public.pem").to_vec(),
+            )],
+        )
+        .unwrap();
+
+        let token = keys.encode(&claims()).unwrap();
+        assert_eq!(
+            keys.decode::<Claims>(&token).unwrap().claims.account,
+            "alice"
+        );
+        assert_eq!(
+            keys.jwks()["keys"][0]["x"],
+            "9uzyu8ClsQtLuCqcBp6T18oQA-07WHY3VrfO6EHQK5o"
+        );
+
+        //簽章kid沒有對應的public key
+        assert!(Keys::asymmetric(
+            Algorithm::EdDSA,
+            "ed-2",
+            include_bytes!("../testdata/ed25519_private.pem"),
+            &[],
+        )
+        .is_err());
     }
 }
@@ -1,4 +1,13 @@
+pub mod crypto;
 pub mod db;
+pub mod error;
+pub mod exchange;
+pub mod export;
+pub mod extract;
 pub mod eztime;
 pub mod jwt;
+pub mod page;
+pub mod pnl;
+pub mod rbac;
 pub mod responder;
+pub mod totp;
@@ -0,0 +1,170 @@
+use crate::error::AppError;
+use anyhow::Result;
+use axum::{
+    async_trait,
+    extract::{FromRequest, Query, RequestParts},
+};
+use sea_orm::{
+    ConnectionTrait, EntityTrait, FromQueryResult, Order, PaginatorTrait, QueryOrder, Select,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use validator::Validate;
+
+//每頁預設筆數
+pub const DEFAULT_PER_PAGE: u64 = 20;
+//每頁最多筆數
+pub const MAX_PER_PAGE: u64 = 100;
+
+/**
+ * 排序方向
+ */
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl From<SortDirection> for Order {
+    fn from(direction: SortDirection) -> Self {
+        match direction {
+            SortDirection::Asc => Order::Asc,
+            SortDirection::Desc => Order::Desc,
+        }
+    }
+}
+
+/**
+ * 可排序的欄位白名單, 以enum定義, 不在白名單內的欄位會被拒絕
+ * Default為未指定sort時使用的欄位
+ */
+pub trait SortField<E: EntityTrait>: DeserializeOwned + Default + Copy + Send {
+    fn column(&self) -> E::Column;
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(bound = "S: DeserializeOwned")]
+struct RawPageQuery<S> {
+    #[validate(range(min = 1))]
+    page: Option<u64>,
+    #[validate(range(min = 1, max = "MAX_PER_PAGE"))]
+    per_page: Option<u64>,
+    sort: Option<S>,
+    direction: Option<SortDirection>,
+}
+
+/**
+ * 分頁與排序的query extractor: ?page=1&per_page=20&sort=created_at&direction=desc
+ * 其他篩選條件另外用Query解析
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct PageQuery<S> {
+    pub page: u64,
+    pub per_page: u64,
+    pub sort: S,
+    pub direction: SortDirection,
+}
+
+#[async_trait]
+impl<B, S> FromRequest<B> for PageQuery<S>
+where
+    B: Send,
+    S: DeserializeOwned + Default + Send,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<RawPageQuery<S>>::from_request(req)
+            .await
+            .map_err(|e| AppError::bad_request(e.to_string()))?;
+        raw.validate()?;
+
+        Ok(PageQuery {
+            page: raw.page.unwrap_or(1),
+            per_page: raw.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            sort: raw.sort.unwrap_or_default(),
+            direction: raw.direction.unwrap_or_default(),
+        })
+    }
+}
+
+/**
+ * 分頁結果
+ */
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+impl<T> Page<T> {
+    /**
+     * 轉換items, 例如Model轉成回傳用的Info
+     */
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            per_page: self.per_page,
+        }
+    }
+
+    pub fn last_page(&self) -> u64 {
+        last_page(self.total, self.per_page)
+    }
+}
+
+/**
+ * 最後一頁, 沒有資料時為1
+ */
+pub fn last_page(total: u64, per_page: u64) -> u64 {
+    if per_page == 0 {
+        return 1;
+    }
+    total.div_ceil(per_page).max(1)
+}
+
+/**
+ * 依PageQuery排序後分頁查詢, 回傳該頁資料與總筆數
+ */
+pub async fn paginate<E, S, C>(
+    db: &C,
+    select: Select<E>,
+    query: &PageQuery<S>,
+) -> Result<Page<E::Model>>
+where
+    E: EntityTrait,
+    E::Model: FromQueryResult + Sized + Send + Sync,
+    S: SortField<E>,
+    C: ConnectionTrait,
+{
+    let paginator = select
+        .order_by(query.sort.column(), query.direction.into())
+        .paginate(db, query.per_page as usize);
+    let total = paginator.num_items().await? as u64;
+    let items = paginator.fetch_page((query.page - 1) as usize).await?;
+
+    Ok(Page {
+        items,
+        total,
+        page: query.page,
+        per_page: query.per_page,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_last_page() {
+        assert_eq!(last_page(0, 20), 1);
+        assert_eq!(last_page(20, 20), 1);
+        assert_eq!(last_page(21, 20), 2);
+        assert_eq!(last_page(5, 0), 1);
+    }
+}
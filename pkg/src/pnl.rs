@@ -0,0 +1,127 @@
+use rust_decimal::prelude::*;
+
+//寫回DB時保留的小數位數
+const STORE_DP: u32 = 8;
+
+/**
+ * f64轉Decimal, NaN / inf 視為0
+ */
+pub fn decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+/**
+ * 手續費率字串(例如 symbols.taker_fee "0.0006")轉Decimal, 格式錯誤視為0
+ */
+pub fn fee_rate(rate: &str) -> Decimal {
+    Decimal::from_str(rate.trim()).unwrap_or_default()
+}
+
+/**
+ * Decimal轉f64寫回DB
+ */
+pub fn to_f64(value: Decimal) -> f64 {
+    value.round_dp(STORE_DP).to_f64().unwrap_or(0.0)
+}
+
+/**
+ * 方向 1 => 多單, 2 => 空單
+ */
+fn direction(side: i8) -> Decimal {
+    match side {
+        2 => Decimal::NEGATIVE_ONE,
+        _ => Decimal::ONE,
+    }
+}
+
+/**
+ * 單邊成交: 價格, 數量, 手續費率
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Fill {
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub fee_rate: Decimal,
+}
+
+impl Fill {
+    pub fn new(price: f64, qty: f64, fee_rate: Decimal) -> Fill {
+        Fill {
+            price: decimal(price),
+            qty: decimal(qty),
+            fee_rate,
+        }
+    }
+
+    pub fn fee(&self, qty: Decimal) -> Decimal {
+        self.price * qty * self.fee_rate
+    }
+}
+
+/**
+ * 已實現盈虧 = 價差 * 平倉數量 - 開倉與平倉手續費, side為開倉方向
+ */
+pub fn realised(side: i8, open: &Fill, close: &Fill) -> Decimal {
+    let qty = open.qty.min(close.qty);
+    let gross = (close.price - open.price) * qty * direction(side);
+    gross - fees(open, close)
+}
+
+/**
+ * 開倉與平倉手續費合計, 以平倉數量計算
+ */
+pub fn fees(open: &Fill, close: &Fill) -> Decimal {
+    let qty = open.qty.min(close.qty);
+    open.fee(qty) + close.fee(qty)
+}
+
+/**
+ * 未實現盈虧 = (標記價格 - 開倉價) * 持倉數量, 不含手續費
+ */
+pub fn unrealised(side: i8, open: &Fill, mark_price: Decimal) -> Decimal {
+    (mark_price - open.price) * open.qty * direction(side)
+}
+
+/**
+ * 盈虧百分比, 開倉價為0時回傳0
+ */
+pub fn percent(side: i8, open_price: f64, close_price: f64) -> Decimal {
+    let open_price = decimal(open_price);
+    if open_price.is_zero() {
+        return Decimal::ZERO;
+    }
+    (decimal(close_price) - open_price) * direction(side) / open_price * Decimal::ONE_HUNDRED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn realised_with_fees() {
+        let maker = fee_rate("0.0001");
+        let taker = fee_rate("0.0006");
+
+        //多單 20000 -> 21000, 0.1張
+        let open = Fill::new(20000.0, 0.1, maker);
+        let close = Fill::new(21000.0, 0.1, taker);
+        // 100 - 0.2 - 1.26
+        assert_eq!(realised(1, &open, &close), decimal(98.54));
+        assert_eq!(fees(&open, &close), decimal(1.46));
+
+        //空單同樣價格為虧損
+        assert_eq!(realised(2, &open, &close), decimal(-101.46));
+    }
+
+    #[test]
+    fn unrealised_and_percent() {
+        let open = Fill::new(0.3, 100.0, Decimal::ZERO);
+        assert_eq!(unrealised(1, &open, decimal(0.1)), decimal(-20.0));
+        assert_eq!(unrealised(2, &open, decimal(0.1)), decimal(20.0));
+
+        assert_eq!(percent(1, 200.0, 210.0), decimal(5.0));
+        assert_eq!(percent(2, 200.0, 210.0), decimal(-5.0));
+        assert_eq!(percent(1, 0.0, 210.0), Decimal::ZERO);
+        assert_eq!(to_f64(decimal(0.1) + decimal(0.2)), 0.3);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::error::AppError;
+use crate::jwt::Claims;
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::marker::PhantomData;
+
+/**
+ * 用戶角色 1 => user, 99 => admin
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Role {
+    User = 1,
+    Admin = 99,
+}
+
+impl Role {
+    pub fn from_i8(role: i8) -> Option<Role> {
+        match role {
+            1 => Some(Role::User),
+            99 => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn to_i8(&self) -> i8 {
+        *self as i8
+    }
+
+    /**
+     * 角色擁有的權限
+     */
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Admin => Permission::ALL,
+            Role::User => &[],
+        }
+    }
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+/**
+ * 權限
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    UsersCreate,
+    UsersManage,
+    StrategiesWrite,
+    SymbolsSync,
+    OrdersReadAll,
+    ReportsReadAll,
+    RiskManage,
+}
+
+impl Permission {
+    pub const ALL: &'static [Permission] = &[
+        Permission::UsersCreate,
+        Permission::UsersManage,
+        Permission::StrategiesWrite,
+        Permission::SymbolsSync,
+        Permission::OrdersReadAll,
+        Permission::ReportsReadAll,
+        Permission::RiskManage,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::UsersCreate => "users:create",
+            Permission::UsersManage => "users:manage",
+            Permission::StrategiesWrite => "strategies:write",
+            Permission::SymbolsSync => "symbols:sync",
+            Permission::OrdersReadAll => "orders:read_all",
+            Permission::ReportsReadAll => "reports:read_all",
+            Permission::RiskManage => "risk:manage",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/**
+ * 角色標記, 給RequireRole使用
+ */
+pub trait RoleMarker: Send + Sync + 'static {
+    const ROLE: Role;
+}
+
+/**
+ * 權限標記, 給RequirePermission使用
+ */
+pub trait PermissionMarker: Send + Sync + 'static {
+    const PERMISSION: Permission;
+}
+
+pub struct Admin;
+
+impl RoleMarker for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/**
+ * 各權限的標記型別, 例如 RequirePermission<perm::StrategiesWrite>
+ */
+pub mod perm {
+    use super::{Permission, PermissionMarker};
+
+    macro_rules! permission_marker {
+        ($($name:ident),* $(,)?) => {
+            $(
+                pub struct $name;
+
+                impl PermissionMarker for $name {
+                    const PERMISSION: Permission = Permission::$name;
+                }
+            )*
+        };
+    }
+
+    permission_marker!(
+        UsersCreate,
+        UsersManage,
+        StrategiesWrite,
+        SymbolsSync,
+        OrdersReadAll,
+        ReportsReadAll,
+        RiskManage
+    );
+}
+
+/**
+ * 取得用戶目前的角色(Claims取出時已依session查詢更新), 未知的角色視為沒有權限
+ */
+fn role_of(claims: &Claims) -> Result<Role, AppError> {
+    Role::from_i8(claims.role).ok_or_else(|| AppError::forbidden("Permission error"))
+}
+
+/**
+ * 要求角色等級, 例如 RequireRole<Admin>
+ */
+pub struct RequireRole<R: RoleMarker>(pub Claims, PhantomData<R>);
+
+#[async_trait]
+impl<B, R> FromRequest<B> for RequireRole<R>
+where
+    B: Send,
+    R: RoleMarker,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request(req).await?;
+        if role_of(&claims)? < R::ROLE {
+            return Err(AppError::forbidden("Permission error"));
+        }
+        Ok(RequireRole(claims, PhantomData))
+    }
+}
+
+/**
+ * 要求權限, 例如 RequirePermission<perm::UsersCreate>
+ */
+pub struct RequirePermission<P: PermissionMarker>(pub Claims, PhantomData<P>);
+
+#[async_trait]
+impl<B, P> FromRequest<B> for RequirePermission<P>
+where
+    B: Send,
+    P: PermissionMarker,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request(req).await?;
+        if !role_of(&claims)?.has(P::PERMISSION) {
+            return Err(AppError::forbidden("Permission error"));
+        }
+        Ok(RequirePermission(claims, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_permissions() {
+        assert_eq!(Role::from_i8(99), Some(Role::Admin));
+        assert_eq!(Role::from_i8(5), None);
+        assert!(Role::Admin > Role::User);
+        assert!(Role::Admin.has(Permission::UsersCreate));
+        assert!(!Role::User.has(Permission::StrategiesWrite));
+        assert_eq!(
+            <perm::OrdersReadAll as PermissionMarker>::PERMISSION.to_string(),
+            "orders:read_all"
+        );
+    }
+}
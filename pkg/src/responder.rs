@@ -1,3 +1,6 @@
+use crate::page::{last_page, Page};
+use axum::Json;
+use once_cell::sync::Lazy;
 use serde::Serialize;
 
 use std::fmt;
@@ -9,6 +12,8 @@ pub trait Data: Serialize + Sized {}
 pub struct Detail(pub String);
 impl Data for Detail {}
 
+impl<D: Data> Data for Vec<D> {}
+
 /**
  * content struct
  */
@@ -21,16 +26,26 @@ pub struct Content<D> {
 
 /**
  * paginate struct
+ * corrent_page為舊版的拼字, 只在PAGINATE_LEGACY_FIELDS=true時一併輸出
  */
 #[derive(Serialize)]
 pub struct Paginate<C> {
     #[serde(flatten)]
     pub content: C,
-    pub total: i32,
-    pub per_page: i32,
-    pub corrent_page: i32,
+    pub total: u64,
+    pub per_page: u64,
+    pub current_page: u64,
+    pub last_page: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corrent_page: Option<u64>,
 }
 
+static LEGACY_FIELDS: Lazy<bool> = Lazy::new(|| {
+    std::env::var("PAGINATE_LEGACY_FIELDS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+});
+
 /**
  * make success resp data
  */
@@ -45,6 +60,14 @@ pub fn success<D: Data>(data: D) -> (i32, Content<D>) {
     (status.to_int(), cnt)
 }
 
+/**
+ * make success json response
+ */
+pub fn ok<D: Data>(data: D) -> Json<Content<D>> {
+    let (_, cnt) = success(data);
+    Json(cnt)
+}
+
 /**
  * make failed resp data
  */
@@ -63,9 +86,9 @@ pub fn failed<D: Data>(status: StatusCode, data: D) -> (i32, Content<D>) {
  */
 pub fn pagination(
     data: impl Data,
-    page: i32,
-    size: i32,
-    total: i32,
+    page: u64,
+    size: u64,
+    total: u64,
 ) -> (i32, Paginate<Content<impl Data>>) {
     let status = StatusCode::StatusOK;
     let cnt = Content {
@@ -78,12 +101,33 @@ pub fn pagination(
         content: cnt,
         total: total,
         per_page: size,
-        corrent_page: page,
+        current_page: page,
+        last_page: last_page(total, size),
+        corrent_page: LEGACY_FIELDS.then_some(page),
     };
 
     (status.to_int(), pagin)
 }
 
+/**
+ * make paginate json response
+ */
+pub fn paged<D: Data>(page: Page<D>) -> Json<Paginate<Content<Vec<D>>>> {
+    let status = StatusCode::StatusOK;
+    Json(Paginate {
+        content: Content {
+            status: status.to_int(),
+            msg: status.to_string(),
+            data: page.items,
+        },
+        total: page.total,
+        per_page: page.per_page,
+        current_page: page.page,
+        last_page: last_page(page.total, page.per_page),
+        corrent_page: LEGACY_FIELDS.then_some(page.page),
+    })
+}
+
 /**
  * status code enum
  */
@@ -95,6 +139,7 @@ pub enum StatusCode {
     StatusDuplicate = 4002,
     StatusForbidden = 4003,
     StatusNotFound = 4004,
+    StatusUnauthorized = 4005,
     StatusInternal = 5000,
     StatusUnknownErr = 5001,
 }
@@ -117,6 +162,7 @@ impl fmt::Display for StatusCode {
             StatusCode::StatusDuplicate => write!(f, "Already exists"),
             StatusCode::StatusForbidden => write!(f, "Forbidden"),
             StatusCode::StatusNotFound => write!(f, "Resource not found"),
+            StatusCode::StatusUnauthorized => write!(f, "Unauthorized"),
             StatusCode::StatusInternal => write!(f, "Internal error"),
             StatusCode::StatusUnknownErr => write!(f, "Unknown error"),
         }
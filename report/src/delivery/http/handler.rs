@@ -0,0 +1,70 @@
+use crate::domain::{ReportContainer, ReportQuery};
+use axum::{
+    extract::{Extension, Query},
+    response::IntoResponse,
+};
+use pkg::{
+    error::{AppError, AppResult},
+    export::{self, ExportQuery},
+    jwt::Claims,
+    rbac::{Permission, Role},
+    responder::ok,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+/**
+ * 是否可查看所有用戶的報表
+ */
+fn can_read_all(claims: &Claims) -> bool {
+    matches!(Role::from_i8(claims.role), Some(role) if role.has(Permission::ReportsReadAll))
+}
+
+/**
+ * 一般用戶只能查自己的交易, admin可指定account或統計全部用戶
+ */
+fn restrict_account(query: &mut ReportQuery, claims: Claims) -> AppResult<()> {
+    if can_read_all(&claims) {
+        return Ok(());
+    }
+    match query.account.as_deref() {
+        Some(account) if account != claims.account => Err(AppError::forbidden("Permission error")),
+        _ => {
+            query.account = Some(claims.account);
+            Ok(())
+        }
+    }
+}
+
+/**
+ * 盈虧報表
+ */
+pub async fn pnl_report(
+    Query(mut query): Query<ReportQuery>,
+    claims: Claims,
+    Extension(c): Extension<Arc<ReportContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    restrict_account(&mut query, claims)?;
+
+    let report = c.report_ucase.pnl(query).await?;
+
+    Ok(ok(report))
+}
+
+/**
+ * 匯出盈虧報表 CSV / XLSX
+ */
+pub async fn export_pnl_report(
+    Query(mut query): Query<ReportQuery>,
+    Query(export): Query<ExportQuery>,
+    claims: Claims,
+    Extension(c): Extension<Arc<ReportContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    export.validate()?;
+    restrict_account(&mut query, claims)?;
+
+    let rows = c.report_ucase.export(query).await?;
+    export::respond(rows, &export, "pnl-report").await
+}
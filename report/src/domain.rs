@@ -0,0 +1,385 @@
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Datelike, Duration, Local};
+use chrono_tz::Tz;
+use entity::{orders::Model as OrderModel, symbols::Model as SymbolModel};
+use pkg::{
+    export::{timezone, validate_timezone, Cell, ExportRow, RowReceiver},
+    eztime::{self, validate_date},
+    pnl,
+    responder::Data,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use validator::Validate;
+
+/**
+ * Traits
+ */
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    async fn list_closed_orders(&self, query: &ReportQuery) -> Result<Vec<OrderModel>>;
+    async fn list_orders(&self, order_link_ids: Vec<String>) -> Result<Vec<OrderModel>>;
+    async fn list_symbols(&self, names: Vec<String>) -> Result<Vec<SymbolModel>>;
+}
+
+#[async_trait]
+pub trait ReportUsecase: Send + Sync {
+    async fn pnl(&self, query: ReportQuery) -> Result<PnlReport>;
+    async fn export(&self, query: ReportQuery) -> Result<RowReceiver<ReportRow>>;
+}
+
+/**
+ * Extension container
+ */
+pub struct ReportContainer {
+    pub report_ucase: Arc<dyn ReportUsecase>,
+}
+
+impl ReportContainer {
+    pub fn new(report_ucase: Arc<dyn ReportUsecase>) -> Arc<ReportContainer> {
+        Arc::new(ReportContainer { report_ucase })
+    }
+}
+
+/**
+ * 統計週期, 週以週一為起始
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn key(&self, dt: &DateTime<Tz>) -> String {
+        match self {
+            Period::Day => dt.format("%Y-%m-%d").to_string(),
+            Period::Week => {
+                let monday =
+                    dt.date_naive() - Duration::days(dt.weekday().num_days_from_monday() as i64);
+                monday.format("%Y-%m-%d").to_string()
+            }
+            Period::Month => dt.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/**
+ * 分組欄位
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    #[default]
+    StrategyName,
+    Symbol,
+}
+
+/**
+ * 報表查詢條件, account為None時統計全部用戶(需要 reports:read_all 權限)
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct ReportQuery {
+    #[validate(length(min = 4, max = 30))]
+    pub account: Option<String>,
+    #[validate(length(min = 1, max = 30))]
+    pub strategy_name: Option<String>,
+    #[validate(length(min = 1, max = 30))]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub period: Period,
+    #[serde(default)]
+    pub group_by: GroupBy,
+    #[validate(custom = "validate_date")]
+    pub start_date: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+    #[validate(custom = "validate_timezone")]
+    pub tz: Option<String>,
+}
+
+impl ReportQuery {
+    /**
+     * 平倉時間區間 [start, end)
+     */
+    pub fn closed_range(&self) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        eztime::range(self.start_date.as_deref(), self.end_date.as_deref())
+    }
+
+    /**
+     * 週期分組使用的時區, 未指定時使用 EXPORT_TIMEZONE, 預設UTC
+     */
+    pub fn timezone(&self) -> Tz {
+        timezone(self.tz.as_deref())
+    }
+}
+
+/**
+ * 一筆已平倉的交易
+ */
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub strategy_name: String,
+    pub symbol: String,
+    pub closed_at: DateTime<Local>,
+    pub pnl: Decimal,
+    pub fees: Decimal,
+}
+
+impl Trade {
+    fn group(&self, group_by: GroupBy) -> &str {
+        match group_by {
+            GroupBy::StrategyName => &self.strategy_name,
+            GroupBy::Symbol => &self.symbol,
+        }
+    }
+}
+
+/**
+ * 累計中的統計, 最大回撤以累計盈虧的高點計算
+ */
+#[derive(Debug, Default)]
+struct Accumulator {
+    trades: u64,
+    wins: u64,
+    losses: u64,
+    total_win: Decimal,
+    total_loss: Decimal,
+    fees: Decimal,
+    equity: Decimal,
+    peak: Decimal,
+    max_drawdown: Decimal,
+}
+
+impl Accumulator {
+    fn add(&mut self, trade: &Trade) {
+        self.trades += 1;
+        if trade.pnl > Decimal::ZERO {
+            self.wins += 1;
+            self.total_win += trade.pnl;
+        } else if trade.pnl < Decimal::ZERO {
+            self.losses += 1;
+            self.total_loss += trade.pnl;
+        }
+        self.fees += trade.fees;
+        self.equity += trade.pnl;
+        self.peak = self.peak.max(self.equity);
+        self.max_drawdown = self.max_drawdown.max(self.peak - self.equity);
+    }
+
+    fn stats(&self) -> PnlStats {
+        let ratio = |total: Decimal, count: u64| {
+            if count == 0 {
+                Decimal::ZERO
+            } else {
+                total / Decimal::from(count)
+            }
+        };
+        PnlStats {
+            trades: self.trades,
+            wins: self.wins,
+            losses: self.losses,
+            win_rate: pnl::to_f64(
+                (ratio(Decimal::from(self.wins), self.trades) * Decimal::ONE_HUNDRED).round_dp(2),
+            ),
+            realised_pnl: pnl::to_f64(self.equity),
+            average_win: pnl::to_f64(ratio(self.total_win, self.wins)),
+            average_loss: pnl::to_f64(ratio(self.total_loss, self.losses)),
+            max_drawdown: pnl::to_f64(self.max_drawdown),
+            fees: pnl::to_f64(self.fees),
+        }
+    }
+}
+
+/**
+ * 盈虧統計, win_rate為百分比
+ */
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PnlStats {
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub win_rate: f64,
+    pub realised_pnl: f64,
+    pub average_win: f64,
+    pub average_loss: f64,
+    pub max_drawdown: f64,
+    pub fees: f64,
+}
+
+/**
+ * 報表的一列: 週期 + 策略或合約
+ */
+#[derive(Serialize, Debug)]
+pub struct ReportRow {
+    pub period: String,
+    pub group: String,
+    #[serde(flatten)]
+    pub stats: PnlStats,
+}
+
+/**
+ * 盈虧報表
+ */
+#[derive(Serialize, Debug)]
+pub struct PnlReport {
+    pub period: Period,
+    pub group_by: GroupBy,
+    pub timezone: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub summary: PnlStats,
+    pub rows: Vec<ReportRow>,
+}
+
+impl Data for PnlReport {}
+
+/**
+ * 報表匯出的資料列
+ */
+impl ExportRow for ReportRow {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "period",
+            "group",
+            "trades",
+            "wins",
+            "losses",
+            "win_rate",
+            "realised_pnl",
+            "average_win",
+            "average_loss",
+            "max_drawdown",
+            "fees",
+        ]
+    }
+
+    fn cells(&self, _tz: &Tz) -> Vec<Cell> {
+        let stats = &self.stats;
+        vec![
+            self.period.as_str().into(),
+            self.group.as_str().into(),
+            stats.trades.into(),
+            stats.wins.into(),
+            stats.losses.into(),
+            stats.win_rate.into(),
+            stats.realised_pnl.into(),
+            stats.average_win.into(),
+            stats.average_loss.into(),
+            stats.max_drawdown.into(),
+            stats.fees.into(),
+        ]
+    }
+}
+
+/**
+ * 依週期(tz時區)與分組彙總, trades需依平倉時間排序
+ */
+pub fn aggregate(
+    trades: &[Trade],
+    period: Period,
+    group_by: GroupBy,
+    tz: &Tz,
+) -> (PnlStats, Vec<ReportRow>) {
+    let mut summary = Accumulator::default();
+    let mut groups: BTreeMap<(String, String), Accumulator> = BTreeMap::new();
+
+    for trade in trades {
+        summary.add(trade);
+        groups
+            .entry((
+                period.key(&trade.closed_at.with_timezone(tz)),
+                trade.group(group_by).to_owned(),
+            ))
+            .or_default()
+            .add(trade);
+    }
+
+    let rows = groups
+        .into_iter()
+        .map(|((period, group), acc)| ReportRow {
+            period,
+            group,
+            stats: acc.stats(),
+        })
+        .collect();
+
+    (summary.stats(), rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use chrono_tz::Asia::Taipei;
+
+    fn taipei(dt: &str) -> DateTime<Tz> {
+        NaiveDateTime::parse_from_str(dt, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_local_timezone(Taipei)
+            .unwrap()
+    }
+
+    fn trade(strategy_name: &str, closed_at: &str, pnl: f64) -> Trade {
+        Trade {
+            strategy_name: strategy_name.to_owned(),
+            symbol: "BTCUSDT".to_owned(),
+            closed_at: taipei(closed_at).with_timezone(&Local),
+            pnl: pnl::decimal(pnl),
+            fees: pnl::decimal(0.5),
+        }
+    }
+
+    #[test]
+    fn period_keys() {
+        let dt = taipei("2022-08-24 10:00:00");
+        assert_eq!(Period::Day.key(&dt), "2022-08-24");
+        assert_eq!(Period::Week.key(&dt), "2022-08-22");
+        assert_eq!(Period::Month.key(&dt), "2022-08");
+    }
+
+    #[test]
+    fn aggregate_stats() {
+        let trades = vec![
+            trade("trend", "2022-08-01 09:00:00", 100.0),
+            trade("trend", "2022-08-02 09:00:00", -30.0),
+            trade("grid", "2022-08-02 10:00:00", 10.0),
+            trade("trend", "2022-08-03 09:00:00", -50.0),
+            // UTC為8/31, 台北時間為9月
+            trade("trend", "2022-09-01 02:00:00", 40.0),
+        ];
+
+        let (summary, rows) = aggregate(&trades, Period::Month, GroupBy::StrategyName, &Taipei);
+        assert_eq!(summary.trades, 5);
+        assert_eq!(summary.win_rate, 60.0);
+        assert_eq!(summary.realised_pnl, 70.0);
+        // 高點100 -> 30
+        assert_eq!(summary.max_drawdown, 70.0);
+        assert_eq!(summary.fees, 2.5);
+
+        let keys: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|row| (row.period.as_str(), row.group.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("2022-08", "grid"),
+                ("2022-08", "trend"),
+                ("2022-09", "trend")
+            ]
+        );
+
+        let trend = &rows[1].stats;
+        assert_eq!(trend.trades, 3);
+        assert_eq!(trend.average_win, 100.0);
+        assert_eq!(trend.average_loss, -40.0);
+        assert_eq!(trend.max_drawdown, 80.0);
+    }
+}
@@ -0,0 +1,34 @@
+mod delivery;
+pub mod domain;
+mod repository;
+pub mod usecase;
+
+pub mod router {
+    use crate::{
+        delivery::http::handler::{export_pnl_report, pnl_report},
+        domain::ReportContainer,
+        repository::mysql::report_repo::ReportRepo,
+        usecase::report_ucase::ReportUcase,
+    };
+    use axum::{extract::Extension, routing::get, Router};
+
+    use pkg::db::ORM;
+    use std::sync::Arc;
+
+    /**
+     * new handler
+     */
+    pub fn new(orm: Arc<dyn ORM>) -> Router {
+        let report_repo = ReportRepo::new(orm);
+        let report_ucase = ReportUcase::new(report_repo);
+        let report_container = ReportContainer::new(report_ucase);
+
+        let report_router = Router::new()
+            .route("/pnl", get(pnl_report))
+            .route("/pnl/export", get(export_pnl_report));
+
+        Router::new()
+            .nest("/v1/reports", report_router)
+            .layer(Extension(report_container))
+    }
+}
@@ -0,0 +1,76 @@
+use crate::domain::{ReportQuery, ReportRepository};
+use async_trait::async_trait;
+use entity::{orders, prelude::*, symbols};
+use order::domain::{OrderState, ACTION_CLOSE};
+use pkg::db::ORM;
+use sea_orm::{prelude::*, QueryOrder};
+use std::sync::Arc;
+
+pub struct ReportRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl ReportRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn ReportRepository> {
+        Arc::new(ReportRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl ReportRepository for ReportRepo {
+    /**
+     * 已確認的平倉單, 依平倉時間排序
+     */
+    async fn list_closed_orders(&self, query: &ReportQuery) -> anyhow::Result<Vec<orders::Model>> {
+        let db = self.mysql.get_db().await;
+        let (start, end) = query.closed_range();
+        let mut select = Orders::find()
+            .filter(orders::Column::Action.eq(ACTION_CLOSE))
+            .filter(orders::Column::State.eq(OrderState::Confirmed.to_i8()));
+        if let Some(account) = query.account.clone() {
+            select = select.filter(orders::Column::UserAccount.eq(account));
+        }
+        if let Some(strategy_name) = query.strategy_name.clone() {
+            select = select.filter(orders::Column::StrategyName.eq(strategy_name));
+        }
+        if let Some(symbol) = query.symbol.clone() {
+            select = select.filter(orders::Column::Symbol.eq(symbol));
+        }
+        if let Some(start) = start {
+            select = select.filter(orders::Column::CreatedAt.gte(start));
+        }
+        if let Some(end) = end {
+            select = select.filter(orders::Column::CreatedAt.lt(end));
+        }
+
+        let models = select
+            .order_by_asc(orders::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+
+    async fn list_orders(&self, order_link_ids: Vec<String>) -> anyhow::Result<Vec<orders::Model>> {
+        if order_link_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let db = self.mysql.get_db().await;
+        let models = Orders::find()
+            .filter(orders::Column::OrderLinkId.is_in(order_link_ids))
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+
+    async fn list_symbols(&self, names: Vec<String>) -> anyhow::Result<Vec<symbols::Model>> {
+        if names.is_empty() {
+            return Ok(vec![]);
+        }
+        let db = self.mysql.get_db().await;
+        let models = Symbols::find()
+            .filter(symbols::Column::Name.is_in(names))
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+}
@@ -0,0 +1,107 @@
+use crate::domain::{
+    aggregate, PnlReport, ReportQuery, ReportRepository, ReportRow, ReportUsecase, Trade,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::SinkExt;
+use order::domain::fill_of;
+use pkg::{
+    export::{self, RowReceiver},
+    pnl,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct ReportUcase {
+    report_repo: Arc<dyn ReportRepository>,
+}
+
+impl ReportUcase {
+    pub fn new(report_repo: Arc<dyn ReportRepository>) -> Arc<dyn ReportUsecase> {
+        Arc::new(ReportUcase { report_repo })
+    }
+}
+
+#[async_trait]
+impl ReportUsecase for ReportUcase {
+    /**
+     * 盈虧報表, 盈虧取平倉單已結算的profit_and_loss, 手續費依開倉/平倉單與合約費率計算
+     */
+    async fn pnl(&self, query: ReportQuery) -> Result<PnlReport> {
+        let closes = self.report_repo.list_closed_orders(&query).await?;
+
+        let open_ids = closes
+            .iter()
+            .map(|close| close.rel_order_link_id.clone())
+            .collect();
+        let opens: HashMap<String, _> = self
+            .report_repo
+            .list_orders(open_ids)
+            .await?
+            .into_iter()
+            .map(|open| (open.order_link_id.clone(), open))
+            .collect();
+
+        let mut names: Vec<String> = closes.iter().map(|close| close.symbol.clone()).collect();
+        names.sort();
+        names.dedup();
+        let symbols: HashMap<String, _> = self
+            .report_repo
+            .list_symbols(names)
+            .await?
+            .into_iter()
+            .map(|symbol| (symbol.name.clone(), symbol))
+            .collect();
+
+        let trades: Vec<Trade> = closes
+            .into_iter()
+            .map(|close| {
+                let fees = match (
+                    opens.get(&close.rel_order_link_id),
+                    symbols.get(&close.symbol),
+                ) {
+                    (Some(open), Some(symbol)) => {
+                        pnl::fees(&fill_of(open, symbol), &fill_of(&close, symbol))
+                    }
+                    _ => Decimal::ZERO,
+                };
+                Trade {
+                    pnl: pnl::decimal(close.profit_and_loss),
+                    fees,
+                    closed_at: close.created_at,
+                    strategy_name: close.strategy_name,
+                    symbol: close.symbol,
+                }
+            })
+            .collect();
+
+        let tz = query.timezone();
+        let (summary, rows) = aggregate(&trades, query.period, query.group_by, &tz);
+
+        Ok(PnlReport {
+            period: query.period,
+            group_by: query.group_by,
+            timezone: tz.name().to_owned(),
+            start_date: query.start_date,
+            end_date: query.end_date,
+            summary,
+            rows,
+        })
+    }
+
+    /**
+     * 報表列數不多, 計算完成後逐列送出
+     */
+    async fn export(&self, query: ReportQuery) -> Result<RowReceiver<ReportRow>> {
+        let report = self.pnl(query).await?;
+        Ok(export::spawn(move |mut tx| async move {
+            for row in report.rows {
+                if tx.send(Ok(row)).await.is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        }))
+    }
+}
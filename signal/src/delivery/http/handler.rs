@@ -0,0 +1,154 @@
+use crate::domain::{
+    secret_matches, LeaderboardQuery, SignalAction, SignalContainer, SignalExport, SignalInfo,
+    SignalPayload, SignalRecordQuery,
+};
+use axum::{
+    extract::{Extension, Path, Query},
+    response::IntoResponse,
+};
+use futures::StreamExt;
+use pkg::{
+    error::{AppError, AppResult},
+    export::{self, ExportQuery},
+    extract::ValidatedJson,
+    jwt::Claims,
+    responder::ok,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+/**
+ * 接收交易訊號 webhook
+ */
+pub async fn receive_signal(
+    Path(strategy_name): Path<String>,
+    ValidatedJson(payload): ValidatedJson<SignalPayload>,
+    Extension(c): Extension<Arc<SignalContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let strategy = match c.signal_ucase.get_strategy(strategy_name.clone()).await? {
+        Some(strategy) => strategy,
+        None => {
+            tracing::warn!("signal rejected: strategy {} not found", strategy_name);
+            return Err(AppError::not_found("Strategy not found"));
+        }
+    };
+
+    //驗證密鑰
+    if !secret_matches(&strategy.secret, &payload.secret) {
+        tracing::warn!("signal rejected: invalid secret for {}", strategy.name);
+        return Err(AppError::forbidden("Invalid secret"));
+    }
+
+    //停用的策略不接受訊號
+    if strategy.state == 0 {
+        tracing::warn!(
+            "signal rejected: strategy {} is disabled, action {:?} price {}",
+            strategy.name,
+            payload.action,
+            payload.price
+        );
+        return Err(AppError::forbidden("Strategy disabled"));
+    }
+
+    let open_record = c
+        .signal_ucase
+        .get_open_record(strategy.name.clone())
+        .await?;
+
+    let record = match payload.action {
+        SignalAction::Open => {
+            //已有持倉中的訊號
+            if open_record.is_some() {
+                tracing::warn!(
+                    "signal rejected: {} already has an open record",
+                    strategy.name
+                );
+                return Err(AppError::duplicate("Strategy already has an open position"));
+            }
+
+            let side = payload.side.unwrap_or(strategy.side);
+            c.signal_ucase.open(&strategy, side, payload.price).await?
+        }
+        SignalAction::Close => {
+            let record = match open_record {
+                Some(record) => record,
+                None => {
+                    tracing::warn!("signal rejected: {} has no open record", strategy.name);
+                    return Err(AppError::not_found("No open position"));
+                }
+            };
+
+            if matches!(payload.side, Some(side) if side != record.side) {
+                return Err(AppError::validation(
+                    "Side does not match the open position",
+                ));
+            }
+
+            c.signal_ucase
+                .close(&strategy, record, payload.price)
+                .await?
+        }
+    };
+
+    Ok(ok(SignalInfo::from(record)))
+}
+
+/**
+ * 匯出策略的訊號紀錄 CSV / XLSX
+ */
+pub async fn export_signal_records(
+    Path(strategy_name): Path<String>,
+    Query(query): Query<SignalRecordQuery>,
+    Query(export): Query<ExportQuery>,
+    _claims: Claims,
+    Extension(c): Extension<Arc<SignalContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    export.validate()?;
+
+    if c.signal_ucase
+        .get_strategy(strategy_name.clone())
+        .await?
+        .is_none()
+    {
+        return Err(AppError::not_found("Strategy not found"));
+    }
+
+    let rows = c
+        .signal_ucase
+        .export(strategy_name, query)
+        .map(|row| row.map(SignalExport));
+    export::respond(rows, &export, "signal_records").await
+}
+
+/**
+ * 策略績效與淨值曲線
+ */
+pub async fn strategy_stats(
+    Path(strategy_name): Path<String>,
+    Extension(c): Extension<Arc<SignalContainer>>,
+) -> AppResult<impl IntoResponse> {
+    if c.signal_ucase
+        .get_strategy(strategy_name.clone())
+        .await?
+        .is_none()
+    {
+        return Err(AppError::not_found("Strategy not found"));
+    }
+
+    let res = c.stats_ucase.performance(strategy_name).await?;
+    Ok(ok(res))
+}
+
+/**
+ * 策略排行榜
+ */
+pub async fn leaderboard(
+    Query(query): Query<LeaderboardQuery>,
+    Extension(c): Extension<Arc<SignalContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+
+    let list = c.stats_ucase.leaderboard(query).await?;
+    Ok(ok(list))
+}
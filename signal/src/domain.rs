@@ -0,0 +1,606 @@
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Local};
+use chrono_tz::Tz;
+use entity::{
+    signal_records::{ActiveModel as SignalActiveModel, Model as SignalModel},
+    strategies::Model as StrategyModel,
+    strategy_stats::Model as StatsModel,
+};
+use pkg::{
+    exchange::Side,
+    export::{format_dt, Cell, ExportRow, RowReceiver, RowSender},
+    eztime::{self, validate_date},
+    page::SortDirection,
+    pnl,
+    responder::Data,
+};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::convert::From;
+use std::sync::Arc;
+use validator::{Validate, ValidationError};
+
+/**
+ * Traits
+ */
+#[async_trait]
+pub trait SignalRepository: Send + Sync {
+    async fn get_strategy(&self, name: String) -> Result<Option<StrategyModel>>;
+    async fn get_open_record(&self, strategy_name: String) -> Result<Option<SignalModel>>;
+    async fn create(&self, active: SignalActiveModel) -> Result<SignalModel>;
+    /**
+     * 以state為條件平倉, 已被平倉時回傳None
+     */
+    async fn close(&self, id: i64, close_price: f64, pnl: f64) -> Result<Option<SignalModel>>;
+    async fn export(
+        &self,
+        strategy_name: String,
+        query: SignalRecordQuery,
+        tx: RowSender<SignalModel>,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+pub trait StatsRepository: Send + Sync {
+    async fn get(&self, strategy_name: String) -> Result<Option<StatsModel>>;
+    async fn list(&self, min_trades: i32) -> Result<Vec<StatsModel>>;
+    async fn save(&self, stats: &StrategyStats) -> Result<()>;
+    async fn list_closed_records(
+        &self,
+        strategy_name: String,
+        after_id: i64,
+    ) -> Result<Vec<SignalModel>>;
+}
+
+#[async_trait]
+pub trait StatsUsecase: Send + Sync {
+    async fn refresh(&self, strategy_name: String) -> Result<StrategyStats>;
+    async fn performance(&self, strategy_name: String) -> Result<StrategyPerformance>;
+    async fn leaderboard(&self, query: LeaderboardQuery) -> Result<Vec<StatsInfo>>;
+}
+
+#[async_trait]
+pub trait SignalUsecase: Send + Sync {
+    async fn get_strategy(&self, name: String) -> Result<Option<StrategyModel>>;
+    async fn get_open_record(&self, strategy_name: String) -> Result<Option<SignalModel>>;
+    async fn open(&self, strategy: &StrategyModel, side: i8, price: f64) -> Result<SignalModel>;
+    async fn close(
+        &self,
+        strategy: &StrategyModel,
+        record: SignalModel,
+        price: f64,
+    ) -> Result<SignalModel>;
+    fn export(&self, strategy_name: String, query: SignalRecordQuery) -> RowReceiver<SignalModel>;
+}
+
+/**
+ * Extension container
+ */
+pub struct SignalContainer {
+    pub signal_ucase: Arc<dyn SignalUsecase>,
+    pub stats_ucase: Arc<dyn StatsUsecase>,
+}
+
+impl SignalContainer {
+    pub fn new(
+        signal_ucase: Arc<dyn SignalUsecase>,
+        stats_ucase: Arc<dyn StatsUsecase>,
+    ) -> Arc<SignalContainer> {
+        Arc::new(SignalContainer {
+            signal_ucase,
+            stats_ucase,
+        })
+    }
+}
+
+/**
+ * signal record state
+ */
+pub const STATE_OPEN: i8 = 1;
+pub const STATE_CLOSED: i8 = 2;
+
+/**
+ * 比對webhook密鑰, 固定時間比較避免timing attack
+ */
+pub fn secret_matches(expected: &str, given: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), given.as_bytes());
+    if a.is_empty() || a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+/**
+ * 計算盈虧百分比 1 => 多單, 2 => 空單
+ */
+pub fn calc_pnl(side: i8, open_price: f64, close_price: f64) -> f64 {
+    pnl::to_f64(pnl::percent(side, open_price, close_price))
+}
+
+/**
+ * 價格必須大於0
+ */
+fn validate_price(price: f64) -> Result<(), ValidationError> {
+    if price > 0.0 && price.is_finite() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive"))
+    }
+}
+
+/**
+ * Webhook action
+ */
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SignalAction {
+    Open,
+    Close,
+}
+
+/**
+ * Webhook payload (TradingView alert message)
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct SignalPayload {
+    #[validate(length(min = 1, max = 64))]
+    pub secret: String,
+    pub action: SignalAction,
+    #[validate(range(min = 1, max = 2))]
+    pub side: Option<i8>,
+    #[validate(custom = "validate_price")]
+    pub price: f64,
+}
+
+/**
+ * Signal record info
+ */
+#[derive(Serialize)]
+pub struct SignalInfo {
+    pub id: i64,
+    pub strategy_name: String,
+    pub state: i8,
+    pub side: i8,
+    pub open_price: f64,
+    pub close_price: f64,
+    pub profit_and_loss: f64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Data for SignalInfo {}
+
+impl From<SignalModel> for SignalInfo {
+    fn from(model: SignalModel) -> Self {
+        let SignalModel {
+            id,
+            strategy_name,
+            state,
+            side,
+            open_price,
+            close_price,
+            profit_and_loss,
+            created_at,
+            updated_at,
+        } = model;
+
+        SignalInfo {
+            id,
+            strategy_name,
+            state,
+            side,
+            open_price,
+            close_price,
+            profit_and_loss,
+            created_at: created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            updated_at: updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+/**
+ * 訊號紀錄查詢條件, start_date / end_date 同訂單查詢
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct SignalRecordQuery {
+    #[validate(range(min = 1, max = 2))]
+    pub state: Option<i8>,
+    #[validate(range(min = 1, max = 2))]
+    pub side: Option<i8>,
+    #[validate(custom = "validate_date")]
+    pub start_date: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+}
+
+impl SignalRecordQuery {
+    /**
+     * 建立時間區間 [start, end)
+     */
+    pub fn created_range(&self) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        eztime::range(self.start_date.as_deref(), self.end_date.as_deref())
+    }
+}
+
+/**
+ * 訊號紀錄匯出的資料列
+ */
+pub struct SignalExport(pub SignalModel);
+
+impl ExportRow for SignalExport {
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "id",
+            "strategy_name",
+            "state",
+            "side",
+            "open_price",
+            "close_price",
+            "profit_and_loss",
+            "created_at",
+            "updated_at",
+        ]
+    }
+
+    fn cells(&self, tz: &Tz) -> Vec<Cell> {
+        let record = &self.0;
+        let state = match record.state {
+            STATE_OPEN => "Open",
+            STATE_CLOSED => "Closed",
+            _ => "",
+        };
+        let side = Side::from_i8(record.side).map(|side| side.to_string());
+        vec![
+            record.id.into(),
+            record.strategy_name.as_str().into(),
+            state.into(),
+            side.unwrap_or_default().into(),
+            record.open_price.into(),
+            record.close_price.into(),
+            record.profit_and_loss.into(),
+            format_dt(&record.created_at, tz).into(),
+            format_dt(&record.updated_at, tz).into(),
+        ]
+    }
+}
+
+/**
+ * 策略績效的累計值, 每平倉一筆訊號就累加一次, 不需重算全部紀錄
+ * 報酬率為signal_records.profit_and_loss(%)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyStats {
+    pub strategy_name: String,
+    pub trades: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub gross_profit: f64,
+    pub gross_loss: f64,
+    pub equity: f64,
+    pub return_sum: f64,
+    pub return_sq_sum: f64,
+    pub losing_streak: i32,
+    pub longest_losing_streak: i32,
+    pub last_record_id: i64,
+}
+
+impl StrategyStats {
+    pub fn new(strategy_name: String) -> StrategyStats {
+        StrategyStats {
+            strategy_name,
+            trades: 0,
+            wins: 0,
+            losses: 0,
+            gross_profit: 0.0,
+            gross_loss: 0.0,
+            equity: 1.0,
+            return_sum: 0.0,
+            return_sq_sum: 0.0,
+            losing_streak: 0,
+            longest_losing_streak: 0,
+            last_record_id: 0,
+        }
+    }
+
+    /**
+     * 計入一筆已平倉的訊號, 已計入過的紀錄略過
+     */
+    pub fn apply(&mut self, record: &SignalModel) {
+        if record.id <= self.last_record_id {
+            return;
+        }
+        let ret = pnl::decimal(record.profit_and_loss);
+
+        self.trades += 1;
+        if ret > Decimal::ZERO {
+            self.wins += 1;
+            self.gross_profit = pnl::to_f64(pnl::decimal(self.gross_profit) + ret);
+            self.losing_streak = 0;
+        } else if ret < Decimal::ZERO {
+            self.losses += 1;
+            self.gross_loss = pnl::to_f64(pnl::decimal(self.gross_loss) - ret);
+            self.losing_streak += 1;
+            self.longest_losing_streak = self.longest_losing_streak.max(self.losing_streak);
+        }
+
+        //複利淨值
+        let growth = Decimal::ONE + ret / Decimal::ONE_HUNDRED;
+        self.equity = pnl::to_f64(pnl::decimal(self.equity) * growth);
+        self.return_sum = pnl::to_f64(pnl::decimal(self.return_sum) + ret);
+        self.return_sq_sum = pnl::to_f64(pnl::decimal(self.return_sq_sum) + ret * ret);
+        self.last_record_id = record.id;
+    }
+
+    /**
+     * 累計報酬率(%)
+     */
+    pub fn cumulative_return(&self) -> f64 {
+        pnl::to_f64((pnl::decimal(self.equity) - Decimal::ONE) * Decimal::ONE_HUNDRED)
+    }
+
+    /**
+     * 勝率(%)
+     */
+    pub fn win_rate(&self) -> f64 {
+        if self.trades == 0 {
+            return 0.0;
+        }
+        let rate = Decimal::from(self.wins) / Decimal::from(self.trades) * Decimal::ONE_HUNDRED;
+        pnl::to_f64(rate.round_dp(2))
+    }
+
+    /**
+     * 獲利因子 = 獲利合計 / 虧損合計, 沒有虧損時為None
+     */
+    pub fn profit_factor(&self) -> Option<f64> {
+        let loss = pnl::decimal(self.gross_loss);
+        if loss.is_zero() {
+            return None;
+        }
+        let factor = pnl::decimal(self.gross_profit) / loss;
+        Some(pnl::to_f64(factor.round_dp(4)))
+    }
+
+    /**
+     * 類Sharpe比率 = 平均報酬 / 報酬標準差(樣本), 不年化也不扣無風險利率
+     */
+    pub fn sharpe_ratio(&self) -> Option<f64> {
+        if self.trades < 2 {
+            return None;
+        }
+        let n = Decimal::from(self.trades);
+        let mean = pnl::decimal(self.return_sum) / n;
+        let variance = (pnl::decimal(self.return_sq_sum) - n * mean * mean) / (n - Decimal::ONE);
+        let std_dev = variance.to_f64()?.max(0.0).sqrt();
+        if std_dev == 0.0 {
+            return None;
+        }
+        let ratio = mean / pnl::decimal(std_dev);
+        Some(pnl::to_f64(ratio.round_dp(4)))
+    }
+}
+
+impl From<StatsModel> for StrategyStats {
+    fn from(model: StatsModel) -> Self {
+        StrategyStats {
+            strategy_name: model.strategy_name,
+            trades: model.trades,
+            wins: model.wins,
+            losses: model.losses,
+            gross_profit: model.gross_profit,
+            gross_loss: model.gross_loss,
+            equity: model.equity,
+            return_sum: model.return_sum,
+            return_sq_sum: model.return_sq_sum,
+            losing_streak: model.losing_streak,
+            longest_losing_streak: model.longest_losing_streak,
+            last_record_id: model.last_record_id,
+        }
+    }
+}
+
+/**
+ * Strategy stats info
+ */
+#[derive(Serialize, Debug)]
+pub struct StatsInfo {
+    pub strategy_name: String,
+    pub trades: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub win_rate: f64,
+    pub cumulative_return: f64,
+    pub profit_factor: Option<f64>,
+    pub sharpe_ratio: Option<f64>,
+    pub longest_losing_streak: i32,
+}
+
+impl Data for StatsInfo {}
+
+impl From<&StrategyStats> for StatsInfo {
+    fn from(stats: &StrategyStats) -> Self {
+        StatsInfo {
+            strategy_name: stats.strategy_name.clone(),
+            trades: stats.trades,
+            wins: stats.wins,
+            losses: stats.losses,
+            win_rate: stats.win_rate(),
+            cumulative_return: stats.cumulative_return(),
+            profit_factor: stats.profit_factor(),
+            sharpe_ratio: stats.sharpe_ratio(),
+            longest_losing_streak: stats.longest_losing_streak,
+        }
+    }
+}
+
+/**
+ * 淨值曲線的一點, equity為到此筆為止的累計報酬率(%)
+ */
+#[derive(Serialize, Debug)]
+pub struct EquityPoint {
+    pub record_id: i64,
+    pub closed_at: String,
+    pub profit_and_loss: f64,
+    pub equity: f64,
+}
+
+/**
+ * 依平倉順序重播紀錄產生淨值曲線
+ */
+pub fn equity_curve(strategy_name: &str, records: &[SignalModel]) -> Vec<EquityPoint> {
+    let mut stats = StrategyStats::new(strategy_name.to_owned());
+    records
+        .iter()
+        .map(|record| {
+            stats.apply(record);
+            EquityPoint {
+                record_id: record.id,
+                closed_at: record.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                profit_and_loss: record.profit_and_loss,
+                equity: stats.cumulative_return(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * 策略績效
+ */
+#[derive(Serialize, Debug)]
+pub struct StrategyPerformance {
+    #[serde(flatten)]
+    pub stats: StatsInfo,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+impl Data for StrategyPerformance {}
+
+/**
+ * 排行榜可排序的指標
+ */
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsMetric {
+    #[default]
+    CumulativeReturn,
+    WinRate,
+    ProfitFactor,
+    SharpeRatio,
+    Trades,
+    LongestLosingStreak,
+}
+
+impl StatsMetric {
+    fn value(&self, info: &StatsInfo) -> Option<f64> {
+        match self {
+            StatsMetric::CumulativeReturn => Some(info.cumulative_return),
+            StatsMetric::WinRate => Some(info.win_rate),
+            StatsMetric::ProfitFactor => info.profit_factor,
+            StatsMetric::SharpeRatio => info.sharpe_ratio,
+            StatsMetric::Trades => Some(info.trades as f64),
+            StatsMetric::LongestLosingStreak => Some(info.longest_losing_streak as f64),
+        }
+    }
+}
+
+/**
+ * 排行榜查詢條件
+ */
+#[derive(Deserialize, Validate, Debug, Default)]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    pub sort: StatsMetric,
+    #[serde(default)]
+    pub direction: SortDirection,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+    #[validate(range(min = 1))]
+    pub min_trades: Option<i32>,
+}
+
+/**
+ * 依指標排序, 沒有值的指標(例如沒有虧損時的profit_factor)排在最後
+ */
+pub fn rank(
+    mut list: Vec<StatsInfo>,
+    metric: StatsMetric,
+    direction: SortDirection,
+) -> Vec<StatsInfo> {
+    list.sort_by(|a, b| match (metric.value(a), metric.value(b)) {
+        (Some(x), Some(y)) => {
+            let ord = x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+            match direction {
+                SortDirection::Asc => ord,
+                SortDirection::Desc => ord.reverse(),
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn record(id: i64, profit_and_loss: f64) -> SignalModel {
+        SignalModel {
+            id,
+            strategy_name: "trend".to_owned(),
+            state: STATE_CLOSED,
+            side: 1,
+            open_price: 100.0,
+            close_price: 100.0 + profit_and_loss,
+            profit_and_loss,
+            created_at: Local::now(),
+            updated_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn stats_accumulate() {
+        let mut stats = StrategyStats::new("trend".to_owned());
+        for (id, ret) in [(1, 10.0), (2, -5.0), (3, -5.0), (4, 20.0), (5, -2.0)] {
+            stats.apply(&record(id, ret));
+        }
+        //重複的紀錄不再計入
+        stats.apply(&record(5, -2.0));
+
+        assert_eq!(stats.trades, 5);
+        assert_eq!(stats.win_rate(), 40.0);
+        assert_eq!(stats.longest_losing_streak, 2);
+        assert_eq!(stats.losing_streak, 1);
+        assert_eq!(stats.profit_factor(), Some(2.5));
+        // 1.1 * 0.95 * 0.95 * 1.2 * 0.98
+        assert_eq!(stats.cumulative_return(), 16.7474);
+        assert!(stats.sharpe_ratio().unwrap() > 0.0);
+
+        let curve = equity_curve("trend", &[record(1, 10.0), record(2, -5.0)]);
+        assert_eq!(curve[1].equity, 4.5);
+    }
+
+    #[test]
+    fn rank_by_metric() {
+        let mut a = StrategyStats::new("a".to_owned());
+        a.apply(&record(1, 10.0));
+        let mut b = StrategyStats::new("b".to_owned());
+        b.apply(&record(2, 5.0));
+        b.apply(&record(3, -1.0));
+
+        let list = vec![StatsInfo::from(&a), StatsInfo::from(&b)];
+        let ranked = rank(list, StatsMetric::ProfitFactor, SortDirection::Desc);
+        //a沒有虧損, profit_factor為None排最後
+        assert_eq!(ranked[0].strategy_name, "b");
+
+        let ranked = rank(ranked, StatsMetric::CumulativeReturn, SortDirection::Desc);
+        assert_eq!(ranked[0].strategy_name, "a");
+    }
+}
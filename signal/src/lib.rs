@@ -0,0 +1,48 @@
+mod delivery;
+pub mod domain;
+mod repository;
+pub mod usecase;
+
+pub mod router {
+    use crate::{
+        delivery::http::handler::{
+            export_signal_records, leaderboard, receive_signal, strategy_stats,
+        },
+        domain::SignalContainer,
+        repository::mysql::{signal_repo::SignalRepo, stats_repo::StatsRepo},
+        usecase::{signal_ucase::SignalUcase, stats_ucase::StatsUcase},
+    };
+    use axum::{
+        extract::Extension,
+        routing::{get, post},
+        Router,
+    };
+
+    use order::domain::OrderUsecase;
+    use pkg::db::ORM;
+    use std::sync::Arc;
+
+    /**
+     * new handler
+     */
+    pub fn new(orm: Arc<dyn ORM>, order_ucase: Arc<dyn OrderUsecase>) -> Router {
+        let signal_repo = SignalRepo::new(orm.clone());
+        let stats_ucase = StatsUcase::new(StatsRepo::new(orm));
+        let signal_ucase = SignalUcase::new(signal_repo, stats_ucase.clone(), order_ucase);
+        let signal_container = SignalContainer::new(signal_ucase, stats_ucase);
+
+        let signal_router = Router::new()
+            .route("/:strategy_name", post(receive_signal))
+            .route("/:strategy_name/export", get(export_signal_records));
+
+        //公開的策略績效
+        let stats_router = Router::new()
+            .route("/leaderboard", get(leaderboard))
+            .route("/strategies/:strategy_name", get(strategy_stats));
+
+        Router::new()
+            .nest("/v1/signal", signal_router)
+            .nest("/v1/stats", stats_router)
+            .layer(Extension(signal_container))
+    }
+}
@@ -0,0 +1,2 @@
+pub mod signal_repo;
+pub mod stats_repo;
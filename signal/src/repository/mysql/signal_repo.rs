@@ -0,0 +1,108 @@
+use crate::domain::{SignalRecordQuery, SignalRepository, STATE_CLOSED, STATE_OPEN};
+use async_trait::async_trait;
+use entity::{prelude::*, signal_records, strategies};
+use futures::{SinkExt, StreamExt};
+use pkg::{db::ORM, export::RowSender};
+use sea_orm::{prelude::*, sea_query::Expr, QueryOrder};
+use std::sync::Arc;
+
+pub struct SignalRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl SignalRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn SignalRepository> {
+        Arc::new(SignalRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl SignalRepository for SignalRepo {
+    async fn get_strategy(&self, name: String) -> anyhow::Result<Option<strategies::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Strategies::find_by_id(name).one(db).await?;
+        Ok(model)
+    }
+
+    async fn get_open_record(
+        &self,
+        strategy_name: String,
+    ) -> anyhow::Result<Option<signal_records::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = SignalRecords::find()
+            .filter(signal_records::Column::StrategyName.eq(strategy_name))
+            .filter(signal_records::Column::State.eq(STATE_OPEN))
+            .order_by_desc(signal_records::Column::Id)
+            .one(db)
+            .await?;
+
+        Ok(model)
+    }
+
+    async fn create(
+        &self,
+        active: signal_records::ActiveModel,
+    ) -> anyhow::Result<signal_records::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.insert(db).await?;
+        Ok(model)
+    }
+
+    async fn close(
+        &self,
+        id: i64,
+        close_price: f64,
+        pnl: f64,
+    ) -> anyhow::Result<Option<signal_records::Model>> {
+        let db = self.mysql.get_db().await;
+        let res = SignalRecords::update_many()
+            .col_expr(signal_records::Column::State, Expr::value(STATE_CLOSED))
+            .col_expr(signal_records::Column::ClosePrice, Expr::value(close_price))
+            .col_expr(signal_records::Column::ProfitAndLoss, Expr::value(pnl))
+            .filter(signal_records::Column::Id.eq(id))
+            .filter(signal_records::Column::State.eq(STATE_OPEN))
+            .exec(db)
+            .await?;
+        if res.rows_affected != 1 {
+            return Ok(None);
+        }
+
+        let model = SignalRecords::find_by_id(id).one(db).await?;
+        Ok(model)
+    }
+
+    async fn export(
+        &self,
+        strategy_name: String,
+        query: SignalRecordQuery,
+        mut tx: RowSender<signal_records::Model>,
+    ) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        let (start, end) = query.created_range();
+        let mut select =
+            SignalRecords::find().filter(signal_records::Column::StrategyName.eq(strategy_name));
+        if let Some(state) = query.state {
+            select = select.filter(signal_records::Column::State.eq(state));
+        }
+        if let Some(side) = query.side {
+            select = select.filter(signal_records::Column::Side.eq(side));
+        }
+        if let Some(start) = start {
+            select = select.filter(signal_records::Column::CreatedAt.gte(start));
+        }
+        if let Some(end) = end {
+            select = select.filter(signal_records::Column::CreatedAt.lt(end));
+        }
+
+        let mut stream = select
+            .order_by_asc(signal_records::Column::Id)
+            .stream(db)
+            .await?;
+        while let Some(model) = stream.next().await {
+            if tx.send(Ok(model?)).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
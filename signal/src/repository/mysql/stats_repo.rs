@@ -0,0 +1,93 @@
+use crate::domain::{StatsRepository, StrategyStats as Stats, STATE_CLOSED};
+use async_trait::async_trait;
+use entity::{prelude::*, signal_records, strategy_stats};
+use pkg::db::ORM;
+use sea_orm::{prelude::*, sea_query::OnConflict, QueryOrder, Set};
+use std::sync::Arc;
+
+pub struct StatsRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl StatsRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn StatsRepository> {
+        Arc::new(StatsRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl StatsRepository for StatsRepo {
+    async fn get(&self, strategy_name: String) -> anyhow::Result<Option<strategy_stats::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = StrategyStats::find_by_id(strategy_name).one(db).await?;
+        Ok(model)
+    }
+
+    async fn list(&self, min_trades: i32) -> anyhow::Result<Vec<strategy_stats::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = StrategyStats::find()
+            .filter(strategy_stats::Column::Trades.gte(min_trades))
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+
+    async fn save(&self, stats: &Stats) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        let active = strategy_stats::ActiveModel {
+            strategy_name: Set(stats.strategy_name.clone()),
+            trades: Set(stats.trades),
+            wins: Set(stats.wins),
+            losses: Set(stats.losses),
+            gross_profit: Set(stats.gross_profit),
+            gross_loss: Set(stats.gross_loss),
+            equity: Set(stats.equity),
+            return_sum: Set(stats.return_sum),
+            return_sq_sum: Set(stats.return_sq_sum),
+            losing_streak: Set(stats.losing_streak),
+            longest_losing_streak: Set(stats.longest_losing_streak),
+            last_record_id: Set(stats.last_record_id),
+            ..Default::default()
+        };
+        StrategyStats::insert(active)
+            .on_conflict(
+                OnConflict::column(strategy_stats::Column::StrategyName)
+                    .update_columns([
+                        strategy_stats::Column::Trades,
+                        strategy_stats::Column::Wins,
+                        strategy_stats::Column::Losses,
+                        strategy_stats::Column::GrossProfit,
+                        strategy_stats::Column::GrossLoss,
+                        strategy_stats::Column::Equity,
+                        strategy_stats::Column::ReturnSum,
+                        strategy_stats::Column::ReturnSqSum,
+                        strategy_stats::Column::LosingStreak,
+                        strategy_stats::Column::LongestLosingStreak,
+                        strategy_stats::Column::LastRecordId,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /**
+     * 已平倉的訊號, 依id排序
+     */
+    async fn list_closed_records(
+        &self,
+        strategy_name: String,
+        after_id: i64,
+    ) -> anyhow::Result<Vec<signal_records::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = SignalRecords::find()
+            .filter(signal_records::Column::StrategyName.eq(strategy_name))
+            .filter(signal_records::Column::State.eq(STATE_CLOSED))
+            .filter(signal_records::Column::Id.gt(after_id))
+            .order_by_asc(signal_records::Column::Id)
+            .all(db)
+            .await?;
+        Ok(models)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod signal_ucase;
+pub mod stats_ucase;
@@ -0,0 +1,154 @@
+use crate::domain::{
+    calc_pnl, SignalRecordQuery, SignalRepository, SignalUsecase, StatsUsecase, STATE_OPEN,
+};
+use async_trait::async_trait;
+use entity::{signal_records, strategies};
+use order::domain::{OrderUsecase, Signal, ACTION_CLOSE, ACTION_OPEN};
+use pkg::{
+    db,
+    error::AppError,
+    export::{self, RowReceiver},
+};
+use sea_orm::ActiveValue::Set;
+use std::sync::Arc;
+
+pub struct SignalUcase {
+    signal_repo: Arc<dyn SignalRepository>,
+    stats_ucase: Arc<dyn StatsUsecase>,
+    order_ucase: Arc<dyn OrderUsecase>,
+}
+
+impl SignalUcase {
+    pub fn new(
+        signal_repo: Arc<dyn SignalRepository>,
+        stats_ucase: Arc<dyn StatsUsecase>,
+        order_ucase: Arc<dyn OrderUsecase>,
+    ) -> Arc<dyn SignalUsecase> {
+        Arc::new(SignalUcase {
+            signal_repo,
+            stats_ucase,
+            order_ucase,
+        })
+    }
+
+    /**
+     * 背景分派訊號給訂閱者下單, 不阻塞webhook回應
+     */
+    fn dispatch(&self, signal: Signal) {
+        let order_ucase = self.order_ucase.clone();
+        tokio::spawn(async move {
+            if let Err(e) = order_ucase.dispatch(signal).await {
+                tracing::error!("dispatch signal failed: {}", e);
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl SignalUsecase for SignalUcase {
+    async fn get_strategy(&self, name: String) -> anyhow::Result<Option<strategies::Model>> {
+        let res = self.signal_repo.get_strategy(name).await?;
+        Ok(res)
+    }
+
+    /**
+     * 取得策略持倉中的訊號
+     */
+    async fn get_open_record(
+        &self,
+        strategy_name: String,
+    ) -> anyhow::Result<Option<signal_records::Model>> {
+        let res = self.signal_repo.get_open_record(strategy_name).await?;
+        Ok(res)
+    }
+
+    /**
+     * 開倉訊號, 同策略只能有一筆持倉中的紀錄, 由唯一索引擋下同時送達的訊號
+     */
+    async fn open(
+        &self,
+        strategy: &strategies::Model,
+        side: i8,
+        price: f64,
+    ) -> anyhow::Result<signal_records::Model> {
+        let active_model = signal_records::ActiveModel {
+            strategy_name: Set(strategy.name.clone()),
+            state: Set(STATE_OPEN),
+            side: Set(side),
+            open_price: Set(price),
+            ..Default::default()
+        };
+        let res = match self.signal_repo.create(active_model).await {
+            Err(e) if db::is_duplicate_key(&e) => {
+                tracing::warn!(
+                    "signal rejected: {} already has an open record",
+                    strategy.name
+                );
+                return Err(AppError::duplicate("Strategy already has an open position").into());
+            }
+            res => res?,
+        };
+
+        self.dispatch(Signal {
+            strategy_name: strategy.name.clone(),
+            symbol: strategy.symbol_name.clone(),
+            action: ACTION_OPEN,
+            side,
+            price,
+        });
+
+        Ok(res)
+    }
+
+    /**
+     * 平倉訊號, 計算盈虧
+     * 以state做為樂觀鎖, 同時送達的平倉訊號只有一個會分派下單
+     */
+    async fn close(
+        &self,
+        strategy: &strategies::Model,
+        record: signal_records::Model,
+        price: f64,
+    ) -> anyhow::Result<signal_records::Model> {
+        let side = record.side;
+        let pnl = calc_pnl(record.side, record.open_price, price);
+        let res = match self.signal_repo.close(record.id, price, pnl).await? {
+            Some(res) => res,
+            None => {
+                tracing::warn!(
+                    "signal rejected: {} record {} already closed",
+                    strategy.name,
+                    record.id
+                );
+                return Err(AppError::not_found("No open position").into());
+            }
+        };
+
+        //更新策略績效快取, 失敗時下次查詢會補算
+        if let Err(e) = self.stats_ucase.refresh(strategy.name.clone()).await {
+            tracing::error!("refresh strategy stats of {} failed: {}", strategy.name, e);
+        }
+
+        self.dispatch(Signal {
+            strategy_name: strategy.name.clone(),
+            symbol: strategy.symbol_name.clone(),
+            action: ACTION_CLOSE,
+            side,
+            price,
+        });
+
+        Ok(res)
+    }
+
+    /**
+     * 背景串流讀取訊號紀錄
+     */
+    fn export(
+        &self,
+        strategy_name: String,
+        query: SignalRecordQuery,
+    ) -> RowReceiver<signal_records::Model> {
+        let signal_repo = self.signal_repo.clone();
+        export::spawn(move |tx| async move { signal_repo.export(strategy_name, query, tx).await })
+    }
+}
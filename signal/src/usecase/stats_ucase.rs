@@ -0,0 +1,80 @@
+use crate::domain::{
+    equity_curve, rank, LeaderboardQuery, StatsInfo, StatsRepository, StatsUsecase,
+    StrategyPerformance, StrategyStats,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+//排行榜預設筆數
+const DEFAULT_LEADERBOARD_LIMIT: usize = 20;
+
+pub struct StatsUcase {
+    stats_repo: Arc<dyn StatsRepository>,
+}
+
+impl StatsUcase {
+    pub fn new(stats_repo: Arc<dyn StatsRepository>) -> Arc<dyn StatsUsecase> {
+        Arc::new(StatsUcase { stats_repo })
+    }
+}
+
+#[async_trait]
+impl StatsUsecase for StatsUcase {
+    /**
+     * 從快取的統計接著計入尚未計入的已平倉訊號, 沒有快取時從頭計算
+     */
+    async fn refresh(&self, strategy_name: String) -> anyhow::Result<StrategyStats> {
+        let mut stats = match self.stats_repo.get(strategy_name.clone()).await? {
+            Some(model) => StrategyStats::from(model),
+            None => StrategyStats::new(strategy_name.clone()),
+        };
+
+        let records = self
+            .stats_repo
+            .list_closed_records(strategy_name, stats.last_record_id)
+            .await?;
+        if records.is_empty() {
+            return Ok(stats);
+        }
+
+        for record in records.iter() {
+            stats.apply(record);
+        }
+        self.stats_repo.save(&stats).await?;
+
+        Ok(stats)
+    }
+
+    /**
+     * 策略績效與淨值曲線
+     */
+    async fn performance(&self, strategy_name: String) -> anyhow::Result<StrategyPerformance> {
+        let stats = self.refresh(strategy_name.clone()).await?;
+        let records = self
+            .stats_repo
+            .list_closed_records(strategy_name.clone(), 0)
+            .await?;
+
+        Ok(StrategyPerformance {
+            stats: StatsInfo::from(&stats),
+            equity_curve: equity_curve(&strategy_name, &records),
+        })
+    }
+
+    /**
+     * 依快取的統計排序
+     */
+    async fn leaderboard(&self, query: LeaderboardQuery) -> anyhow::Result<Vec<StatsInfo>> {
+        let list = self
+            .stats_repo
+            .list(query.min_trades.unwrap_or(1))
+            .await?
+            .into_iter()
+            .map(|model| StatsInfo::from(&StrategyStats::from(model)))
+            .collect();
+
+        let mut list = rank(list, query.sort, query.direction);
+        list.truncate(query.limit.unwrap_or(DEFAULT_LEADERBOARD_LIMIT));
+        Ok(list)
+    }
+}
@@ -1,8 +1,10 @@
-use anyhow::{Error, Result};
-use axum::Router;
+use anyhow::{anyhow, Error, Result};
+use axum::{routing::get, Extension, Router};
 use dotenv::dotenv;
 use migration::{Migrator, MigratorTrait};
 use pkg::db::ORM;
+use pkg::exchange::bybit::BybitFactory;
+use pkg::jwt::jwks_handler;
 use std::net::SocketAddr;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -10,6 +12,12 @@ use sea_orm::{DatabaseConnection, DbErr};
 
 use std::sync::Arc;
 
+use order::router::new as new_order_router;
+use report::router::new as new_report_router;
+use signal::router::new as new_signal_router;
+use strategy::router::new as new_strategy_router;
+use subscribe::router::new as new_subscribe_router;
+use symbol::router::new as new_symbol_router;
 use user::router::new as new_user_router;
 
 //migrate run migrate
@@ -18,6 +26,9 @@ async fn migrate(db: &DatabaseConnection) -> Result<(), DbErr> {
     Ok(())
 }
 
+//bootstrap-admin 只建立或重設admin後結束
+const CMD_BOOTSTRAP_ADMIN: &str = "bootstrap-admin";
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     dotenv().ok();
@@ -30,22 +41,80 @@ async fn main() -> Result<(), Error> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let command = std::env::args().nth(1);
+    if let Some(cmd) = command.as_deref() {
+        if cmd != CMD_BOOTSTRAP_ADMIN {
+            return Err(anyhow!("unknown command: {}", cmd));
+        }
+    }
+
+    //------- jwt keys ----------
+    pkg::jwt::init();
+
     //------- db connect ----------
     let mysql = pkg::db::Mysql::new().await?;
     let db = mysql.get_db().await;
     migrate(db).await?;
 
     let mysql = Arc::new(mysql);
+    let exchange = BybitFactory::from_env();
+
+    //------- admin bootstrap ----------
+    if command.is_some() {
+        let res = user::bootstrap::admin(mysql.clone(), exchange)
+            .await?
+            .ok_or_else(|| anyhow!("ADMIN_ACCOUNT and ADMIN_PASS are not set"))?;
+        tracing::info!("admin bootstrap: {:?}", res);
+        return Ok(());
+    }
+    //ADMIN_ACCOUNT有設定但ADMIN_PASS未設定(或密碼強度不足)時不啟動
+    user::bootstrap::admin(mysql.clone(), exchange.clone())
+        .await
+        .map_err(|e| anyhow!("admin bootstrap failed: {}", e))?;
+
+    //------- exchange & order engine ----------
+    let order_engine = order::engine::new(mysql.clone(), exchange.clone());
+    order::worker::spawn(order_engine.clone());
+    symbol::sync::spawn(mysql.clone(), exchange.clone());
 
     //----- user -----------
-    let user_router = new_user_router(mysql); // v1/user
+    let session_store = user::session::store(mysql.clone());
+    let user_router = new_user_router(mysql.clone(), exchange.clone()); // v1/user
+
+    //----- strategy -----------
+    let strategy_router = new_strategy_router(mysql.clone()); // v1/strategy
+
+    //----- symbol -----------
+    let symbol_router = new_symbol_router(mysql.clone(), exchange); // v1/symbols
+
+    //----- subscribe -----------
+    let subscribe_router = new_subscribe_router(mysql.clone()); // v1/subscribe
+
+    //----- signal -----------
+    let signal_router = new_signal_router(mysql.clone(), order_engine.clone()); // v1/signal
+
+    //----- order -----------
+    let order_router = new_order_router(mysql.clone(), order_engine); // v1/orders, v1/risk
+
+    //----- report -----------
+    let report_router = new_report_router(mysql); // v1/reports
 
     //--------------------------
 
-    let main_router = Router::new().merge(user_router);
+    let main_router = Router::new()
+        .merge(user_router)
+        .merge(strategy_router)
+        .merge(symbol_router)
+        .merge(subscribe_router)
+        .merge(signal_router)
+        .merge(order_router)
+        .merge(report_router);
     //--------------------------
 
-    let app = Router::new().nest("/api", main_router);
+    let app = Router::new()
+        .route("/.well-known/jwks.json", get(jwks_handler))
+        .nest("/api", main_router)
+        .layer(Extension(session_store));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
 
@@ -53,7 +122,7 @@ async fn main() -> Result<(), Error> {
     println!("web listening on {}", addr);
 
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 
@@ -0,0 +1,122 @@
+use crate::domain::{CreateStrategy, StrategyContainer, StrategyInfo, UpdateStrategy};
+use axum::{
+    extract::{Extension, Path},
+    response::IntoResponse,
+};
+use entity::strategies::Model as StrategyModel;
+use pkg::{
+    error::{AppError, AppResult},
+    extract::ValidatedJson,
+    jwt::Claims,
+    responder::ok,
+};
+use std::sync::Arc;
+
+/**
+ * 依名稱取得策略
+ */
+async fn find_strategy(c: &StrategyContainer, name: String) -> AppResult<StrategyModel> {
+    c.strategy_ucase
+        .get_by_name(name)
+        .await?
+        .ok_or_else(|| AppError::not_found("Strategy not found"))
+}
+
+/**
+ * 策略列表
+ */
+pub async fn list_strategies(
+    _claims: Claims,
+    Extension(c): Extension<Arc<StrategyContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let list = c.strategy_ucase.list().await?;
+    Ok(ok(list))
+}
+
+/**
+ * 取得單一策略
+ */
+pub async fn get_strategy(
+    Path(name): Path<String>,
+    _claims: Claims,
+    Extension(c): Extension<Arc<StrategyContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let model = find_strategy(&c, name).await?;
+    Ok(ok(StrategyInfo::from(model)))
+}
+
+/**
+ * 新增策略
+ */
+pub async fn create_strategy(
+    ValidatedJson(payload): ValidatedJson<CreateStrategy>,
+    Extension(c): Extension<Arc<StrategyContainer>>,
+) -> AppResult<impl IntoResponse> {
+    //判斷策略存在
+    if c.strategy_ucase.is_exist(payload.name.clone()).await? {
+        return Err(AppError::duplicate("Strategy already exist"));
+    }
+
+    //判斷合約存在
+    if !c
+        .strategy_ucase
+        .symbol_exist(payload.symbol_name.clone())
+        .await?
+    {
+        return Err(AppError::validation("Symbol not found"));
+    }
+
+    //存入DB
+    let model = c.strategy_ucase.create(payload).await?;
+    Ok(ok(StrategyInfo::from(model)))
+}
+
+/**
+ * 更新策略
+ */
+pub async fn update_strategy(
+    Path(name): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateStrategy>,
+    Extension(c): Extension<Arc<StrategyContainer>>,
+) -> AppResult<impl IntoResponse> {
+    //判斷合約存在
+    if let Some(symbol_name) = payload.symbol_name.clone() {
+        if !c.strategy_ucase.symbol_exist(symbol_name).await? {
+            return Err(AppError::validation("Symbol not found"));
+        }
+    }
+
+    let model = find_strategy(&c, name).await?;
+    let model = c.strategy_ucase.update(model, payload).await?;
+    Ok(ok(StrategyInfo::from(model)))
+}
+
+/**
+ * 啟用策略
+ */
+pub async fn enable_strategy(
+    Path(name): Path<String>,
+    Extension(c): Extension<Arc<StrategyContainer>>,
+) -> AppResult<impl IntoResponse> {
+    set_state(name, 1, c).await
+}
+
+/**
+ * 停用策略
+ */
+pub async fn disable_strategy(
+    Path(name): Path<String>,
+    Extension(c): Extension<Arc<StrategyContainer>>,
+) -> AppResult<impl IntoResponse> {
+    set_state(name, 0, c).await
+}
+
+async fn set_state(
+    name: String,
+    state: i8,
+    c: Arc<StrategyContainer>,
+) -> AppResult<impl IntoResponse> {
+    let model = find_strategy(&c, name).await?;
+    let model = c.strategy_ucase.set_state(model, state).await?;
+    Ok(ok(StrategyInfo::from(model)))
+}
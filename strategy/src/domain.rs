@@ -0,0 +1,157 @@
+use anyhow::Result;
+use axum::async_trait;
+use entity::strategies::{ActiveModel as StrategyActiveModel, Model as StrategyModel};
+use pkg::responder::Data;
+use serde::{Deserialize, Serialize};
+use std::convert::From;
+use std::sync::Arc;
+use validator::Validate;
+
+/**
+ * Traits
+ */
+#[async_trait]
+pub trait StrategyRepository: Send + Sync {
+    async fn list(&self) -> Result<Vec<StrategyModel>>;
+    async fn get_by_name(&self, name: String) -> Result<Option<StrategyModel>>;
+    async fn is_exist(&self, name: String) -> Result<bool>;
+    async fn symbol_exist(&self, symbol_name: String) -> Result<bool>;
+    async fn create(&self, active: StrategyActiveModel) -> Result<StrategyModel>;
+    async fn update(&self, active: StrategyActiveModel) -> Result<StrategyModel>;
+}
+
+#[async_trait]
+pub trait StrategyUsecase: Send + Sync {
+    async fn list(&self) -> Result<Vec<StrategyInfo>>;
+    async fn get_by_name(&self, name: String) -> Result<Option<StrategyModel>>;
+    async fn is_exist(&self, name: String) -> Result<bool>;
+    async fn symbol_exist(&self, symbol_name: String) -> Result<bool>;
+    async fn create(&self, body: CreateStrategy) -> Result<StrategyModel>;
+    async fn update(&self, model: StrategyModel, body: UpdateStrategy) -> Result<StrategyModel>;
+    async fn set_state(&self, model: StrategyModel, state: i8) -> Result<StrategyModel>;
+}
+
+/**
+ * Extension container
+ */
+pub struct StrategyContainer {
+    pub strategy_ucase: Arc<dyn StrategyUsecase>,
+}
+
+impl StrategyContainer {
+    pub fn new(strategy_ucase: Arc<dyn StrategyUsecase>) -> Arc<StrategyContainer> {
+        Arc::new(StrategyContainer { strategy_ucase })
+    }
+}
+
+/**
+ * Create strategy request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateStrategy {
+    #[validate(length(min = 1, max = 30))]
+    pub name: String,
+    #[validate(length(min = 1, max = 30))]
+    pub symbol_name: String,
+    #[validate(range(min = 1, max = 2))]
+    pub side: i8,
+    #[validate(length(min = 16, max = 64))]
+    pub secret: String,
+    #[validate(length(max = 255))]
+    #[serde(default)]
+    pub remark: String,
+}
+
+/**
+ * Update strategy request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct UpdateStrategy {
+    #[validate(length(min = 1, max = 30))]
+    pub symbol_name: Option<String>,
+    #[validate(range(min = 1, max = 2))]
+    pub side: Option<i8>,
+    #[validate(length(min = 16, max = 64))]
+    pub secret: Option<String>,
+    #[validate(length(max = 255))]
+    pub remark: Option<String>,
+}
+
+/**
+ * Strategy info
+ */
+#[derive(Serialize)]
+pub struct StrategyInfo {
+    pub name: String,
+    pub symbol_name: String,
+    pub state: i8,
+    pub side: i8,
+    pub remark: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Data for StrategyInfo {}
+
+impl From<StrategyModel> for StrategyInfo {
+    fn from(model: StrategyModel) -> Self {
+        let StrategyModel {
+            name,
+            symbol_name,
+            state,
+            side,
+            remark,
+            created_at,
+            updated_at,
+            ..
+        } = model;
+
+        StrategyInfo {
+            name,
+            symbol_name,
+            state,
+            side,
+            remark,
+            created_at: created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            updated_at: updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn info_hides_secret() {
+        let created_at = Local.with_ymd_and_hms(2022, 8, 13, 8, 0, 0).unwrap();
+        let info = StrategyInfo::from(StrategyModel {
+            name: "btc_1h".to_owned(),
+            symbol_name: "BTCUSDT".to_owned(),
+            state: 1,
+            side: 1,
+            secret: "0123456789abcdef".to_owned(),
+            remark: String::new(),
+            created_at,
+            updated_at: created_at,
+        });
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert!(json.get("secret").is_none());
+        assert_eq!(json["created_at"], "2022-08-13 08:00:00");
+    }
+
+    #[test]
+    fn create_requires_long_secret() {
+        let body: CreateStrategy = serde_json::from_value(serde_json::json!({
+            "name": "btc_1h",
+            "symbol_name": "BTCUSDT",
+            "side": 1,
+            "secret": "short",
+        }))
+        .unwrap();
+        let errors = body.validate().unwrap_err();
+        assert!(errors.field_errors().contains_key("secret"));
+    }
+}
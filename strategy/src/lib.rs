@@ -0,0 +1,54 @@
+mod delivery;
+pub mod domain;
+mod repository;
+pub mod usecase;
+
+pub mod router {
+    use crate::{
+        delivery::http::handler::{
+            create_strategy, disable_strategy, enable_strategy, get_strategy, list_strategies,
+            update_strategy,
+        },
+        domain::StrategyContainer,
+        repository::mysql::strategy_repo::StrategyRepo,
+        usecase::strategy_ucase::StrategyUcase,
+    };
+    use axum::{
+        extract::Extension,
+        middleware::from_extractor,
+        routing::{get, post, put},
+        Router,
+    };
+
+    use pkg::{
+        db::ORM,
+        rbac::{perm, RequirePermission},
+    };
+    use std::sync::Arc;
+
+    /**
+     * new handler
+     */
+    pub fn new(orm: Arc<dyn ORM>) -> Router {
+        let strategy_repo = StrategyRepo::new(orm);
+        let strategy_ucase = StrategyUcase::new(strategy_repo);
+        let strategy_container = StrategyContainer::new(strategy_ucase);
+
+        //需要 strategies:write 權限
+        let write_router = Router::new()
+            .route("/", post(create_strategy))
+            .route("/:name", put(update_strategy))
+            .route("/:name/enable", post(enable_strategy))
+            .route("/:name/disable", post(disable_strategy))
+            .route_layer(from_extractor::<RequirePermission<perm::StrategiesWrite>>());
+
+        let strategy_router = Router::new()
+            .route("/", get(list_strategies))
+            .route("/:name", get(get_strategy))
+            .merge(write_router);
+
+        Router::new()
+            .nest("/v1/strategy", strategy_router)
+            .layer(Extension(strategy_container))
+    }
+}
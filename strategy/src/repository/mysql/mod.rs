@@ -0,0 +1 @@
+pub mod strategy_repo;
@@ -0,0 +1,81 @@
+use crate::domain::StrategyRepository;
+use async_trait::async_trait;
+use entity::{prelude::*, strategies};
+use pkg::db::ORM;
+use sea_orm::{prelude::*, ConnectionTrait, DbBackend, QueryOrder, Statement};
+use std::sync::Arc;
+
+pub struct StrategyRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl StrategyRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn StrategyRepository> {
+        Arc::new(StrategyRepo { mysql })
+    }
+
+    async fn count(&self, sql: &str, value: String) -> anyhow::Result<bool> {
+        let db = self.mysql.get_db().await;
+        let res = db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::MySql,
+                sql,
+                vec![value.into()],
+            ))
+            .await?;
+
+        let count = match res {
+            Some(row) => row.try_get::<i64>("", "count")?,
+            None => 0,
+        };
+        Ok(count > 0)
+    }
+}
+
+#[async_trait]
+impl StrategyRepository for StrategyRepo {
+    async fn list(&self) -> anyhow::Result<Vec<strategies::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = Strategies::find()
+            .order_by_asc(strategies::Column::Name)
+            .all(db)
+            .await?;
+
+        Ok(models)
+    }
+
+    async fn get_by_name(&self, name: String) -> anyhow::Result<Option<strategies::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Strategies::find_by_id(name).one(db).await?;
+
+        Ok(model)
+    }
+
+    async fn is_exist(&self, name: String) -> anyhow::Result<bool> {
+        self.count(
+            r#"SELECT COUNT(name) AS count FROM strategies WHERE name = ?"#,
+            name,
+        )
+        .await
+    }
+
+    async fn symbol_exist(&self, symbol_name: String) -> anyhow::Result<bool> {
+        self.count(
+            r#"SELECT COUNT(name) AS count FROM symbols WHERE name = ?"#,
+            symbol_name,
+        )
+        .await
+    }
+
+    async fn create(&self, active: strategies::ActiveModel) -> anyhow::Result<strategies::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.insert(db).await?;
+        Ok(model)
+    }
+
+    async fn update(&self, active: strategies::ActiveModel) -> anyhow::Result<strategies::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.update(db).await?;
+        Ok(model)
+    }
+}
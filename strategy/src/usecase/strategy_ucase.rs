@@ -0,0 +1,101 @@
+use crate::domain::{
+    CreateStrategy, StrategyInfo, StrategyRepository, StrategyUsecase, UpdateStrategy,
+};
+use async_trait::async_trait;
+use entity::strategies;
+use sea_orm::ActiveValue::Set;
+use std::sync::Arc;
+
+pub struct StrategyUcase {
+    strategy_repo: Arc<dyn StrategyRepository>,
+}
+
+impl StrategyUcase {
+    pub fn new(strategy_repo: Arc<dyn StrategyRepository>) -> Arc<dyn StrategyUsecase> {
+        Arc::new(StrategyUcase { strategy_repo })
+    }
+}
+
+#[async_trait]
+impl StrategyUsecase for StrategyUcase {
+    /**
+     * 取得策略列表
+     */
+    async fn list(&self) -> anyhow::Result<Vec<StrategyInfo>> {
+        let models = self.strategy_repo.list().await?;
+        let list = models.into_iter().map(StrategyInfo::from).collect();
+        Ok(list)
+    }
+
+    async fn get_by_name(&self, name: String) -> anyhow::Result<Option<strategies::Model>> {
+        let res = self.strategy_repo.get_by_name(name).await?;
+        Ok(res)
+    }
+
+    async fn is_exist(&self, name: String) -> anyhow::Result<bool> {
+        self.strategy_repo.is_exist(name).await
+    }
+
+    /**
+     * 合約是否存在
+     */
+    async fn symbol_exist(&self, symbol_name: String) -> anyhow::Result<bool> {
+        self.strategy_repo.symbol_exist(symbol_name).await
+    }
+
+    /**
+     * 建立策略, 預設為停用
+     */
+    async fn create(&self, body: CreateStrategy) -> anyhow::Result<strategies::Model> {
+        let active_model = strategies::ActiveModel {
+            name: Set(body.name),
+            symbol_name: Set(body.symbol_name),
+            side: Set(body.side),
+            secret: Set(body.secret),
+            remark: Set(body.remark),
+            state: Set(0),
+            ..Default::default()
+        };
+        let res = self.strategy_repo.create(active_model).await?;
+        Ok(res)
+    }
+
+    /**
+     * 更新策略
+     */
+    async fn update(
+        &self,
+        model: strategies::Model,
+        body: UpdateStrategy,
+    ) -> anyhow::Result<strategies::Model> {
+        let mut active: strategies::ActiveModel = model.into();
+        if let Some(symbol_name) = body.symbol_name {
+            active.symbol_name = Set(symbol_name);
+        }
+        if let Some(side) = body.side {
+            active.side = Set(side);
+        }
+        if let Some(secret) = body.secret {
+            active.secret = Set(secret);
+        }
+        if let Some(remark) = body.remark {
+            active.remark = Set(remark);
+        }
+        let res = self.strategy_repo.update(active).await?;
+        Ok(res)
+    }
+
+    /**
+     * 啟用 / 停用策略
+     */
+    async fn set_state(
+        &self,
+        model: strategies::Model,
+        state: i8,
+    ) -> anyhow::Result<strategies::Model> {
+        let mut active: strategies::ActiveModel = model.into();
+        active.state = Set(state);
+        let res = self.strategy_repo.update(active).await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::domain::{
+    check_leverage, CreateSubscribe, SubscribeContainer, SubscribeInfo, UpdateSubscribe,
+};
+use axum::{
+    extract::{Extension, Path},
+    response::IntoResponse,
+};
+use entity::subscribes::Model as SubscribeModel;
+use pkg::{
+    error::{AppError, AppResult},
+    extract::ValidatedJson,
+    jwt::Claims,
+    responder::{ok, Detail},
+};
+use std::sync::Arc;
+
+/**
+ * 依策略綁定的合約檢查槓桿
+ */
+async fn verify_leverage(
+    c: &SubscribeContainer,
+    strategy_name: String,
+    leverage: i16,
+) -> AppResult<()> {
+    let strategy = c
+        .subscribe_ucase
+        .get_strategy(strategy_name)
+        .await?
+        .ok_or_else(|| AppError::not_found("Strategy not found"))?;
+
+    let symbol = c
+        .subscribe_ucase
+        .get_symbol(strategy.symbol_name)
+        .await?
+        .ok_or_else(|| AppError::not_found("Symbol not found"))?;
+
+    check_leverage(&symbol, leverage).map_err(AppError::Validation)
+}
+
+/**
+ * 取得用戶對策略的訂閱
+ */
+async fn find_subscribe(
+    c: &SubscribeContainer,
+    account: String,
+    strategy_name: String,
+) -> AppResult<SubscribeModel> {
+    c.subscribe_ucase
+        .get(account, strategy_name)
+        .await?
+        .ok_or_else(|| AppError::not_found("Subscribe not found"))
+}
+
+/**
+ * 我的訂閱列表
+ */
+pub async fn list_subscribes(
+    claims: Claims,
+    Extension(c): Extension<Arc<SubscribeContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let list = c.subscribe_ucase.list(claims.account).await?;
+    Ok(ok(list))
+}
+
+/**
+ * 訂閱策略
+ */
+pub async fn subscribe(
+    ValidatedJson(payload): ValidatedJson<CreateSubscribe>,
+    claims: Claims,
+    Extension(c): Extension<Arc<SubscribeContainer>>,
+) -> AppResult<impl IntoResponse> {
+    //判斷是否重複訂閱
+    if c.subscribe_ucase
+        .get(claims.account.clone(), payload.strategy_name.clone())
+        .await?
+        .is_some()
+    {
+        return Err(AppError::duplicate("Strategy already subscribed"));
+    }
+
+    verify_leverage(&c, payload.strategy_name.clone(), payload.leverage).await?;
+
+    //存入DB
+    let model = c.subscribe_ucase.create(claims.account, payload).await?;
+    Ok(ok(SubscribeInfo::from(model)))
+}
+
+/**
+ * 更新訂閱設定
+ */
+pub async fn update_subscribe(
+    Path(strategy_name): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateSubscribe>,
+    claims: Claims,
+    Extension(c): Extension<Arc<SubscribeContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let model = find_subscribe(&c, claims.account, strategy_name.clone()).await?;
+
+    if let Some(leverage) = payload.leverage {
+        verify_leverage(&c, strategy_name, leverage).await?;
+    }
+
+    let model = c.subscribe_ucase.update(model, payload).await?;
+    Ok(ok(SubscribeInfo::from(model)))
+}
+
+/**
+ * 取消訂閱
+ */
+pub async fn unsubscribe(
+    Path(strategy_name): Path<String>,
+    claims: Claims,
+    Extension(c): Extension<Arc<SubscribeContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let model = find_subscribe(&c, claims.account, strategy_name).await?;
+
+    c.subscribe_ucase.delete(model).await?;
+    Ok(ok(Detail("Unsubscribed".to_owned())))
+}
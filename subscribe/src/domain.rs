@@ -0,0 +1,206 @@
+use anyhow::Result;
+use axum::async_trait;
+use entity::{
+    strategies::Model as StrategyModel,
+    subscribes::{ActiveModel as SubscribeActiveModel, Model as SubscribeModel},
+    symbols::Model as SymbolModel,
+};
+use pkg::responder::Data;
+use serde::{Deserialize, Serialize};
+use std::convert::From;
+use std::sync::Arc;
+use validator::{Validate, ValidationError};
+
+/**
+ * Traits
+ */
+#[async_trait]
+pub trait SubscribeRepository: Send + Sync {
+    async fn list_by_account(&self, account: String) -> Result<Vec<SubscribeModel>>;
+    async fn get(&self, account: String, strategy_name: String) -> Result<Option<SubscribeModel>>;
+    async fn get_strategy(&self, name: String) -> Result<Option<StrategyModel>>;
+    async fn get_symbol(&self, name: String) -> Result<Option<SymbolModel>>;
+    async fn create(&self, active: SubscribeActiveModel) -> Result<SubscribeModel>;
+    async fn update(&self, active: SubscribeActiveModel) -> Result<SubscribeModel>;
+    async fn delete(&self, model: SubscribeModel) -> Result<()>;
+}
+
+#[async_trait]
+pub trait SubscribeUsecase: Send + Sync {
+    async fn list(&self, account: String) -> Result<Vec<SubscribeInfo>>;
+    async fn get(&self, account: String, strategy_name: String) -> Result<Option<SubscribeModel>>;
+    async fn get_strategy(&self, name: String) -> Result<Option<StrategyModel>>;
+    async fn get_symbol(&self, name: String) -> Result<Option<SymbolModel>>;
+    async fn create(&self, account: String, body: CreateSubscribe) -> Result<SubscribeModel>;
+    async fn update(&self, model: SubscribeModel, body: UpdateSubscribe) -> Result<SubscribeModel>;
+    async fn delete(&self, model: SubscribeModel) -> Result<()>;
+}
+
+/**
+ * Extension container
+ */
+pub struct SubscribeContainer {
+    pub subscribe_ucase: Arc<dyn SubscribeUsecase>,
+}
+
+impl SubscribeContainer {
+    pub fn new(subscribe_ucase: Arc<dyn SubscribeUsecase>) -> Arc<SubscribeContainer> {
+        Arc::new(SubscribeContainer { subscribe_ucase })
+    }
+}
+
+/**
+ * 金額必須大於0
+ */
+fn validate_amount(amount: f64) -> Result<(), ValidationError> {
+    if amount > 0.0 && amount.is_finite() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("positive"))
+    }
+}
+
+/**
+ * 檢查槓桿是否符合合約的上下限與最小增減單位
+ */
+pub fn check_leverage(symbol: &SymbolModel, leverage: i16) -> Result<(), String> {
+    let leverage = leverage as i32;
+    if leverage < symbol.min_leverage || leverage > symbol.max_leverage {
+        return Err(format!(
+            "Leverage must be between {} and {}",
+            symbol.min_leverage, symbol.max_leverage
+        ));
+    }
+
+    let step: f64 = symbol.leverage_step.parse().unwrap_or(1.0);
+    if step > 0.0 {
+        let steps = (leverage - symbol.min_leverage) as f64 / step;
+        if (steps - steps.round()).abs() > 1e-9 {
+            return Err(format!("Leverage must be a multiple of {}", step));
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Create subscribe request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateSubscribe {
+    #[validate(length(min = 1, max = 30))]
+    pub strategy_name: String,
+    #[validate(custom = "validate_amount")]
+    pub amount: f64,
+    #[serde(default)]
+    pub is_isolated: bool,
+    #[validate(range(min = 1))]
+    pub leverage: i16,
+}
+
+/**
+ * Update subscribe request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct UpdateSubscribe {
+    #[validate(custom = "validate_amount")]
+    pub amount: Option<f64>,
+    pub is_isolated: Option<bool>,
+    #[validate(range(min = 1))]
+    pub leverage: Option<i16>,
+}
+
+/**
+ * Subscribe info
+ */
+#[derive(Serialize)]
+pub struct SubscribeInfo {
+    pub strategy_name: String,
+    pub amount: f64,
+    pub is_isolated: bool,
+    pub leverage: i16,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Data for SubscribeInfo {}
+
+impl From<SubscribeModel> for SubscribeInfo {
+    fn from(model: SubscribeModel) -> Self {
+        let SubscribeModel {
+            strategy_name,
+            amount,
+            is_isolated,
+            leverage,
+            created_at,
+            updated_at,
+            ..
+        } = model;
+
+        SubscribeInfo {
+            strategy_name,
+            amount,
+            is_isolated: is_isolated.unwrap_or(0) == 1,
+            leverage,
+            created_at: created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            updated_at: updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(min_leverage: i32, max_leverage: i32, leverage_step: &str) -> SymbolModel {
+        SymbolModel {
+            name: "BTCUSDT".to_owned(),
+            alias: "BTCUSDT".to_owned(),
+            status: "Trading".to_owned(),
+            base_currency: "BTC".to_owned(),
+            quote_currency: "USDT".to_owned(),
+            price_scale: 2,
+            taker_fee: "0.0006".to_owned(),
+            maker_fee: "0.0001".to_owned(),
+            funding_interval: 480,
+            max_trading_qty: 100.0,
+            min_trading_qty: 0.001,
+            qty_step: 0.001,
+            post_only_max_trading_qty: "1000".to_owned(),
+            min_price: "0.5".to_owned(),
+            max_price: "999999".to_owned(),
+            tick_size: "0.5".to_owned(),
+            min_leverage,
+            max_leverage,
+            leverage_step: leverage_step.to_owned(),
+        }
+    }
+
+    #[test]
+    fn leverage_range() {
+        let ranged = symbol(1, 100, "0.01");
+        assert!(check_leverage(&ranged, 1).is_ok());
+        assert!(check_leverage(&ranged, 100).is_ok());
+        assert_eq!(
+            check_leverage(&ranged, 0),
+            Err("Leverage must be between 1 and 100".to_owned())
+        );
+        assert!(check_leverage(&ranged, 101).is_err());
+    }
+
+    #[test]
+    fn leverage_step() {
+        //由min_leverage起算的倍數
+        let stepped = symbol(1, 50, "5");
+        assert!(check_leverage(&stepped, 6).is_ok());
+        assert!(check_leverage(&stepped, 46).is_ok());
+        assert_eq!(
+            check_leverage(&stepped, 5),
+            Err("Leverage must be a multiple of 5".to_owned())
+        );
+
+        //無法解析時視為1, 0則不檢查
+        assert!(check_leverage(&symbol(1, 50, "abc"), 7).is_ok());
+        assert!(check_leverage(&symbol(1, 50, "0"), 7).is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+mod delivery;
+pub mod domain;
+mod repository;
+pub mod usecase;
+
+pub mod router {
+    use crate::{
+        delivery::http::handler::{list_subscribes, subscribe, unsubscribe, update_subscribe},
+        domain::SubscribeContainer,
+        repository::mysql::subscribe_repo::SubscribeRepo,
+        usecase::subscribe_ucase::SubscribeUcase,
+    };
+    use axum::{
+        extract::Extension,
+        routing::{get, put},
+        Router,
+    };
+
+    use pkg::db::ORM;
+    use std::sync::Arc;
+
+    /**
+     * new handler
+     */
+    pub fn new(orm: Arc<dyn ORM>) -> Router {
+        let subscribe_repo = SubscribeRepo::new(orm);
+        let subscribe_ucase = SubscribeUcase::new(subscribe_repo);
+        let subscribe_container = SubscribeContainer::new(subscribe_ucase);
+
+        let subscribe_router = Router::new()
+            .route("/", get(list_subscribes).post(subscribe))
+            .route("/:strategy_name", put(update_subscribe).delete(unsubscribe));
+
+        Router::new()
+            .nest("/v1/subscribe", subscribe_router)
+            .layer(Extension(subscribe_container))
+    }
+}
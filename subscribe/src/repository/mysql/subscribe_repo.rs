@@ -0,0 +1,76 @@
+use crate::domain::SubscribeRepository;
+use async_trait::async_trait;
+use entity::{prelude::*, strategies, subscribes, symbols};
+use pkg::db::ORM;
+use sea_orm::{prelude::*, QueryOrder};
+use std::sync::Arc;
+
+pub struct SubscribeRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl SubscribeRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn SubscribeRepository> {
+        Arc::new(SubscribeRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl SubscribeRepository for SubscribeRepo {
+    async fn list_by_account(&self, account: String) -> anyhow::Result<Vec<subscribes::Model>> {
+        let db = self.mysql.get_db().await;
+        let models = Subscribes::find()
+            .filter(subscribes::Column::UserAccount.eq(account))
+            .order_by_asc(subscribes::Column::Id)
+            .all(db)
+            .await?;
+
+        Ok(models)
+    }
+
+    async fn get(
+        &self,
+        account: String,
+        strategy_name: String,
+    ) -> anyhow::Result<Option<subscribes::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Subscribes::find()
+            .filter(subscribes::Column::UserAccount.eq(account))
+            .filter(subscribes::Column::StrategyName.eq(strategy_name))
+            .one(db)
+            .await?;
+
+        Ok(model)
+    }
+
+    async fn get_strategy(&self, name: String) -> anyhow::Result<Option<strategies::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Strategies::find_by_id(name).one(db).await?;
+        Ok(model)
+    }
+
+    async fn get_symbol(&self, name: String) -> anyhow::Result<Option<symbols::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Symbols::find_by_id(name).one(db).await?;
+        Ok(model)
+    }
+
+    async fn create(&self, active: subscribes::ActiveModel) -> anyhow::Result<subscribes::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.insert(db).await?;
+        Ok(model)
+    }
+
+    async fn update(&self, active: subscribes::ActiveModel) -> anyhow::Result<subscribes::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.update(db).await?;
+        Ok(model)
+    }
+
+    async fn delete(&self, model: subscribes::Model) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        let active: subscribes::ActiveModel = model.into();
+        active.delete(db).await?;
+        Ok(())
+    }
+}
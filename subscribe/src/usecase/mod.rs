@@ -0,0 +1 @@
+pub mod subscribe_ucase;
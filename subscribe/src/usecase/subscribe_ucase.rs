@@ -0,0 +1,102 @@
+use crate::domain::{
+    CreateSubscribe, SubscribeInfo, SubscribeRepository, SubscribeUsecase, UpdateSubscribe,
+};
+use async_trait::async_trait;
+use entity::{strategies, subscribes, symbols};
+use pkg::{db, error::AppError};
+use sea_orm::ActiveValue::Set;
+use std::sync::Arc;
+
+pub struct SubscribeUcase {
+    subscribe_repo: Arc<dyn SubscribeRepository>,
+}
+
+impl SubscribeUcase {
+    pub fn new(subscribe_repo: Arc<dyn SubscribeRepository>) -> Arc<dyn SubscribeUsecase> {
+        Arc::new(SubscribeUcase { subscribe_repo })
+    }
+}
+
+#[async_trait]
+impl SubscribeUsecase for SubscribeUcase {
+    /**
+     * 取得用戶的訂閱列表
+     */
+    async fn list(&self, account: String) -> anyhow::Result<Vec<SubscribeInfo>> {
+        let models = self.subscribe_repo.list_by_account(account).await?;
+        let list = models.into_iter().map(SubscribeInfo::from).collect();
+        Ok(list)
+    }
+
+    async fn get(
+        &self,
+        account: String,
+        strategy_name: String,
+    ) -> anyhow::Result<Option<subscribes::Model>> {
+        let res = self.subscribe_repo.get(account, strategy_name).await?;
+        Ok(res)
+    }
+
+    async fn get_strategy(&self, name: String) -> anyhow::Result<Option<strategies::Model>> {
+        let res = self.subscribe_repo.get_strategy(name).await?;
+        Ok(res)
+    }
+
+    async fn get_symbol(&self, name: String) -> anyhow::Result<Option<symbols::Model>> {
+        let res = self.subscribe_repo.get_symbol(name).await?;
+        Ok(res)
+    }
+
+    /**
+     * 訂閱策略, 同時送出的重複訂閱由唯一索引擋下
+     */
+    async fn create(
+        &self,
+        account: String,
+        body: CreateSubscribe,
+    ) -> anyhow::Result<subscribes::Model> {
+        let active_model = subscribes::ActiveModel {
+            user_account: Set(account),
+            strategy_name: Set(body.strategy_name),
+            amount: Set(body.amount),
+            is_isolated: Set(Some(body.is_isolated as i8)),
+            leverage: Set(body.leverage),
+            ..Default::default()
+        };
+        match self.subscribe_repo.create(active_model).await {
+            Err(e) if db::is_duplicate_key(&e) => {
+                Err(AppError::duplicate("Strategy already subscribed").into())
+            }
+            res => res,
+        }
+    }
+
+    /**
+     * 更新訂閱設定
+     */
+    async fn update(
+        &self,
+        model: subscribes::Model,
+        body: UpdateSubscribe,
+    ) -> anyhow::Result<subscribes::Model> {
+        let mut active: subscribes::ActiveModel = model.into();
+        if let Some(amount) = body.amount {
+            active.amount = Set(amount);
+        }
+        if let Some(is_isolated) = body.is_isolated {
+            active.is_isolated = Set(Some(is_isolated as i8));
+        }
+        if let Some(leverage) = body.leverage {
+            active.leverage = Set(leverage);
+        }
+        let res = self.subscribe_repo.update(active).await?;
+        Ok(res)
+    }
+
+    /**
+     * 取消訂閱
+     */
+    async fn delete(&self, model: subscribes::Model) -> anyhow::Result<()> {
+        self.subscribe_repo.delete(model).await
+    }
+}
@@ -0,0 +1,49 @@
+use crate::domain::{SymbolContainer, SymbolInfo, SymbolQuery};
+use axum::{
+    extract::{Extension, Path, Query},
+    response::IntoResponse,
+};
+use pkg::{
+    error::{AppError, AppResult},
+    jwt::Claims,
+    responder::ok,
+};
+use std::sync::Arc;
+
+/**
+ * 合約列表, 可依quote_currency / status篩選
+ */
+pub async fn list_symbols(
+    Query(query): Query<SymbolQuery>,
+    _claims: Claims,
+    Extension(c): Extension<Arc<SymbolContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let list = c.symbol_ucase.list(query).await?;
+    Ok(ok(list))
+}
+
+/**
+ * 取得單一合約
+ */
+pub async fn get_symbol(
+    Path(name): Path<String>,
+    _claims: Claims,
+    Extension(c): Extension<Arc<SymbolContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let model = c
+        .symbol_ucase
+        .get_by_name(name)
+        .await?
+        .ok_or_else(|| AppError::not_found("Symbol not found"))?;
+    Ok(ok(SymbolInfo::from(model)))
+}
+
+/**
+ * 手動從交易所同步合約
+ */
+pub async fn sync_symbols(
+    Extension(c): Extension<Arc<SymbolContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let report = c.symbol_ucase.sync().await?;
+    Ok(ok(report))
+}
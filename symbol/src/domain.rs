@@ -0,0 +1,145 @@
+use anyhow::Result;
+use axum::async_trait;
+use entity::symbols::{ActiveModel as SymbolActiveModel, Model as SymbolModel};
+use pkg::{exchange::SymbolInfo as ExchangeSymbol, responder::Data};
+use sea_orm::ActiveValue::Set;
+use serde::{Deserialize, Serialize};
+use std::convert::From;
+use std::sync::Arc;
+
+/**
+ * Traits
+ */
+#[async_trait]
+pub trait SymbolRepository: Send + Sync {
+    async fn list(&self, query: SymbolQuery) -> Result<Vec<SymbolModel>>;
+    async fn get_by_name(&self, name: String) -> Result<Option<SymbolModel>>;
+    async fn upsert(&self, actives: Vec<SymbolActiveModel>) -> Result<()>;
+    async fn mark_delisted(&self, listed: Vec<String>) -> Result<u64>;
+}
+
+#[async_trait]
+pub trait SymbolUsecase: Send + Sync {
+    async fn list(&self, query: SymbolQuery) -> Result<Vec<SymbolInfo>>;
+    async fn get_by_name(&self, name: String) -> Result<Option<SymbolModel>>;
+    async fn sync(&self) -> Result<SyncReport>;
+}
+
+/**
+ * Extension container
+ */
+pub struct SymbolContainer {
+    pub symbol_ucase: Arc<dyn SymbolUsecase>,
+}
+
+impl SymbolContainer {
+    pub fn new(symbol_ucase: Arc<dyn SymbolUsecase>) -> Arc<SymbolContainer> {
+        Arc::new(SymbolContainer { symbol_ucase })
+    }
+}
+
+/**
+ * 下架合約的狀態
+ */
+pub const STATUS_CLOSED: &str = "Closed";
+
+/**
+ * 列表篩選條件
+ */
+#[derive(Deserialize, Debug, Default)]
+pub struct SymbolQuery {
+    pub quote_currency: Option<String>,
+    pub status: Option<String>,
+}
+
+/**
+ * 同步結果
+ */
+#[derive(Serialize, Debug, Default)]
+pub struct SyncReport {
+    pub upserted: usize,
+    pub delisted: u64,
+}
+
+impl Data for SyncReport {}
+
+/**
+ * 交易所合約規格轉為active model
+ */
+pub fn to_active(info: ExchangeSymbol) -> SymbolActiveModel {
+    SymbolActiveModel {
+        name: Set(info.name),
+        alias: Set(info.alias),
+        status: Set(info.status),
+        base_currency: Set(info.base_currency),
+        quote_currency: Set(info.quote_currency),
+        price_scale: Set(info.price_scale),
+        taker_fee: Set(info.taker_fee),
+        maker_fee: Set(info.maker_fee),
+        funding_interval: Set(info.funding_interval),
+        max_trading_qty: Set(info.max_trading_qty),
+        min_trading_qty: Set(info.min_trading_qty),
+        qty_step: Set(info.qty_step),
+        post_only_max_trading_qty: Set(info.post_only_max_trading_qty),
+        min_price: Set(info.min_price),
+        max_price: Set(info.max_price),
+        tick_size: Set(info.tick_size),
+        min_leverage: Set(info.min_leverage),
+        max_leverage: Set(info.max_leverage),
+        leverage_step: Set(info.leverage_step),
+    }
+}
+
+/**
+ * Symbol info
+ */
+#[derive(Serialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub alias: String,
+    pub status: String,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub price_scale: i32,
+    pub taker_fee: String,
+    pub maker_fee: String,
+    pub funding_interval: i32,
+    pub max_trading_qty: f64,
+    pub min_trading_qty: f64,
+    pub qty_step: f64,
+    pub post_only_max_trading_qty: String,
+    pub min_price: String,
+    pub max_price: String,
+    pub tick_size: String,
+    pub min_leverage: i32,
+    pub max_leverage: i32,
+    pub leverage_step: String,
+}
+
+impl Data for SymbolInfo {}
+
+impl From<SymbolModel> for SymbolInfo {
+    fn from(model: SymbolModel) -> Self {
+        SymbolInfo {
+            name: model.name,
+            alias: model.alias,
+            status: model.status,
+            base_currency: model.base_currency,
+            quote_currency: model.quote_currency,
+            price_scale: model.price_scale,
+            taker_fee: model.taker_fee,
+            maker_fee: model.maker_fee,
+            funding_interval: model.funding_interval,
+            max_trading_qty: model.max_trading_qty,
+            min_trading_qty: model.min_trading_qty,
+            qty_step: model.qty_step,
+            post_only_max_trading_qty: model.post_only_max_trading_qty,
+            min_price: model.min_price,
+            max_price: model.max_price,
+            tick_size: model.tick_size,
+            min_leverage: model.min_leverage,
+            max_leverage: model.max_leverage,
+            leverage_step: model.leverage_step,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+mod delivery;
+pub mod domain;
+mod repository;
+pub mod usecase;
+
+pub mod router {
+    use crate::{
+        delivery::http::handler::{get_symbol, list_symbols, sync_symbols},
+        domain::SymbolContainer,
+        repository::mysql::symbol_repo::SymbolRepo,
+        usecase::symbol_ucase::SymbolUcase,
+    };
+    use axum::{
+        extract::Extension,
+        middleware::from_extractor,
+        routing::{get, post},
+        Router,
+    };
+
+    use pkg::{
+        db::ORM,
+        exchange::ExchangeFactory,
+        rbac::{perm, RequirePermission},
+    };
+    use std::sync::Arc;
+
+    /**
+     * new handler
+     */
+    pub fn new(orm: Arc<dyn ORM>, exchange: Arc<dyn ExchangeFactory>) -> Router {
+        let symbol_repo = SymbolRepo::new(orm);
+        let symbol_ucase = SymbolUcase::new(symbol_repo, exchange);
+        let symbol_container = SymbolContainer::new(symbol_ucase);
+
+        //需要 symbols:sync 權限
+        let sync_router = Router::new()
+            .route("/sync", post(sync_symbols))
+            .route_layer(from_extractor::<RequirePermission<perm::SymbolsSync>>());
+
+        let symbol_router = Router::new()
+            .route("/", get(list_symbols))
+            .route("/:name", get(get_symbol))
+            .merge(sync_router);
+
+        Router::new()
+            .nest("/v1/symbols", symbol_router)
+            .layer(Extension(symbol_container))
+    }
+}
+
+pub mod sync {
+    use crate::{repository::mysql::symbol_repo::SymbolRepo, usecase::symbol_ucase::SymbolUcase};
+    use pkg::{db::ORM, exchange::ExchangeFactory};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+
+    //預設每小時同步一次
+    const DEFAULT_SYNC_INTERVAL: u64 = 3600;
+
+    /**
+     * 背景同步合約, 啟動時先同步一次, 間隔秒數由SYMBOL_SYNC_INTERVAL設定
+     */
+    pub fn spawn(orm: Arc<dyn ORM>, exchange: Arc<dyn ExchangeFactory>) -> JoinHandle<()> {
+        let interval = std::env::var("SYMBOL_SYNC_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_SYNC_INTERVAL);
+        let symbol_ucase = SymbolUcase::new(SymbolRepo::new(orm), exchange);
+
+        tokio::spawn(async move {
+            //第一次tick立即觸發
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            loop {
+                ticker.tick().await;
+                match symbol_ucase.sync().await {
+                    Ok(report) => tracing::info!("symbol sync: {:?}", report),
+                    Err(e) => tracing::error!("symbol sync failed: {}", e),
+                }
+            }
+        })
+    }
+}
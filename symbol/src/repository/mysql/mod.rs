@@ -0,0 +1 @@
+pub mod symbol_repo;
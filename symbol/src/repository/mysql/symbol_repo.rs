@@ -0,0 +1,99 @@
+use crate::domain::{SymbolQuery, SymbolRepository, STATUS_CLOSED};
+use async_trait::async_trait;
+use entity::{prelude::*, symbols};
+use pkg::db::ORM;
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, OnConflict},
+    QueryOrder,
+};
+use std::sync::Arc;
+
+pub struct SymbolRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl SymbolRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn SymbolRepository> {
+        Arc::new(SymbolRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl SymbolRepository for SymbolRepo {
+    async fn list(&self, query: SymbolQuery) -> anyhow::Result<Vec<symbols::Model>> {
+        let db = self.mysql.get_db().await;
+        let mut select = Symbols::find();
+        if let Some(quote_currency) = query.quote_currency {
+            select = select.filter(symbols::Column::QuoteCurrency.eq(quote_currency));
+        }
+        if let Some(status) = query.status {
+            select = select.filter(symbols::Column::Status.eq(status));
+        }
+        let models = select.order_by_asc(symbols::Column::Name).all(db).await?;
+
+        Ok(models)
+    }
+
+    async fn get_by_name(&self, name: String) -> anyhow::Result<Option<symbols::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Symbols::find_by_id(name).one(db).await?;
+        Ok(model)
+    }
+
+    /**
+     * 新增或更新合約規格
+     */
+    async fn upsert(&self, actives: Vec<symbols::ActiveModel>) -> anyhow::Result<()> {
+        if actives.is_empty() {
+            return Ok(());
+        }
+
+        let db = self.mysql.get_db().await;
+        Symbols::insert_many(actives)
+            .on_conflict(
+                OnConflict::column(symbols::Column::Name)
+                    .update_columns([
+                        symbols::Column::Alias,
+                        symbols::Column::Status,
+                        symbols::Column::BaseCurrency,
+                        symbols::Column::QuoteCurrency,
+                        symbols::Column::PriceScale,
+                        symbols::Column::TakerFee,
+                        symbols::Column::MakerFee,
+                        symbols::Column::FundingInterval,
+                        symbols::Column::MaxTradingQty,
+                        symbols::Column::MinTradingQty,
+                        symbols::Column::QtyStep,
+                        symbols::Column::PostOnlyMaxTradingQty,
+                        symbols::Column::MinPrice,
+                        symbols::Column::MaxPrice,
+                        symbols::Column::TickSize,
+                        symbols::Column::MinLeverage,
+                        symbols::Column::MaxLeverage,
+                        symbols::Column::LeverageStep,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /**
+     * 交易所已不存在的合約標記為下架
+     */
+    async fn mark_delisted(&self, listed: Vec<String>) -> anyhow::Result<u64> {
+        let db = self.mysql.get_db().await;
+        let mut update = Symbols::update_many()
+            .col_expr(symbols::Column::Status, Expr::value(STATUS_CLOSED))
+            .filter(symbols::Column::Status.ne(STATUS_CLOSED));
+        if !listed.is_empty() {
+            update = update.filter(symbols::Column::Name.is_not_in(listed));
+        }
+        let res = update.exec(db).await?;
+
+        Ok(res.rows_affected)
+    }
+}
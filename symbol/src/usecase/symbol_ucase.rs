@@ -0,0 +1,152 @@
+use crate::domain::{
+    to_active, SymbolInfo, SymbolQuery, SymbolRepository, SymbolUsecase, SyncReport,
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use entity::symbols;
+use pkg::exchange::ExchangeFactory;
+use std::sync::Arc;
+
+pub struct SymbolUcase {
+    symbol_repo: Arc<dyn SymbolRepository>,
+    exchange: Arc<dyn ExchangeFactory>,
+}
+
+impl SymbolUcase {
+    pub fn new(
+        symbol_repo: Arc<dyn SymbolRepository>,
+        exchange: Arc<dyn ExchangeFactory>,
+    ) -> Arc<dyn SymbolUsecase> {
+        Arc::new(SymbolUcase {
+            symbol_repo,
+            exchange,
+        })
+    }
+}
+
+#[async_trait]
+impl SymbolUsecase for SymbolUcase {
+    /**
+     * 合約列表
+     */
+    async fn list(&self, query: SymbolQuery) -> anyhow::Result<Vec<SymbolInfo>> {
+        let models = self.symbol_repo.list(query).await?;
+        let list = models.into_iter().map(SymbolInfo::from).collect();
+        Ok(list)
+    }
+
+    async fn get_by_name(&self, name: String) -> anyhow::Result<Option<symbols::Model>> {
+        let res = self.symbol_repo.get_by_name(name).await?;
+        Ok(res)
+    }
+
+    /**
+     * 從交易所同步合約規格
+     */
+    async fn sync(&self) -> anyhow::Result<SyncReport> {
+        //公開api不需要key
+        let client = self.exchange.client("", "");
+        let infos = client.list_symbols().await?;
+        if infos.is_empty() {
+            //避免交易所異常回傳空列表時把所有合約標記為下架
+            return Err(anyhow!("exchange returned no symbols"));
+        }
+
+        let listed: Vec<String> = infos.iter().map(|info| info.name.clone()).collect();
+        let upserted = infos.len();
+        let actives = infos.into_iter().map(to_active).collect();
+        self.symbol_repo.upsert(actives).await?;
+        let delisted = self.symbol_repo.mark_delisted(listed).await?;
+
+        Ok(SyncReport { upserted, delisted })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::SymbolQuery;
+    use entity::symbols::{ActiveModel as SymbolActiveModel, Model as SymbolModel};
+    use pkg::exchange::{Exchange, OrderInfo, PlaceOrder, Position, SymbolInfo as ExchangeSymbol};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    //只回傳空的合約列表
+    struct EmptyExchange;
+
+    #[async_trait]
+    impl Exchange for EmptyExchange {
+        async fn place_order(&self, _req: PlaceOrder) -> anyhow::Result<OrderInfo> {
+            Err(anyhow!("not used"))
+        }
+        async fn cancel_order(&self, _symbol: &str, _link_id: &str) -> anyhow::Result<OrderInfo> {
+            Err(anyhow!("not used"))
+        }
+        async fn query_order(&self, _symbol: &str, _link_id: &str) -> anyhow::Result<OrderInfo> {
+            Err(anyhow!("not used"))
+        }
+        async fn set_leverage(&self, _symbol: &str, _buy: i16, _sell: i16) -> anyhow::Result<()> {
+            Err(anyhow!("not used"))
+        }
+        async fn switch_isolated(
+            &self,
+            _symbol: &str,
+            _is_isolated: bool,
+            _buy: i16,
+            _sell: i16,
+        ) -> anyhow::Result<()> {
+            Err(anyhow!("not used"))
+        }
+        async fn get_position(&self, _symbol: &str) -> anyhow::Result<Vec<Position>> {
+            Err(anyhow!("not used"))
+        }
+        async fn list_symbols(&self) -> anyhow::Result<Vec<ExchangeSymbol>> {
+            Ok(vec![])
+        }
+        async fn mark_price(&self, _symbol: &str) -> anyhow::Result<f64> {
+            Err(anyhow!("not used"))
+        }
+        async fn verify_credentials(&self) -> anyhow::Result<()> {
+            Err(anyhow!("not used"))
+        }
+    }
+
+    impl ExchangeFactory for EmptyExchange {
+        fn client(&self, _api_key: &str, _secret_key: &str) -> Arc<dyn Exchange> {
+            Arc::new(EmptyExchange)
+        }
+    }
+
+    //記錄是否有寫入
+    #[derive(Default)]
+    struct RecordingRepo {
+        written: AtomicBool,
+    }
+
+    #[async_trait]
+    impl SymbolRepository for RecordingRepo {
+        async fn list(&self, _query: SymbolQuery) -> anyhow::Result<Vec<SymbolModel>> {
+            Ok(vec![])
+        }
+        async fn get_by_name(&self, _name: String) -> anyhow::Result<Option<SymbolModel>> {
+            Ok(None)
+        }
+        async fn upsert(&self, _actives: Vec<SymbolActiveModel>) -> anyhow::Result<()> {
+            self.written.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        async fn mark_delisted(&self, _listed: Vec<String>) -> anyhow::Result<u64> {
+            self.written.store(true, Ordering::SeqCst);
+            Ok(0)
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_rejects_empty_list() {
+        let repo = Arc::new(RecordingRepo::default());
+        let ucase = SymbolUcase::new(repo.clone(), Arc::new(EmptyExchange));
+
+        assert!(ucase.sync().await.is_err());
+        //空列表時不可把所有合約標記為下架
+        assert!(!repo.written.load(Ordering::SeqCst));
+    }
+}
@@ -1,66 +1,188 @@
-use crate::domain::{AuthBody, AuthPayload, CreateUser, UserContainer, UserInfo};
-use axum::{extract::Extension, http::StatusCode, response::IntoResponse, Json};
+use crate::domain::{
+    ApiKeyInfo, AuthPayload, ChangePassword, CreateUser, LoginResponse, RefreshPayload, SetApiKey,
+    TotpCode, TotpLoginPayload, UpdateProfile, UpdateUser, UserContainer, UserInfo, UserQuery,
+    UserSort,
+};
+use axum::{
+    extract::{Extension, Path, Query, TypedHeader},
+    headers::UserAgent,
+    response::IntoResponse,
+};
 use bcrypt::{hash, verify, DEFAULT_COST};
+use entity::users::Model as UserModel;
+use once_cell::sync::Lazy;
 use pkg::{
+    error::{AppError, AppResult},
+    extract::{ClientIp, ValidatedJson},
     jwt::Claims,
-    responder::{failed, success, Detail, StatusCode as RespCode},
+    page::PageQuery,
+    responder::{ok, paged, Detail},
 };
 use std::sync::Arc;
 use validator::Validate;
 
+//帳號不存在時也做一次bcrypt比對, 讓回應時間與密碼錯誤相同, 避免帳號被列舉
+static DUMMY_HASH: Lazy<String> =
+    Lazy::new(|| hash("dummy-password-for-timing", DEFAULT_COST).unwrap());
+
+/**
+ * 取得登入者的user data
+ */
+async fn current_user(c: &UserContainer, account: String) -> AppResult<UserModel> {
+    c.user_ucase
+        .get_by_account(account)
+        .await?
+        .ok_or_else(|| AppError::not_found("User not found"))
+}
+
+/**
+ * 取得裝置資訊
+ */
+fn device_of(user_agent: Option<TypedHeader<UserAgent>>) -> String {
+    user_agent
+        .map(|TypedHeader(ua)| ua.to_string())
+        .unwrap_or_default()
+}
+
 /**
- * 登錄認證
+ * 登錄認證, 已啟用兩步驟驗證時只回傳mfa_token, 需再呼叫 /login/totp
  */
 pub async fn auth(
-    Json(payload): Json<AuthPayload>,
-    Extension(c): Extension<Arc<UserContainer>>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    //request validate
-    match payload.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            println!("{:#?}", e);
-            let (_, resp) = failed(RespCode::StatusBadReq, Detail("validate error".to_owned()));
-            let jsonv = serde_json::to_value(resp).unwrap();
-            return (StatusCode::BAD_REQUEST, Json(jsonv));
+    ValidatedJson(payload): ValidatedJson<AuthPayload>,
+    ClientIp(ip): ClientIp,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    //失敗次數過多, 等待時間內直接拒絕
+    c.login_throttle.check(&payload.account, ip).await?;
+
+    //取得user data, 帳號不存在與密碼錯誤回傳相同訊息
+    let user_data = c.user_ucase.get_by_account(payload.account.clone()).await?;
+
+    //驗證密碼
+    let valid = match &user_data {
+        Some(user_data) => {
+            verify(&payload.password, user_data.password.as_str()).unwrap_or_else(|e| {
+                tracing::error!("password hash of {} is invalid: {}", user_data.account, e);
+                false
+            })
+        }
+        None => {
+            let _ = verify(&payload.password, DUMMY_HASH.as_str());
+            false
+        }
+    };
+    let user_data = match user_data {
+        Some(user_data) if valid => user_data,
+        _ => {
+            c.login_throttle.failed(&payload.account, ip).await?;
+            return Err(AppError::validation("Password Verify error"));
         }
+    };
+
+    //兩步驟驗證, 通過後才發token
+    if c.totp_ucase.is_enabled(user_data.account.clone()).await? {
+        let challenge = c.totp_ucase.challenge(user_data.account).await?;
+        return Ok(ok(LoginResponse::Mfa(challenge)));
     }
+    c.login_throttle.succeeded(&payload.account).await?;
 
-    //取得user data
-    let user_data = c
-        .user_ucase
-        .get_by_account(payload.account.clone())
-        .await
-        .unwrap()
-        .unwrap();
+    //建立session並產生token
+    let body = c.user_ucase.login(user_data, device_of(user_agent)).await?;
 
-    //驗證密碼
-    let valid = verify(payload.password, user_data.password.as_str()).unwrap();
-    if !valid {
-        let (_, resp) = failed(
-            RespCode::StatusValidation,
-            Detail("Password Verify error".to_owned()),
-        );
-        let jsonv = serde_json::to_value(resp).unwrap();
-        return (StatusCode::BAD_REQUEST, Json(jsonv));
+    // Send the authorized token
+    Ok(ok(LoginResponse::Token(body)))
+}
+
+/**
+ * 登錄第二步: 驗證totp或recovery code後發token
+ */
+pub async fn auth_totp(
+    ValidatedJson(payload): ValidatedJson<TotpLoginPayload>,
+    ClientIp(ip): ClientIp,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let totp = c.totp_ucase.get_challenge(payload.mfa_token).await?;
+    let account = totp.user_account.clone();
+    c.login_throttle.check(&account, ip).await?;
+
+    if !c.totp_ucase.verify_code(&totp, payload.code).await? {
+        c.login_throttle.failed(&account, ip).await?;
+        return Err(AppError::validation("Invalid code"));
     }
+    c.totp_ucase.finish_challenge(totp).await?;
+    c.login_throttle.succeeded(&account).await?;
 
-    //產生jwt token
-    let token = c
+    let user_data = c
         .user_ucase
-        .gen_token(user_data.account.clone(), user_data.role)
-        .await
-        .unwrap();
+        .get_by_account(account)
+        .await?
+        .ok_or_else(|| AppError::unauthorized("Invalid mfa token"))?;
+    let body = c.user_ucase.login(user_data, device_of(user_agent)).await?;
 
-    c.user_ucase
-        .save_token(user_data, token.clone())
-        .await
-        .unwrap();
+    Ok(ok(body))
+}
 
-    // Send the authorized token
-    let (_, resp) = success(AuthBody::new(token));
-    let jsonv = serde_json::to_value(resp).unwrap();
-    (StatusCode::OK, Json(jsonv))
+/**
+ * 兩步驟驗證狀態
+ */
+pub async fn get_totp(
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let status = c.totp_ucase.status(claims.account).await?;
+
+    Ok(ok(status))
+}
+
+/**
+ * 開始綁定兩步驟驗證, 回傳secret與otpauth uri
+ */
+pub async fn enrol_totp(
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let enrolment = c.totp_ucase.enrol(claims.account).await?;
+
+    Ok(ok(enrolment))
+}
+
+/**
+ * 確認綁定, 回傳recovery codes
+ */
+pub async fn confirm_totp(
+    ValidatedJson(payload): ValidatedJson<TotpCode>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let codes = c.totp_ucase.confirm(claims.account, payload.code).await?;
+
+    Ok(ok(codes))
+}
+
+/**
+ * 以refresh token換發token
+ */
+pub async fn refresh(
+    ValidatedJson(payload): ValidatedJson<RefreshPayload>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let body = c.user_ucase.refresh(payload.refresh_token).await?;
+
+    Ok(ok(body))
+}
+
+/**
+ * 登出, 撤銷目前的session
+ */
+pub async fn logout(
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    c.user_ucase.logout(claims.sid).await?;
+
+    Ok(ok(Detail("Logged out".to_owned())))
 }
 
 /**
@@ -69,63 +191,283 @@ pub async fn auth(
 pub async fn get_info(
     claims: Claims,
     Extension(c): Extension<Arc<UserContainer>>,
-) -> impl IntoResponse {
-    let user_info = c.user_ucase.get_info(claims.account).await.unwrap();
+) -> AppResult<impl IntoResponse> {
+    let user_info = c.user_ucase.get_info(claims.account).await?;
 
-    let (_, resp) = success(user_info);
+    Ok(ok(user_info))
+}
+
+/**
+ * 更新個人資料
+ */
+pub async fn update_profile(
+    ValidatedJson(payload): ValidatedJson<UpdateProfile>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = current_user(&c, claims.account).await?;
+    let user_data = c.user_ucase.update_profile(user_data, payload).await?;
 
-    (StatusCode::OK, Json(resp))
+    Ok(ok(UserInfo::from(user_data)))
+}
+
+/**
+ * 變更密碼, 目前以外的session會被登出
+ */
+pub async fn change_password(
+    ValidatedJson(payload): ValidatedJson<ChangePassword>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = current_user(&c, claims.account).await?;
+    c.user_ucase
+        .change_password(user_data, claims.sid, payload)
+        .await?;
+
+    Ok(ok(Detail("Password changed".to_owned())))
 }
 
 /**
  * create user
  */
 pub async fn create_user(
-    Json(mut payload): Json<CreateUser>,
+    ValidatedJson(mut payload): ValidatedJson<CreateUser>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    //判斷使用者存在
+    if c.user_ucase.is_exist(payload.account.clone()).await? {
+        return Err(AppError::duplicate("Account already exist"));
+    }
+
+    //hash 密碼
+    payload.password =
+        hash(payload.password.clone(), DEFAULT_COST).map_err(|e| AppError::Internal(e.into()))?;
+
+    //存入DB
+    let user_data = c.user_ucase.create(payload).await?;
+
+    Ok(ok(UserInfo::from(user_data)))
+}
+
+/**
+ * 取得交易所api key (遮蔽)
+ */
+pub async fn get_api_key(
     claims: Claims,
     Extension(c): Extension<Arc<UserContainer>>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    //只有admin能新增用戶
-    if claims.role < 99 {
-        let (_, resp) = failed(
-            RespCode::StatusValidation,
-            Detail("Permission error".to_owned()),
-        );
-        let jsonv = serde_json::to_value(resp).unwrap();
+) -> AppResult<impl IntoResponse> {
+    let user_data = current_user(&c, claims.account).await?;
 
-        return (StatusCode::BAD_REQUEST, Json(serde_json::json!(jsonv)));
-    }
+    Ok(ok(ApiKeyInfo::from(&user_data)))
+}
+
+/**
+ * 設定或更換交易所api key, 先向交易所驗證後才儲存
+ */
+pub async fn set_api_key(
+    ValidatedJson(payload): ValidatedJson<SetApiKey>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = current_user(&c, claims.account).await?;
 
-    //request validate
-    match payload.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            println!("{:#?}", e);
-            let (_, resp) = failed(RespCode::StatusBadReq, Detail("validate error".to_owned()));
-            let jsonv = serde_json::to_value(resp).unwrap();
-            return (StatusCode::BAD_REQUEST, Json(jsonv));
+    //交易所api key可以動用資金, 已啟用兩步驟驗證時需再驗證一次
+    if let Some(totp) = c.totp_ucase.get_enabled(user_data.account.clone()).await? {
+        let code = payload
+            .totp_code
+            .clone()
+            .ok_or_else(|| AppError::validation("Two-factor code required"))?;
+        if !c.totp_ucase.verify_code(&totp, code).await? {
+            return Err(AppError::validation("Invalid code"));
         }
     }
 
-    //判斷使用者存在
-    let exist = c.user_ucase.is_exist(payload.account.clone()).await;
-    if exist {
-        let (_, resp) = failed(
-            RespCode::StatusDuplicate,
-            Detail(String::from("Account already exist")),
-        );
-        let jsonv = serde_json::to_value(resp).unwrap();
-        return (StatusCode::BAD_REQUEST, Json(serde_json::json!(jsonv)));
+    //交易所驗證
+    if let Err(e) = c.user_ucase.verify_api_key(&payload).await {
+        tracing::warn!("api key verify failed for {}: {}", user_data.account, e);
+        return Err(AppError::validation("Api key verify error"));
     }
 
-    //hash 密碼
-    let pwd = hash(payload.password.clone(), DEFAULT_COST).unwrap();
-    payload.password = pwd;
+    let info = c.user_ucase.set_api_key(user_data, payload).await?;
 
-    //存入DB
-    let user_data = c.user_ucase.create(payload).await.unwrap();
-    let (_, resp) = success(UserInfo::from(user_data));
+    Ok(ok(info))
+}
+
+/**
+ * 清除交易所api key
+ */
+pub async fn clear_api_key(
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = current_user(&c, claims.account).await?;
+    let info = c.user_ucase.clear_api_key(user_data).await?;
+
+    Ok(ok(info))
+}
+
+/**
+ * 驗證已儲存的交易所api key
+ */
+pub async fn verify_api_key(
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = current_user(&c, claims.account).await?;
+
+    if let Err(e) = c.user_ucase.verify_saved_api_key(&user_data).await {
+        tracing::warn!("api key verify failed for {}: {}", user_data.account, e);
+        return Err(AppError::validation("Api key verify error"));
+    }
+
+    Ok(ok(ApiKeyInfo::from(&user_data)))
+}
+
+/**
+ * 依帳號取得用戶(不含已刪除)
+ */
+async fn find_user(c: &UserContainer, account: String) -> AppResult<UserModel> {
+    c.user_ucase
+        .get_by_account(account)
+        .await?
+        .ok_or_else(|| AppError::not_found("User not found"))
+}
+
+/**
+ * admin不能停用/刪除/降級自己, 避免系統沒有可用的admin
+ */
+fn not_self(claims: &Claims, account: &str) -> AppResult<()> {
+    if claims.account == account {
+        return Err(AppError::forbidden("Cannot modify own account"));
+    }
+    Ok(())
+}
+
+/**
+ * 用戶列表 (admin)
+ */
+pub async fn list_users(
+    page: PageQuery<UserSort>,
+    Query(query): Query<UserQuery>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+
+    let list = c.user_ucase.list(query, page).await?;
+
+    Ok(paged(list))
+}
+
+/**
+ * 取得單一用戶 (admin)
+ */
+pub async fn get_user(
+    Path(account): Path<String>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = find_user(&c, account).await?;
+
+    Ok(ok(UserInfo::from(user_data)))
+}
+
+/**
+ * 更新用戶 (admin)
+ */
+pub async fn update_user(
+    Path(account): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateUser>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    if payload.role.is_some() || payload.state.is_some() {
+        not_self(&claims, &account)?;
+    }
+
+    let user_data = find_user(&c, account).await?;
+    let user_data = c.user_ucase.update(user_data, payload).await?;
+
+    Ok(ok(UserInfo::from(user_data)))
+}
+
+/**
+ * 啟用用戶 (admin)
+ */
+pub async fn enable_user(
+    Path(account): Path<String>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    set_state(account, 1, claims, c).await
+}
+
+/**
+ * 停用用戶 (admin), 該用戶既有的token會立即失效
+ */
+pub async fn disable_user(
+    Path(account): Path<String>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    set_state(account, 0, claims, c).await
+}
+
+async fn set_state(
+    account: String,
+    state: i8,
+    claims: Claims,
+    c: Arc<UserContainer>,
+) -> AppResult<impl IntoResponse> {
+    not_self(&claims, &account)?;
+
+    let user_data = find_user(&c, account).await?;
+    let user_data = c.user_ucase.set_state(user_data, state).await?;
+
+    Ok(ok(UserInfo::from(user_data)))
+}
+
+/**
+ * 軟刪除用戶 (admin)
+ */
+pub async fn delete_user(
+    Path(account): Path<String>,
+    claims: Claims,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    not_self(&claims, &account)?;
+
+    let user_data = find_user(&c, account).await?;
+    c.user_ucase.delete(user_data).await?;
+
+    Ok(ok(Detail("User deleted".to_owned())))
+}
+
+/**
+ * 還原已刪除的用戶 (admin)
+ */
+pub async fn restore_user(
+    Path(account): Path<String>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    let user_data = c
+        .user_ucase
+        .get_deleted(account)
+        .await?
+        .ok_or_else(|| AppError::not_found("User not found"))?;
+    let user_data = c.user_ucase.restore(user_data).await?;
+
+    Ok(ok(UserInfo::from(user_data)))
+}
+
+/**
+ * 重設用戶的兩步驟驗證 (admin)
+ */
+pub async fn reset_totp(
+    Path(account): Path<String>,
+    Extension(c): Extension<Arc<UserContainer>>,
+) -> AppResult<impl IntoResponse> {
+    if !c.totp_ucase.reset(account).await? {
+        return Err(AppError::not_found("Two-factor enrolment not found"));
+    }
 
-    let jsonv = serde_json::to_value(resp).unwrap();
-    (StatusCode::OK, Json(serde_json::json!(jsonv)))
+    Ok(ok(Detail("Two-factor authentication reset".to_owned())))
 }
@@ -1 +1 @@
-pub mod http;
\ No newline at end of file
+pub mod http;
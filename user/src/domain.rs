@@ -2,12 +2,24 @@
 // use sea_orm::{DatabaseConnection, DbErr};
 use anyhow::Result;
 use axum::async_trait;
-use entity::users::{ActiveModel as UserActiveModel, Model as UserModel};
-use pkg::responder::Data;
+use chrono::{DateTime, Duration, Local};
+use entity::{
+    refresh_tokens::{ActiveModel as RefreshTokenActiveModel, Model as RefreshTokenModel},
+    user_sessions::{ActiveModel as SessionActiveModel, Model as SessionModel},
+    user_totps::{ActiveModel as TotpActiveModel, Model as TotpModel},
+    users::{self, ActiveModel as UserActiveModel, Model as UserModel},
+};
+use pkg::{
+    crypto::mask,
+    page::{Page, PageQuery, SortField},
+    rbac::Role,
+    responder::Data,
+};
 use serde::{Deserialize, Serialize};
 use std::convert::From;
+use std::net::IpAddr;
 use std::sync::Arc;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 /**
  * Traits
@@ -15,9 +27,79 @@ use validator::Validate;
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn get_by_account(&self, account: String) -> Result<Option<UserModel>>;
+    async fn get_deleted(&self, account: String) -> Result<Option<UserModel>>;
+    async fn find_any(&self, account: String) -> Result<Option<UserModel>>;
+    async fn list(&self, query: UserQuery, page: PageQuery<UserSort>) -> Result<Page<UserModel>>;
     async fn save_token(&self, model: UserModel, token: String) -> Result<UserModel>;
-    async fn is_exist(&self, account: String) -> bool;
+    async fn is_exist(&self, account: String) -> Result<bool>;
     async fn create(&self, active: UserActiveModel) -> Result<UserModel>;
+    async fn update(&self, active: UserActiveModel) -> Result<UserModel>;
+    async fn recent_passwords(&self, account: String, limit: u64) -> Result<Vec<String>>;
+    async fn change_password(&self, model: UserModel, password: String) -> Result<UserModel>;
+    async fn save_api_key(
+        &self,
+        model: UserModel,
+        api_key: String,
+        secret_key: String,
+    ) -> Result<UserModel>;
+}
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn create_session(&self, active: SessionActiveModel) -> Result<SessionModel>;
+    async fn get_session(&self, sid: String) -> Result<Option<SessionModel>>;
+    async fn revoke_session(&self, sid: String) -> Result<()>;
+    async fn revoke_other_sessions(&self, account: String, keep_sid: String) -> Result<u64>;
+    async fn create_refresh_token(&self, active: RefreshTokenActiveModel) -> Result<()>;
+    async fn get_refresh_token(&self, token_hash: String) -> Result<Option<RefreshTokenModel>>;
+    async fn use_refresh_token(&self, id: i64) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+    async fn get(&self, key: String) -> Result<Option<LoginAttempt>>;
+    /**
+     * 原子地累加一次失敗並回傳新紀錄, 同時多個請求也不會少算
+     */
+    async fn fail(
+        &self,
+        key: String,
+        policy: &ThrottlePolicy,
+        now: DateTime<Local>,
+    ) -> Result<LoginAttempt>;
+    async fn reset(&self, key: String) -> Result<()>;
+}
+
+#[async_trait]
+pub trait LoginThrottleUsecase: Send + Sync {
+    async fn check(&self, account: &str, ip: IpAddr) -> Result<()>;
+    async fn failed(&self, account: &str, ip: IpAddr) -> Result<()>;
+    async fn succeeded(&self, account: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait TotpRepository: Send + Sync {
+    async fn get(&self, account: String) -> Result<Option<TotpModel>>;
+    async fn get_by_challenge(&self, challenge_hash: String) -> Result<Option<TotpModel>>;
+    async fn create(&self, active: TotpActiveModel) -> Result<TotpModel>;
+    async fn update(&self, active: TotpActiveModel) -> Result<TotpModel>;
+    async fn use_step(&self, id: i64, step: i64) -> Result<bool>;
+    async fn use_recovery_code(&self, id: i64, current: String, remaining: String) -> Result<bool>;
+    async fn delete(&self, account: String) -> Result<u64>;
+}
+
+#[async_trait]
+pub trait TotpUsecase: Send + Sync {
+    async fn status(&self, account: String) -> Result<TotpStatus>;
+    async fn is_enabled(&self, account: String) -> Result<bool>;
+    async fn get_enabled(&self, account: String) -> Result<Option<TotpModel>>;
+    async fn enrol(&self, account: String) -> Result<TotpEnrolment>;
+    async fn confirm(&self, account: String, code: String) -> Result<RecoveryCodes>;
+    async fn challenge(&self, account: String) -> Result<MfaChallenge>;
+    async fn get_challenge(&self, token: String) -> Result<TotpModel>;
+    async fn finish_challenge(&self, model: TotpModel) -> Result<()>;
+    async fn verify_code(&self, model: &TotpModel, code: String) -> Result<bool>;
+    async fn reset(&self, account: String) -> Result<bool>;
 }
 
 #[async_trait]
@@ -25,9 +107,30 @@ pub trait UserUsecase: Send + Sync {
     async fn get_by_account(&self, account: String) -> Result<Option<UserModel>>;
     async fn save_token(&self, model: UserModel, token: String) -> Result<UserModel>;
     async fn get_info(&self, account: String) -> Result<UserInfo>;
-    async fn is_exist(&self, account: String) -> bool;
-    async fn gen_token(&self, account: String, role: i8) -> Result<String>;
+    async fn is_exist(&self, account: String) -> Result<bool>;
+    async fn gen_token(&self, account: String, role: i8, sid: String) -> Result<String>;
+    async fn login(&self, model: UserModel, device: String) -> Result<AuthBody>;
+    async fn refresh(&self, refresh_token: String) -> Result<AuthBody>;
+    async fn logout(&self, sid: String) -> Result<()>;
     async fn create(&self, body: CreateUser) -> Result<UserModel>;
+    async fn verify_api_key(&self, body: &SetApiKey) -> Result<()>;
+    async fn verify_saved_api_key(&self, model: &UserModel) -> Result<()>;
+    async fn set_api_key(&self, model: UserModel, body: SetApiKey) -> Result<ApiKeyInfo>;
+    async fn clear_api_key(&self, model: UserModel) -> Result<ApiKeyInfo>;
+    async fn ensure_admin(&self, account: String, password: String) -> Result<AdminBootstrap>;
+    async fn list(&self, query: UserQuery, page: PageQuery<UserSort>) -> Result<Page<UserInfo>>;
+    async fn get_deleted(&self, account: String) -> Result<Option<UserModel>>;
+    async fn update(&self, model: UserModel, body: UpdateUser) -> Result<UserModel>;
+    async fn set_state(&self, model: UserModel, state: i8) -> Result<UserModel>;
+    async fn delete(&self, model: UserModel) -> Result<UserModel>;
+    async fn restore(&self, model: UserModel) -> Result<UserModel>;
+    async fn update_profile(&self, model: UserModel, body: UpdateProfile) -> Result<UserModel>;
+    async fn change_password(
+        &self,
+        model: UserModel,
+        sid: String,
+        body: ChangePassword,
+    ) -> Result<()>;
 }
 
 /**
@@ -35,11 +138,21 @@ pub trait UserUsecase: Send + Sync {
  */
 pub struct UserContainer {
     pub user_ucase: Arc<dyn UserUsecase>,
+    pub login_throttle: Arc<dyn LoginThrottleUsecase>,
+    pub totp_ucase: Arc<dyn TotpUsecase>,
 }
 
 impl UserContainer {
-    pub fn new(user_ucase: Arc<dyn UserUsecase>) -> Arc<UserContainer> {
-        Arc::new(UserContainer { user_ucase })
+    pub fn new(
+        user_ucase: Arc<dyn UserUsecase>,
+        login_throttle: Arc<dyn LoginThrottleUsecase>,
+        totp_ucase: Arc<dyn TotpUsecase>,
+    ) -> Arc<UserContainer> {
+        Arc::new(UserContainer {
+            user_ucase,
+            login_throttle,
+            totp_ucase,
+        })
     }
 }
 
@@ -54,10 +167,69 @@ pub struct CreateUser {
     pub password: String,
     #[validate(length(min = 1, max = 30))]
     pub name: String,
-    #[validate(range(min = 1, max = 99))]
+    #[validate(custom = "validate_role")]
     pub role: i8,
 }
 
+/**
+ * Update user request (admin)
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct UpdateUser {
+    #[validate(length(min = 1, max = 30))]
+    pub name: Option<String>,
+    #[validate(custom = "validate_role")]
+    pub role: Option<i8>,
+    #[validate(range(min = 0, max = 1))]
+    pub state: Option<i8>,
+}
+
+/**
+ * 用戶列表查詢條件, 分頁與排序由PageQuery處理
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct UserQuery {
+    pub role: Option<i8>,
+    #[validate(range(min = 0, max = 1))]
+    pub state: Option<i8>,
+    #[validate(length(min = 1, max = 30))]
+    pub name: Option<String>,
+}
+
+/**
+ * 用戶列表可排序的欄位
+ */
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSort {
+    #[default]
+    Id,
+    Account,
+    Name,
+    CreatedAt,
+}
+
+impl SortField<users::Entity> for UserSort {
+    fn column(&self) -> users::Column {
+        match self {
+            UserSort::Id => users::Column::Id,
+            UserSort::Account => users::Column::Account,
+            UserSort::Name => users::Column::Name,
+            UserSort::CreatedAt => users::Column::CreatedAt,
+        }
+    }
+}
+
+/**
+ * 角色必須是已定義的Role
+ */
+fn validate_role(role: i8) -> Result<(), ValidationError> {
+    match Role::from_i8(role) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("role")),
+    }
+}
+
 /**
  * User info
  */
@@ -120,16 +292,446 @@ pub struct AuthPayload {
 pub struct AuthBody {
     pub access_token: String,
     pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
 }
 
 impl Data for AuthBody {}
 
 impl AuthBody {
     //generate data
-    pub fn new(access_token: String) -> Self {
+    pub fn new(access_token: String, expires_in: i64, refresh_token: String) -> Self {
         Self {
             access_token,
             token_type: String::from("Bearer"),
+            expires_in,
+            refresh_token,
         }
     }
 }
+
+/**
+ * 已啟用兩步驟驗證時, 登入第一步回傳的challenge
+ */
+#[derive(Serialize)]
+pub struct MfaChallenge {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: i64,
+}
+
+impl Data for MfaChallenge {}
+
+/**
+ * 登入結果: 直接發token, 或需要再驗證totp
+ */
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Token(AuthBody),
+    Mfa(MfaChallenge),
+}
+
+impl Data for LoginResponse {}
+
+/**
+ * 登入第二步 request, code可為totp或recovery code
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct TotpLoginPayload {
+    #[validate(length(min = 1, max = 128))]
+    pub mfa_token: String,
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+/**
+ * Totp code request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct TotpCode {
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+/**
+ * 兩步驟驗證狀態
+ */
+#[derive(Serialize)]
+pub struct TotpStatus {
+    pub enabled: bool,
+    pub pending: bool,
+    pub recovery_codes_left: usize,
+}
+
+impl Data for TotpStatus {}
+
+impl From<Option<&TotpModel>> for TotpStatus {
+    fn from(model: Option<&TotpModel>) -> Self {
+        match model {
+            Some(model) => TotpStatus {
+                enabled: model.confirmed_at.is_some(),
+                pending: model.confirmed_at.is_none(),
+                recovery_codes_left: recovery_code_hashes(&model.recovery_codes).len(),
+            },
+            None => TotpStatus {
+                enabled: false,
+                pending: false,
+                recovery_codes_left: 0,
+            },
+        }
+    }
+}
+
+/**
+ * 綁定用的secret與otpauth uri, 只在綁定時回傳一次
+ */
+#[derive(Serialize)]
+pub struct TotpEnrolment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+impl Data for TotpEnrolment {}
+
+/**
+ * recovery codes, 只在綁定完成時回傳一次
+ */
+#[derive(Serialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+impl Data for RecoveryCodes {}
+
+/**
+ * 取出保存的recovery code hash
+ */
+pub fn recovery_code_hashes(stored: &str) -> Vec<&str> {
+    stored.split(',').filter(|h| !h.is_empty()).collect()
+}
+
+/**
+ * recovery code正規化, 忽略大小寫與分隔符號
+ */
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/**
+ * Refresh token request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct RefreshPayload {
+    #[validate(length(min = 1, max = 128))]
+    pub refresh_token: String,
+}
+
+//access token預設15分鐘
+const DEFAULT_ACCESS_TOKEN_TTL: i64 = 900;
+//refresh token預設30天
+const DEFAULT_REFRESH_TOKEN_TTL: i64 = 2_592_000;
+
+/**
+ * access token有效秒數, 由ACCESS_TOKEN_TTL設定
+ */
+pub fn access_token_ttl() -> i64 {
+    std::env::var("ACCESS_TOKEN_TTL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_ACCESS_TOKEN_TTL)
+}
+
+/**
+ * refresh token有效秒數, 由REFRESH_TOKEN_TTL設定
+ */
+pub fn refresh_token_ttl() -> i64 {
+    std::env::var("REFRESH_TOKEN_TTL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REFRESH_TOKEN_TTL)
+}
+
+//預設不可重複使用最近5組密碼(含目前密碼)
+const DEFAULT_PASSWORD_HISTORY_SIZE: u64 = 5;
+
+/**
+ * 不可重複使用的最近密碼數量, 由PASSWORD_HISTORY_SIZE設定
+ */
+pub fn password_history_size() -> u64 {
+    std::env::var("PASSWORD_HISTORY_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_PASSWORD_HISTORY_SIZE)
+}
+
+/**
+ * 登入失敗紀錄
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginAttempt {
+    pub failures: u32,
+    pub blocked_until: Option<DateTime<Local>>,
+    pub last_failed_at: DateTime<Local>,
+}
+
+/**
+ * 登入失敗的限制規則
+ * 每次失敗後須等待 2^(失敗次數-1) 秒, 達到max_failures後鎖定lockout秒
+ * 超過lockout秒沒有再失敗則重新計數
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottlePolicy {
+    pub max_failures: u32,
+    pub lockout: i64,
+}
+
+//預設同帳號失敗5次鎖定
+const DEFAULT_LOGIN_MAX_FAILURES: u32 = 5;
+//預設同ip失敗20次鎖定
+const DEFAULT_LOGIN_IP_MAX_FAILURES: u32 = 20;
+//預設鎖定15分鐘
+const DEFAULT_LOGIN_LOCKOUT_SECONDS: i64 = 900;
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+impl ThrottlePolicy {
+    /**
+     * 帳號規則, 由LOGIN_MAX_FAILURES / LOGIN_LOCKOUT_SECONDS設定
+     */
+    pub fn account_from_env() -> Self {
+        ThrottlePolicy {
+            max_failures: env_or("LOGIN_MAX_FAILURES", DEFAULT_LOGIN_MAX_FAILURES),
+            lockout: env_or("LOGIN_LOCKOUT_SECONDS", DEFAULT_LOGIN_LOCKOUT_SECONDS),
+        }
+    }
+
+    /**
+     * ip規則, 由LOGIN_IP_MAX_FAILURES / LOGIN_LOCKOUT_SECONDS設定
+     */
+    pub fn ip_from_env() -> Self {
+        ThrottlePolicy {
+            max_failures: env_or("LOGIN_IP_MAX_FAILURES", DEFAULT_LOGIN_IP_MAX_FAILURES),
+            lockout: env_or("LOGIN_LOCKOUT_SECONDS", DEFAULT_LOGIN_LOCKOUT_SECONDS),
+        }
+    }
+
+    /**
+     * 依前一次的紀錄計算失敗後的新紀錄
+     */
+    pub fn fail(&self, prev: Option<LoginAttempt>, now: DateTime<Local>) -> LoginAttempt {
+        let failures = match prev {
+            Some(prev) if prev.last_failed_at > self.window_start(now) => {
+                prev.failures.saturating_add(1)
+            }
+            _ => 1,
+        };
+
+        LoginAttempt {
+            failures,
+            blocked_until: Some(self.blocked_until(failures, now)),
+            last_failed_at: now,
+        }
+    }
+
+    /**
+     * 最後失敗時間早於此時間則重新計數
+     */
+    pub fn window_start(&self, now: DateTime<Local>) -> DateTime<Local> {
+        now - Duration::seconds(self.lockout)
+    }
+
+    /**
+     * 失敗failures次後的等待期限, 每次加倍, 達上限後鎖定lockout秒
+     */
+    pub fn blocked_until(&self, failures: u32, now: DateTime<Local>) -> DateTime<Local> {
+        let wait = if failures >= self.max_failures {
+            self.lockout
+        } else {
+            (1i64 << failures.saturating_sub(1).min(30)).min(self.lockout)
+        };
+        now + Duration::seconds(wait)
+    }
+
+    /**
+     * 還需等待的秒數, 不需等待時為None
+     */
+    pub fn retry_after(attempt: &LoginAttempt, now: DateTime<Local>) -> Option<i64> {
+        match attempt.blocked_until {
+            Some(until) if until > now => {
+                let millis = (until - now).num_milliseconds();
+                Some((millis + 999) / 1000)
+            }
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Update profile request
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct UpdateProfile {
+    #[validate(length(min = 1, max = 30))]
+    pub name: Option<String>,
+}
+
+/**
+ * Change password request, 長度規則同CreateUser
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct ChangePassword {
+    #[validate(length(min = 6, max = 50))]
+    pub current_password: String,
+    #[validate(length(min = 6, max = 50))]
+    pub new_password: String,
+}
+
+/**
+ * Set exchange api key request, 已啟用兩步驟驗證時需帶totp或recovery code
+ */
+#[derive(Deserialize, Validate, Debug)]
+pub struct SetApiKey {
+    #[validate(length(min = 1, max = 100))]
+    pub api_key: String,
+    #[validate(length(min = 1, max = 100))]
+    pub secret_key: String,
+    #[validate(length(min = 6, max = 32))]
+    pub totp_code: Option<String>,
+}
+
+/**
+ * Exchange api key info, secret不回傳, api key只顯示遮蔽後的值
+ */
+#[derive(Serialize)]
+pub struct ApiKeyInfo {
+    pub api_key: String,
+    pub configured: bool,
+}
+
+impl Data for ApiKeyInfo {}
+
+impl From<&UserModel> for ApiKeyInfo {
+    fn from(model: &UserModel) -> Self {
+        ApiKeyInfo {
+            api_key: mask(&model.api_key),
+            configured: !model.api_key.is_empty() && !model.secret_key.is_empty(),
+        }
+    }
+}
+
+/**
+ * admin初始化結果
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminBootstrap {
+    Created,
+    Reset,
+    Unchanged,
+}
+
+//密碼最短長度
+const MIN_PASSWORD_LEN: usize = 12;
+
+//常見的弱密碼
+const COMMON_PASSWORDS: [&str; 9] = [
+    "password",
+    "adminpassword",
+    "administrator",
+    "123456789012",
+    "qwertyuiop",
+    "changeme",
+    "letmein",
+    "welcome",
+    //舊版env.example的範例值
+    "ch4nge-this-secret",
+];
+
+/**
+ * 檢查密碼強度: 長度至少12, 至少包含大寫/小寫/數字/符號其中三種,
+ * 不可包含帳號或常見密碼
+ */
+pub fn check_password_strength(account: &str, password: &str) -> Result<(), &'static str> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err("Password must be at least 12 characters");
+    }
+
+    let classes = [
+        password.chars().any(|c| c.is_ascii_lowercase()),
+        password.chars().any(|c| c.is_ascii_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_ascii_alphanumeric()),
+    ];
+    if classes.iter().filter(|&&has| has).count() < 3 {
+        return Err("Password must mix upper, lower case letters, digits or symbols");
+    }
+
+    let lower = password.to_lowercase();
+    if !account.is_empty() && lower.contains(&account.to_lowercase()) {
+        return Err("Password must not contain the account");
+    }
+    if COMMON_PASSWORDS.iter().any(|common| lower.contains(common)) {
+        return Err("Password is too common");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_backoff_and_lockout() {
+        let policy = ThrottlePolicy {
+            max_failures: 3,
+            lockout: 600,
+        };
+        let now = Local::now();
+
+        let first = policy.fail(None, now);
+        assert_eq!(first.failures, 1);
+        assert_eq!(ThrottlePolicy::retry_after(&first, now), Some(1));
+
+        let second = policy.fail(Some(first), now);
+        assert_eq!(ThrottlePolicy::retry_after(&second, now), Some(2));
+
+        let third = policy.fail(Some(second), now);
+        assert_eq!(third.failures, 3);
+        assert_eq!(ThrottlePolicy::retry_after(&third, now), Some(600));
+        assert_eq!(
+            ThrottlePolicy::retry_after(&third, now + Duration::seconds(600)),
+            None
+        );
+
+        //超過lockout後重新計數
+        let later = policy.fail(Some(third), now + Duration::seconds(601));
+        assert_eq!(later.failures, 1);
+    }
+
+    #[test]
+    fn recovery_codes() {
+        assert_eq!(normalize_recovery_code(" AB12-cd34 "), "ab12cd34");
+        assert_eq!(recovery_code_hashes("a,b,"), vec!["a", "b"]);
+        assert!(recovery_code_hashes("").is_empty());
+    }
+
+    #[test]
+    fn password_strength() {
+        assert!(check_password_strength("admin", "Sh0rt!").is_err());
+        assert!(check_password_strength("admin", "alllowercaseletters").is_err());
+        assert!(check_password_strength("admin", "MyAdmin-Pass-2022").is_err());
+        assert!(check_password_strength("root", "Password-2022!").is_err());
+        assert!(check_password_strength("admin", "Tr0ub4dor&3-horse").is_ok());
+        //公開過的範例密碼不可使用
+        assert!(check_password_strength("admin", "Ch4nge-This-Secret!").is_err());
+    }
+}
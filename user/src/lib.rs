@@ -5,34 +5,153 @@ pub mod usecase;
 
 pub mod router {
     use crate::{
-        delivery::http::handler::{auth, create_user, get_info},
-        domain::UserContainer,
-        repository::mysql::user_repo::UserRepo,
-        usecase::user_ucase::UserUcase,
+        delivery::http::handler::{
+            auth, auth_totp, change_password, clear_api_key, confirm_totp, create_user,
+            delete_user, disable_user, enable_user, enrol_totp, get_api_key, get_info, get_totp,
+            get_user, list_users, logout, refresh, reset_totp, restore_user, set_api_key,
+            update_profile, update_user, verify_api_key,
+        },
+        domain::{LoginAttemptRepository, ThrottlePolicy, UserContainer},
+        repository::{
+            memory::login_attempt_repo::LoginAttemptRepo as MemoryLoginAttemptRepo,
+            mysql::{
+                login_attempt_repo::LoginAttemptRepo, session_repo::SessionRepo,
+                totp_repo::TotpRepo, user_repo::UserRepo,
+            },
+        },
+        usecase::{
+            login_throttle_ucase::LoginThrottleUcase, totp_ucase::TotpUcase, user_ucase::UserUcase,
+        },
     };
     use axum::{
         extract::Extension,
-        routing::{get, post},
+        middleware::from_extractor,
+        routing::{delete, get, post},
         Router,
     };
 
-    use pkg::db::ORM;
+    use pkg::{
+        db::ORM,
+        exchange::ExchangeFactory,
+        rbac::{perm, RequirePermission},
+    };
     use std::sync::Arc;
 
+    /**
+     * 登入失敗計數的store, LOGIN_ATTEMPT_STORE=mysql時多個instance共用, 預設memory
+     */
+    fn login_attempt_repo(orm: Arc<dyn ORM>) -> Arc<dyn LoginAttemptRepository> {
+        match std::env::var("LOGIN_ATTEMPT_STORE").as_deref() {
+            Ok("mysql") => LoginAttemptRepo::new(orm),
+            _ => MemoryLoginAttemptRepo::new(),
+        }
+    }
+
     /**
      * new handler
      */
-    pub fn new(orm: Arc<dyn ORM>) -> Router {
-        let user_repo = UserRepo::new(orm);
-        let user_ucase = UserUcase::new(user_repo);
-        let user_container = UserContainer::new(user_ucase);
+    pub fn new(orm: Arc<dyn ORM>, exchange: Arc<dyn ExchangeFactory>) -> Router {
+        let user_repo = UserRepo::new(orm.clone());
+        let session_repo = SessionRepo::new(orm.clone());
+        let user_ucase = UserUcase::new(user_repo, session_repo, exchange);
+        let login_throttle = LoginThrottleUcase::new(
+            login_attempt_repo(orm.clone()),
+            ThrottlePolicy::account_from_env(),
+            ThrottlePolicy::ip_from_env(),
+        );
+        let totp_ucase = TotpUcase::new(TotpRepo::new(orm.clone()));
+        let user_container = UserContainer::new(user_ucase, login_throttle, totp_ucase);
+
+        //需要 users:create 權限
+        let admin_router = Router::new()
+            .route("/", post(create_user))
+            .route_layer(from_extractor::<RequirePermission<perm::UsersCreate>>());
 
         let user_router = Router::new()
             .route("/login", post(auth))
-            .route("/", get(get_info).post(create_user));
+            .route("/login/totp", post(auth_totp))
+            .route("/refresh", post(refresh))
+            .route("/logout", post(logout))
+            .route("/", get(get_info).patch(update_profile))
+            .route("/password", post(change_password))
+            .route(
+                "/api-key",
+                get(get_api_key).put(set_api_key).delete(clear_api_key),
+            )
+            .route("/api-key/verify", post(verify_api_key))
+            .route("/totp", get(get_totp).post(enrol_totp))
+            .route("/totp/confirm", post(confirm_totp))
+            .merge(admin_router);
+
+        //用戶管理, 需要 users:manage 權限
+        let users_router = Router::new()
+            .route("/", get(list_users))
+            .route(
+                "/:account",
+                get(get_user).put(update_user).delete(delete_user),
+            )
+            .route("/:account/enable", post(enable_user))
+            .route("/:account/disable", post(disable_user))
+            .route("/:account/restore", post(restore_user))
+            .route("/:account/totp", delete(reset_totp))
+            .route_layer(from_extractor::<RequirePermission<perm::UsersManage>>());
 
         Router::new()
             .nest("/v1/user", user_router)
+            .nest("/v1/users", users_router)
             .layer(Extension(user_container))
     }
 }
+
+pub mod session {
+    use crate::repository::mysql::session_repo::SessionRepo;
+    use pkg::{db::ORM, jwt::SessionStore};
+    use std::sync::Arc;
+
+    /**
+     * Claims extractor用的session store, 需以Extension掛在router上
+     */
+    pub fn store(orm: Arc<dyn ORM>) -> Arc<dyn SessionStore> {
+        SessionRepo::store(orm)
+    }
+}
+
+pub mod bootstrap {
+    use crate::{
+        domain::AdminBootstrap,
+        repository::mysql::{session_repo::SessionRepo, user_repo::UserRepo},
+        usecase::user_ucase::UserUcase,
+    };
+    use anyhow::{anyhow, Result};
+    use pkg::{db::ORM, exchange::ExchangeFactory};
+    use std::sync::Arc;
+
+    /**
+     * 依ADMIN_ACCOUNT / ADMIN_PASS建立或重設admin, 未設定時回傳None
+     * 密碼只會寫入hash, 不會出現在log
+     */
+    pub async fn admin(
+        orm: Arc<dyn ORM>,
+        exchange: Arc<dyn ExchangeFactory>,
+    ) -> Result<Option<AdminBootstrap>> {
+        let account = std::env::var("ADMIN_ACCOUNT").unwrap_or_default();
+        let password = std::env::var("ADMIN_PASS").unwrap_or_default();
+        if account.is_empty() && password.is_empty() {
+            return Ok(None);
+        }
+        if account.is_empty() || password.is_empty() {
+            return Err(anyhow!("ADMIN_ACCOUNT and ADMIN_PASS must both be set"));
+        }
+
+        let user_ucase =
+            UserUcase::new(UserRepo::new(orm.clone()), SessionRepo::new(orm), exchange);
+        let res = user_ucase.ensure_admin(account.clone(), password).await?;
+        match res {
+            AdminBootstrap::Created => tracing::info!("admin account {} created", account),
+            AdminBootstrap::Reset => tracing::info!("admin account {} reset", account),
+            AdminBootstrap::Unchanged => tracing::info!("admin account {} up to date", account),
+        }
+
+        Ok(Some(res))
+    }
+}
@@ -0,0 +1,84 @@
+use crate::domain::{LoginAttempt, LoginAttemptRepository, ThrottlePolicy};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Local};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+//超過此數量時清掉一天前的紀錄, 避免大量不存在的帳號塞爆記憶體
+const MAX_ENTRIES: usize = 10_000;
+
+/**
+ * 單一instance使用的記憶體store, 重啟後紀錄會清空
+ */
+pub struct LoginAttemptRepo {
+    attempts: Mutex<HashMap<String, LoginAttempt>>,
+}
+
+impl LoginAttemptRepo {
+    pub fn new() -> Arc<dyn LoginAttemptRepository> {
+        Arc::new(LoginAttemptRepo {
+            attempts: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptRepo {
+    async fn get(&self, key: String) -> anyhow::Result<Option<LoginAttempt>> {
+        let attempts = self.attempts.lock().unwrap();
+        Ok(attempts.get(&key).cloned())
+    }
+
+    async fn fail(
+        &self,
+        key: String,
+        policy: &ThrottlePolicy,
+        now: DateTime<Local>,
+    ) -> anyhow::Result<LoginAttempt> {
+        //讀取與寫入都在同一個lock內
+        let mut attempts = self.attempts.lock().unwrap();
+        if attempts.len() >= MAX_ENTRIES {
+            let expired = now - Duration::days(1);
+            attempts.retain(|_, a| {
+                a.last_failed_at > expired || matches!(a.blocked_until, Some(t) if t > expired)
+            });
+        }
+        let attempt = policy.fail(attempts.get(&key).cloned(), now);
+        attempts.insert(key, attempt.clone());
+        Ok(attempt)
+    }
+
+    async fn reset(&self, key: String) -> anyhow::Result<()> {
+        let mut attempts = self.attempts.lock().unwrap();
+        attempts.remove(&key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_failures_are_counted() {
+        let repo: Arc<dyn LoginAttemptRepository> = LoginAttemptRepo::new();
+        let policy = ThrottlePolicy {
+            max_failures: 5,
+            lockout: 600,
+        };
+        let now = Local::now();
+
+        let tasks: Vec<_> = (0..20)
+            .map(|_| {
+                let repo = repo.clone();
+                tokio::spawn(async move { repo.fail("ip:127.0.0.1".into(), &policy, now).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let attempt = repo.get("ip:127.0.0.1".into()).await.unwrap().unwrap();
+        assert_eq!(attempt.failures, 20);
+    }
+}
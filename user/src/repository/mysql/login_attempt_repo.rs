@@ -0,0 +1,100 @@
+use crate::domain::{LoginAttempt, LoginAttemptRepository, ThrottlePolicy};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use entity::{login_attempts, prelude::*};
+use pkg::db::ORM;
+use sea_orm::{
+    prelude::*, sea_query::Expr, ConnectionTrait, DbBackend, Statement, TransactionTrait,
+};
+use std::sync::Arc;
+
+/**
+ * DB store, 多個instance共用計數
+ */
+pub struct LoginAttemptRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl LoginAttemptRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn LoginAttemptRepository> {
+        Arc::new(LoginAttemptRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptRepo {
+    async fn get(&self, key: String) -> anyhow::Result<Option<LoginAttempt>> {
+        let db = self.mysql.get_db().await;
+        let model = LoginAttempts::find()
+            .filter(login_attempts::Column::AttemptKey.eq(key))
+            .one(db)
+            .await?;
+
+        Ok(model.map(|m| LoginAttempt {
+            failures: m.failures.max(0) as u32,
+            blocked_until: m.blocked_until,
+            last_failed_at: m.last_failed_at,
+        }))
+    }
+
+    /**
+     * 由DB累加失敗次數, 該筆紀錄在transaction結束前被鎖住, 再依次數寫入等待期限
+     */
+    async fn fail(
+        &self,
+        key: String,
+        policy: &ThrottlePolicy,
+        now: DateTime<Local>,
+    ) -> anyhow::Result<LoginAttempt> {
+        let db = self.mysql.get_db().await;
+        let txn = db.begin().await?;
+
+        //failures要先於last_failed_at更新, 才會用到前一次的失敗時間
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::MySql,
+            r#"INSERT INTO login_attempts (attempt_key, failures, last_failed_at) VALUES (?, 1, ?)
+            ON DUPLICATE KEY UPDATE
+                failures = IF(last_failed_at > ?, failures + 1, 1),
+                last_failed_at = VALUES(last_failed_at)"#,
+            vec![
+                key.clone().into(),
+                now.into(),
+                policy.window_start(now).into(),
+            ],
+        ))
+        .await?;
+
+        let model = LoginAttempts::find()
+            .filter(login_attempts::Column::AttemptKey.eq(key.clone()))
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("login attempt {} not found", key))?;
+        let failures = model.failures.max(0) as u32;
+        let blocked_until = policy.blocked_until(failures, now);
+
+        LoginAttempts::update_many()
+            .col_expr(
+                login_attempts::Column::BlockedUntil,
+                Expr::value(blocked_until),
+            )
+            .filter(login_attempts::Column::Id.eq(model.id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(LoginAttempt {
+            failures,
+            blocked_until: Some(blocked_until),
+            last_failed_at: model.last_failed_at,
+        })
+    }
+
+    async fn reset(&self, key: String) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        LoginAttempts::delete_many()
+            .filter(login_attempts::Column::AttemptKey.eq(key))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
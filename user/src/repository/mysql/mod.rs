@@ -1 +1,4 @@
+pub mod login_attempt_repo;
+pub mod session_repo;
+pub mod totp_repo;
 pub mod user_repo;
@@ -0,0 +1,131 @@
+use crate::domain::SessionRepository;
+use async_trait::async_trait;
+use chrono::Local;
+use entity::{prelude::*, refresh_tokens, user_sessions};
+use pkg::{db::ORM, jwt::SessionStore};
+use sea_orm::{prelude::*, sea_query::Expr, ConnectionTrait, DbBackend, Statement};
+use std::sync::Arc;
+
+pub struct SessionRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl SessionRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn SessionRepository> {
+        Arc::new(SessionRepo { mysql })
+    }
+
+    /**
+     * 給Claims extractor使用的session store
+     */
+    pub fn store(mysql: Arc<dyn ORM>) -> Arc<dyn SessionStore> {
+        Arc::new(SessionRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SessionRepo {
+    async fn create_session(
+        &self,
+        active: user_sessions::ActiveModel,
+    ) -> anyhow::Result<user_sessions::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.insert(db).await?;
+        Ok(model)
+    }
+
+    async fn get_session(&self, sid: String) -> anyhow::Result<Option<user_sessions::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = UserSessions::find()
+            .filter(user_sessions::Column::Sid.eq(sid))
+            .one(db)
+            .await?;
+        Ok(model)
+    }
+
+    async fn revoke_session(&self, sid: String) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        UserSessions::update_many()
+            .col_expr(user_sessions::Column::RevokedAt, Expr::value(Local::now()))
+            .filter(user_sessions::Column::Sid.eq(sid))
+            .filter(user_sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        account: String,
+        keep_sid: String,
+    ) -> anyhow::Result<u64> {
+        let db = self.mysql.get_db().await;
+        let res = UserSessions::update_many()
+            .col_expr(user_sessions::Column::RevokedAt, Expr::value(Local::now()))
+            .filter(user_sessions::Column::UserAccount.eq(account))
+            .filter(user_sessions::Column::Sid.ne(keep_sid))
+            .filter(user_sessions::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+
+    async fn create_refresh_token(
+        &self,
+        active: refresh_tokens::ActiveModel,
+    ) -> anyhow::Result<()> {
+        let db = self.mysql.get_db().await;
+        RefreshTokens::insert(active).exec(db).await?;
+        Ok(())
+    }
+
+    async fn get_refresh_token(
+        &self,
+        token_hash: String,
+    ) -> anyhow::Result<Option<refresh_tokens::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = RefreshTokens::find()
+            .filter(refresh_tokens::Column::TokenHash.eq(token_hash))
+            .one(db)
+            .await?;
+        Ok(model)
+    }
+
+    /**
+     * 標記已換發, 以used_at IS NULL做為樂觀鎖, 同時使用只有一個會成功
+     */
+    async fn use_refresh_token(&self, id: i64) -> anyhow::Result<bool> {
+        let db = self.mysql.get_db().await;
+        let res = RefreshTokens::update_many()
+            .col_expr(refresh_tokens::Column::UsedAt, Expr::value(Local::now()))
+            .filter(refresh_tokens::Column::Id.eq(id))
+            .filter(refresh_tokens::Column::UsedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
+}
+
+#[async_trait]
+impl SessionStore for SessionRepo {
+    async fn active_role(&self, sid: &str, account: &str) -> anyhow::Result<Option<i8>> {
+        let db = self.mysql.get_db().await;
+        let res = db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::MySql,
+                r#"SELECT u.role AS role FROM user_sessions s
+                    INNER JOIN users u ON u.account = s.user_account
+                    WHERE s.sid = ? AND s.user_account = ? AND s.revoked_at IS NULL
+                    AND u.state = 1 AND u.deleted_at IS NULL"#,
+                vec![sid.into(), account.into()],
+            ))
+            .await?;
+
+        let role = match res {
+            Some(row) => Some(row.try_get("", "role")?),
+            None => None,
+        };
+
+        Ok(role)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::domain::TotpRepository;
+use async_trait::async_trait;
+use entity::{prelude::*, user_totps};
+use pkg::db::ORM;
+use sea_orm::{prelude::*, sea_query::Expr};
+use std::sync::Arc;
+
+pub struct TotpRepo {
+    mysql: Arc<dyn ORM>,
+}
+
+impl TotpRepo {
+    pub fn new(mysql: Arc<dyn ORM>) -> Arc<dyn TotpRepository> {
+        Arc::new(TotpRepo { mysql })
+    }
+}
+
+#[async_trait]
+impl TotpRepository for TotpRepo {
+    async fn get(&self, account: String) -> anyhow::Result<Option<user_totps::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = UserTotps::find()
+            .filter(user_totps::Column::UserAccount.eq(account))
+            .one(db)
+            .await?;
+        Ok(model)
+    }
+
+    async fn get_by_challenge(
+        &self,
+        challenge_hash: String,
+    ) -> anyhow::Result<Option<user_totps::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = UserTotps::find()
+            .filter(user_totps::Column::ChallengeHash.eq(challenge_hash))
+            .one(db)
+            .await?;
+        Ok(model)
+    }
+
+    async fn create(&self, active: user_totps::ActiveModel) -> anyhow::Result<user_totps::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.insert(db).await?;
+        Ok(model)
+    }
+
+    async fn update(&self, active: user_totps::ActiveModel) -> anyhow::Result<user_totps::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.update(db).await?;
+        Ok(model)
+    }
+
+    /**
+     * 記錄已使用的time step, 只能往後, 同一個code同時使用只有一個會成功
+     */
+    async fn use_step(&self, id: i64, step: i64) -> anyhow::Result<bool> {
+        let db = self.mysql.get_db().await;
+        let res = UserTotps::update_many()
+            .col_expr(user_totps::Column::LastUsedStep, Expr::value(step))
+            .filter(user_totps::Column::Id.eq(id))
+            .filter(user_totps::Column::LastUsedStep.lt(step))
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
+
+    /**
+     * 移除已使用的recovery code, 以目前的值做為樂觀鎖
+     */
+    async fn use_recovery_code(
+        &self,
+        id: i64,
+        current: String,
+        remaining: String,
+    ) -> anyhow::Result<bool> {
+        let db = self.mysql.get_db().await;
+        let res = UserTotps::update_many()
+            .col_expr(user_totps::Column::RecoveryCodes, Expr::value(remaining))
+            .filter(user_totps::Column::Id.eq(id))
+            .filter(user_totps::Column::RecoveryCodes.eq(current))
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
+
+    async fn delete(&self, account: String) -> anyhow::Result<u64> {
+        let db = self.mysql.get_db().await;
+        let res = UserTotps::delete_many()
+            .filter(user_totps::Column::UserAccount.eq(account))
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+}
@@ -1,8 +1,14 @@
-use crate::domain::UserRepository;
+use crate::domain::{UserQuery, UserRepository, UserSort};
 use async_trait::async_trait;
-use entity::{prelude::*, users};
-use pkg::db::ORM;
-use sea_orm::{prelude::*, ConnectionTrait, DbBackend, Set, Statement};
+use entity::{password_histories, prelude::*, users};
+use pkg::{
+    db::ORM,
+    page::{paginate, Page, PageQuery},
+};
+use sea_orm::{
+    prelude::*, ConnectionTrait, DbBackend, QueryOrder, QuerySelect, Set, Statement,
+    TransactionTrait,
+};
 use std::sync::Arc;
 
 pub struct UserRepo {
@@ -21,12 +27,57 @@ impl UserRepository for UserRepo {
         let db = self.mysql.get_db().await;
         let model = Users::find()
             .filter(users::Column::Account.eq(account))
+            .filter(users::Column::DeletedAt.is_null())
             .one(db)
             .await?;
 
         Ok(model)
     }
 
+    async fn get_deleted(&self, account: String) -> anyhow::Result<Option<users::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Users::find()
+            .filter(users::Column::Account.eq(account))
+            .filter(users::Column::DeletedAt.is_not_null())
+            .one(db)
+            .await?;
+
+        Ok(model)
+    }
+
+    /**
+     * 包含已刪除的用戶
+     */
+    async fn find_any(&self, account: String) -> anyhow::Result<Option<users::Model>> {
+        let db = self.mysql.get_db().await;
+        let model = Users::find()
+            .filter(users::Column::Account.eq(account))
+            .one(db)
+            .await?;
+
+        Ok(model)
+    }
+
+    async fn list(
+        &self,
+        query: UserQuery,
+        page: PageQuery<UserSort>,
+    ) -> anyhow::Result<Page<users::Model>> {
+        let db = self.mysql.get_db().await;
+        let mut select = Users::find().filter(users::Column::DeletedAt.is_null());
+        if let Some(role) = query.role {
+            select = select.filter(users::Column::Role.eq(role));
+        }
+        if let Some(state) = query.state {
+            select = select.filter(users::Column::State.eq(state));
+        }
+        if let Some(name) = query.name.as_deref() {
+            select = select.filter(users::Column::Name.contains(name));
+        }
+
+        paginate(db, select, &page).await
+    }
+
     async fn save_token(&self, model: users::Model, token: String) -> anyhow::Result<users::Model> {
         let db = self.mysql.get_db().await;
         let mut user: entity::users::ActiveModel = model.into();
@@ -35,7 +86,8 @@ impl UserRepository for UserRepo {
         Ok(res)
     }
 
-    async fn is_exist(&self, account: String) -> bool {
+    async fn is_exist(&self, account: String) -> anyhow::Result<bool> {
+        //包含已刪除的用戶, 帳號不可重複使用
         let db = self.mysql.get_db().await;
         let res = db
             .query_one(Statement::from_sql_and_values(
@@ -43,12 +95,14 @@ impl UserRepository for UserRepo {
                 r#"SELECT COUNT(account) AS count FROM users WHERE account = ?"#,
                 vec![account.into()],
             ))
-            .await
-            .unwrap();
+            .await?;
 
-        let count: i64 = res.unwrap().try_get("", "count").unwrap();
+        let count: i64 = match res {
+            Some(row) => row.try_get("", "count")?,
+            None => 0,
+        };
 
-        count > 0
+        Ok(count > 0)
     }
 
     async fn create(&self, active: users::ActiveModel) -> anyhow::Result<users::Model> {
@@ -56,4 +110,62 @@ impl UserRepository for UserRepo {
         let model = active.insert(db).await?;
         Ok(model)
     }
+
+    async fn update(&self, active: users::ActiveModel) -> anyhow::Result<users::Model> {
+        let db = self.mysql.get_db().await;
+        let model = active.update(db).await?;
+        Ok(model)
+    }
+
+    async fn recent_passwords(&self, account: String, limit: u64) -> anyhow::Result<Vec<String>> {
+        let db = self.mysql.get_db().await;
+        let models = PasswordHistories::find()
+            .filter(password_histories::Column::UserAccount.eq(account))
+            .order_by_desc(password_histories::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        Ok(models.into_iter().map(|m| m.password).collect())
+    }
+
+    /**
+     * 舊密碼寫入歷史並更新密碼, 在同一個transaction內完成
+     */
+    async fn change_password(
+        &self,
+        model: users::Model,
+        password: String,
+    ) -> anyhow::Result<users::Model> {
+        let db = self.mysql.get_db().await;
+        let txn = db.begin().await?;
+
+        let history = password_histories::ActiveModel {
+            user_account: Set(model.account.clone()),
+            password: Set(model.password.clone()),
+            ..Default::default()
+        };
+        PasswordHistories::insert(history).exec(&txn).await?;
+
+        let mut user: users::ActiveModel = model.into();
+        user.password = Set(password);
+        let res = user.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(res)
+    }
+
+    async fn save_api_key(
+        &self,
+        model: users::Model,
+        api_key: String,
+        secret_key: String,
+    ) -> anyhow::Result<users::Model> {
+        let db = self.mysql.get_db().await;
+        let mut user: entity::users::ActiveModel = model.into();
+        user.api_key = Set(api_key);
+        user.secret_key = Set(secret_key);
+        let res = user.update(db).await?;
+        Ok(res)
+    }
 }
@@ -0,0 +1,92 @@
+use crate::domain::{LoginAttemptRepository, LoginThrottleUsecase, ThrottlePolicy};
+use async_trait::async_trait;
+use chrono::Local;
+use pkg::error::AppError;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+pub struct LoginThrottleUcase {
+    attempt_repo: Arc<dyn LoginAttemptRepository>,
+    account_policy: ThrottlePolicy,
+    ip_policy: ThrottlePolicy,
+}
+
+impl LoginThrottleUcase {
+    pub fn new(
+        attempt_repo: Arc<dyn LoginAttemptRepository>,
+        account_policy: ThrottlePolicy,
+        ip_policy: ThrottlePolicy,
+    ) -> Arc<dyn LoginThrottleUsecase> {
+        Arc::new(LoginThrottleUcase {
+            attempt_repo,
+            account_policy,
+            ip_policy,
+        })
+    }
+
+    /**
+     * 記錄一次失敗
+     */
+    async fn record(&self, key: String, policy: &ThrottlePolicy) -> anyhow::Result<()> {
+        let attempt = self
+            .attempt_repo
+            .fail(key.clone(), policy, Local::now())
+            .await?;
+        if attempt.failures == policy.max_failures {
+            tracing::warn!(
+                "login locked for {} after {} failures",
+                key,
+                attempt.failures
+            );
+        }
+        Ok(())
+    }
+}
+
+fn account_key(account: &str) -> String {
+    format!("account:{}", account.to_lowercase())
+}
+
+fn ip_key(ip: IpAddr) -> String {
+    format!("ip:{}", ip)
+}
+
+#[async_trait]
+impl LoginThrottleUsecase for LoginThrottleUcase {
+    /**
+     * 帳號或ip還在等待時間內則拒絕
+     */
+    async fn check(&self, account: &str, ip: IpAddr) -> anyhow::Result<()> {
+        let now = Local::now();
+        for key in [account_key(account), ip_key(ip)] {
+            let retry = match self.attempt_repo.get(key).await? {
+                Some(attempt) => ThrottlePolicy::retry_after(&attempt, now),
+                None => None,
+            };
+            if let Some(seconds) = retry {
+                return Err(AppError::forbidden(format!(
+                    "Too many login attempts, retry after {} seconds",
+                    seconds
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 登入失敗, 帳號與ip各自計數
+     */
+    async fn failed(&self, account: &str, ip: IpAddr) -> anyhow::Result<()> {
+        self.record(account_key(account), &self.account_policy)
+            .await?;
+        self.record(ip_key(ip), &self.ip_policy).await
+    }
+
+    /**
+     * 登入成功, 清除帳號的失敗紀錄; ip的紀錄保留, 避免用自己的帳號重置計數
+     */
+    async fn succeeded(&self, account: &str) -> anyhow::Result<()> {
+        self.attempt_repo.reset(account_key(account)).await
+    }
+}
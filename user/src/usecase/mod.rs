@@ -1 +1,3 @@
+pub mod login_throttle_ucase;
+pub mod totp_ucase;
 pub mod user_ucase;
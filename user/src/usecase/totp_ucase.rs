@@ -0,0 +1,267 @@
+use crate::domain::{
+    normalize_recovery_code, recovery_code_hashes, MfaChallenge, RecoveryCodes, TotpEnrolment,
+    TotpRepository, TotpStatus, TotpUsecase,
+};
+use async_trait::async_trait;
+use chrono::{Duration, Local};
+use entity::user_totps;
+use pkg::{crypto, error::AppError, totp};
+use sea_orm::ActiveValue::Set;
+use std::sync::Arc;
+
+//recovery code數量
+const RECOVERY_CODE_COUNT: usize = 10;
+//每個recovery code的亂數長度(bytes), 80 bits
+const RECOVERY_CODE_BYTES: usize = 10;
+//登入第二步的有效秒數
+const CHALLENGE_TTL: i64 = 300;
+//challenge token長度(bytes)
+const CHALLENGE_TOKEN_BYTES: usize = 32;
+
+pub struct TotpUcase {
+    totp_repo: Arc<dyn TotpRepository>,
+}
+
+impl TotpUcase {
+    pub fn new(totp_repo: Arc<dyn TotpRepository>) -> Arc<dyn TotpUsecase> {
+        Arc::new(TotpUcase { totp_repo })
+    }
+}
+
+/**
+ * authenticator app顯示的發行者名稱, 由TOTP_ISSUER設定
+ */
+fn issuer() -> String {
+    std::env::var("TOTP_ISSUER").unwrap_or_else(|_| "rest-rs".to_owned())
+}
+
+/**
+ * 產生recovery codes, 回傳(明碼, 保存用的hash)
+ */
+fn gen_recovery_codes() -> (Vec<String>, String) {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw = crypto::random_token(RECOVERY_CODE_BYTES);
+            format_recovery_code(&raw)
+        })
+        .collect();
+    let hashes: Vec<String> = codes
+        .iter()
+        .map(|code| crypto::sha256_hex(&normalize_recovery_code(code)))
+        .collect();
+
+    (codes, hashes.join(","))
+}
+
+/**
+ * 每5個字元以-分隔, 方便抄寫
+ */
+fn format_recovery_code(raw: &str) -> String {
+    raw.as_bytes()
+        .chunks(5)
+        .map(|chunk| String::from_utf8_lossy(chunk))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[async_trait]
+impl TotpUsecase for TotpUcase {
+    async fn status(&self, account: String) -> anyhow::Result<TotpStatus> {
+        let model = self.totp_repo.get(account).await?;
+        Ok(TotpStatus::from(model.as_ref()))
+    }
+
+    async fn is_enabled(&self, account: String) -> anyhow::Result<bool> {
+        Ok(self.get_enabled(account).await?.is_some())
+    }
+
+    /**
+     * 已完成綁定的totp, 未啟用時為None
+     */
+    async fn get_enabled(&self, account: String) -> anyhow::Result<Option<user_totps::Model>> {
+        let model = self.totp_repo.get(account).await?;
+        Ok(model.filter(|model| model.confirmed_at.is_some()))
+    }
+
+    /**
+     * 產生新的secret, 需以confirm確認後才會啟用
+     */
+    async fn enrol(&self, account: String) -> anyhow::Result<TotpEnrolment> {
+        let secret = totp::generate_secret();
+        let sealed = crypto::encrypt(&secret, &account)?;
+
+        match self.totp_repo.get(account.clone()).await? {
+            Some(model) if model.confirmed_at.is_some() => {
+                return Err(
+                    AppError::duplicate("Two-factor authentication already enabled").into(),
+                );
+            }
+            Some(model) => {
+                let mut active: user_totps::ActiveModel = model.into();
+                active.secret = Set(sealed);
+                active.last_used_step = Set(0);
+                active.recovery_codes = Set(String::new());
+                self.totp_repo.update(active).await?;
+            }
+            None => {
+                let active = user_totps::ActiveModel {
+                    user_account: Set(account.clone()),
+                    secret: Set(sealed),
+                    ..Default::default()
+                };
+                self.totp_repo.create(active).await?;
+            }
+        }
+
+        Ok(TotpEnrolment {
+            otpauth_uri: totp::otpauth_uri(&issuer(), &account, &secret),
+            secret,
+        })
+    }
+
+    /**
+     * 以app產生的code確認綁定, 完成後回傳recovery codes
+     */
+    async fn confirm(&self, account: String, code: String) -> anyhow::Result<RecoveryCodes> {
+        let model = match self.totp_repo.get(account).await? {
+            Some(model) if model.confirmed_at.is_none() => model,
+            Some(_) => {
+                return Err(AppError::duplicate("Two-factor authentication already enabled").into())
+            }
+            None => return Err(AppError::not_found("Two-factor enrolment not found").into()),
+        };
+
+        if !self.verify_code(&model, code).await? {
+            return Err(AppError::validation("Invalid code").into());
+        }
+
+        let (codes, hashes) = gen_recovery_codes();
+        let mut active: user_totps::ActiveModel = model.into();
+        active.confirmed_at = Set(Some(Local::now()));
+        active.recovery_codes = Set(hashes);
+        let model = self.totp_repo.update(active).await?;
+        tracing::info!(
+            "two-factor authentication enabled for {}",
+            model.user_account
+        );
+
+        Ok(RecoveryCodes {
+            recovery_codes: codes,
+        })
+    }
+
+    /**
+     * 登入第一步通過後產生challenge, 只保存token的hash
+     */
+    async fn challenge(&self, account: String) -> anyhow::Result<MfaChallenge> {
+        let model = self
+            .totp_repo
+            .get(account)
+            .await?
+            .ok_or_else(|| AppError::not_found("Two-factor enrolment not found"))?;
+
+        let token = crypto::random_token(CHALLENGE_TOKEN_BYTES);
+        let mut active: user_totps::ActiveModel = model.into();
+        active.challenge_hash = Set(Some(crypto::sha256_hex(&token)));
+        active.challenge_expired_at = Set(Some(Local::now() + Duration::seconds(CHALLENGE_TTL)));
+        self.totp_repo.update(active).await?;
+
+        Ok(MfaChallenge {
+            mfa_required: true,
+            mfa_token: token,
+            expires_in: CHALLENGE_TTL,
+        })
+    }
+
+    async fn get_challenge(&self, token: String) -> anyhow::Result<user_totps::Model> {
+        let model = self
+            .totp_repo
+            .get_by_challenge(crypto::sha256_hex(&token))
+            .await?;
+        match model {
+            Some(model)
+                if model.confirmed_at.is_some()
+                    && matches!(model.challenge_expired_at, Some(t) if t > Local::now()) =>
+            {
+                Ok(model)
+            }
+            _ => Err(AppError::unauthorized("Invalid mfa token").into()),
+        }
+    }
+
+    /**
+     * challenge只能使用一次
+     */
+    async fn finish_challenge(&self, model: user_totps::Model) -> anyhow::Result<()> {
+        let mut active: user_totps::ActiveModel = model.into();
+        active.challenge_hash = Set(None);
+        active.challenge_expired_at = Set(None);
+        self.totp_repo.update(active).await?;
+        Ok(())
+    }
+
+    /**
+     * 驗證totp code或recovery code, 通過的code會被標記為已使用
+     */
+    async fn verify_code(&self, model: &user_totps::Model, code: String) -> anyhow::Result<bool> {
+        let secret = crypto::decrypt(&model.secret, &model.user_account)?;
+        let now = Local::now().timestamp().max(0) as u64;
+        if let Some(step) = totp::verify(&secret, &code, now)? {
+            let step = step as i64;
+            return Ok(
+                step > model.last_used_step && self.totp_repo.use_step(model.id, step).await?
+            );
+        }
+
+        //recovery code只有綁定完成後可用
+        if model.confirmed_at.is_none() {
+            return Ok(false);
+        }
+        let hash = crypto::sha256_hex(&normalize_recovery_code(&code));
+        let hashes = recovery_code_hashes(&model.recovery_codes);
+        if !hashes.contains(&hash.as_str()) {
+            return Ok(false);
+        }
+        let remaining: Vec<&str> = hashes.into_iter().filter(|h| *h != hash).collect();
+        let used = self
+            .totp_repo
+            .use_recovery_code(model.id, model.recovery_codes.clone(), remaining.join(","))
+            .await?;
+        if used {
+            tracing::info!(
+                "recovery code used by {}, {} left",
+                model.user_account,
+                remaining.len()
+            );
+        }
+
+        Ok(used)
+    }
+
+    /**
+     * admin重設, 刪除綁定後用戶可重新綁定
+     */
+    async fn reset(&self, account: String) -> anyhow::Result<bool> {
+        let deleted = self.totp_repo.delete(account.clone()).await?;
+        if deleted > 0 {
+            tracing::info!("two-factor authentication reset for {}", account);
+        }
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_code_entropy() {
+        let (codes, hashes) = gen_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(recovery_code_hashes(&hashes).len(), RECOVERY_CODE_COUNT);
+        for code in codes.iter() {
+            assert_eq!(code.len(), 23);
+            assert_eq!(normalize_recovery_code(code).len(), RECOVERY_CODE_BYTES * 2);
+        }
+    }
+}
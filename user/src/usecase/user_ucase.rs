@@ -1,18 +1,72 @@
-use crate::domain::{CreateUser, UserInfo, UserRepository, UserUsecase};
+use crate::domain::{
+    access_token_ttl, check_password_strength, password_history_size, refresh_token_ttl,
+    AdminBootstrap, ApiKeyInfo, AuthBody, ChangePassword, CreateUser, SessionRepository, SetApiKey,
+    UpdateProfile, UpdateUser, UserInfo, UserQuery, UserRepository, UserSort, UserUsecase,
+};
+use anyhow::anyhow;
 use async_trait::async_trait;
+use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Duration, Local};
-use entity::users;
-use pkg::jwt::{encode_token, Claims};
+use entity::{refresh_tokens, user_sessions, users};
+use pkg::{
+    crypto, db,
+    error::AppError,
+    exchange::ExchangeFactory,
+    jwt::{encode_token, Claims},
+    page::{Page, PageQuery},
+    rbac::Role,
+};
 use sea_orm::ActiveValue::Set;
 use std::sync::Arc;
+use uuid::Uuid;
+
+//refresh token長度(bytes)
+const REFRESH_TOKEN_BYTES: usize = 32;
 
 pub struct UserUcase {
     user_repo: Arc<dyn UserRepository>,
+    session_repo: Arc<dyn SessionRepository>,
+    exchange: Arc<dyn ExchangeFactory>,
 }
 
 impl UserUcase {
-    pub fn new(user_repo: Arc<dyn UserRepository>) -> Arc<dyn UserUsecase> {
-        Arc::new(UserUcase { user_repo })
+    pub fn new(
+        user_repo: Arc<dyn UserRepository>,
+        session_repo: Arc<dyn SessionRepository>,
+        exchange: Arc<dyn ExchangeFactory>,
+    ) -> Arc<dyn UserUsecase> {
+        Arc::new(UserUcase {
+            user_repo,
+            session_repo,
+            exchange,
+        })
+    }
+
+    /**
+     * 簽發access token與新的refresh token
+     */
+    async fn issue_tokens(&self, model: users::Model, sid: String) -> anyhow::Result<AuthBody> {
+        let access_token = self
+            .gen_token(model.account.clone(), model.role, sid.clone())
+            .await?;
+
+        //refresh token只保存hash
+        let refresh_token = crypto::random_token(REFRESH_TOKEN_BYTES);
+        let active = refresh_tokens::ActiveModel {
+            sid: Set(sid),
+            token_hash: Set(crypto::sha256_hex(&refresh_token)),
+            expired_at: Set(Local::now() + Duration::seconds(refresh_token_ttl())),
+            ..Default::default()
+        };
+        self.session_repo.create_refresh_token(active).await?;
+
+        self.save_token(model, access_token.clone()).await?;
+
+        Ok(AuthBody::new(
+            access_token,
+            access_token_ttl(),
+            refresh_token,
+        ))
     }
 }
 
@@ -35,31 +89,35 @@ impl UserUsecase for UserUcase {
      * 取得用戶資料
      */
     async fn get_info(&self, account: String) -> anyhow::Result<UserInfo> {
-        let user_data = self.get_by_account(account).await?.unwrap();
+        let user_data = self
+            .get_by_account(account)
+            .await?
+            .ok_or_else(|| AppError::not_found("User not found"))?;
         let info = UserInfo::from(user_data);
         Ok(info)
     }
 
     /**
-     * 取得用戶資料
+     * 帳號是否已存在, 包含已刪除的用戶
      */
-    async fn is_exist(&self, account: String) -> bool {
+    async fn is_exist(&self, account: String) -> anyhow::Result<bool> {
         self.user_repo.is_exist(account).await
     }
 
     /**
      * 產生jwt token
      */
-    async fn gen_token(&self, account: String, role: i8) -> anyhow::Result<String> {
+    async fn gen_token(&self, account: String, role: i8, sid: String) -> anyhow::Result<String> {
         //計算過期時間..
-        let dt = Local::now() + Duration::weeks(1);
+        let dt = Local::now() + Duration::seconds(access_token_ttl());
         let ts = dt.timestamp() as usize;
 
         let claims = Claims {
-            account: account,
-            role: role,
+            account,
+            role,
+            sid,
             // Mandatory expiry time as UTC timestamp
-            exp: ts, //1 weeks
+            exp: ts,
         };
 
         // Create the authorization token
@@ -68,6 +126,79 @@ impl UserUsecase for UserUcase {
         Ok(token)
     }
 
+    /**
+     * 登入: 建立session並簽發token
+     */
+    async fn login(&self, model: users::Model, device: String) -> anyhow::Result<AuthBody> {
+        //停用或已刪除的用戶不能登入
+        if model.state == 0 || model.deleted_at.is_some() {
+            return Err(AppError::forbidden("Account disabled").into());
+        }
+
+        let active = user_sessions::ActiveModel {
+            sid: Set(Uuid::new_v4().to_string()),
+            user_account: Set(model.account.clone()),
+            device: Set(device.chars().take(255).collect()),
+            ..Default::default()
+        };
+        let session = self.session_repo.create_session(active).await?;
+
+        self.issue_tokens(model, session.sid).await
+    }
+
+    /**
+     * 以refresh token換發新token, 舊的refresh token同時失效
+     */
+    async fn refresh(&self, refresh_token: String) -> anyhow::Result<AuthBody> {
+        let token = self
+            .session_repo
+            .get_refresh_token(crypto::sha256_hex(&refresh_token))
+            .await?
+            .ok_or_else(|| AppError::unauthorized("Invalid refresh token"))?;
+
+        let session = self
+            .session_repo
+            .get_session(token.sid.clone())
+            .await?
+            .ok_or_else(|| AppError::unauthorized("Invalid refresh token"))?;
+        if session.revoked_at.is_some() {
+            return Err(AppError::unauthorized("Session revoked").into());
+        }
+
+        //已換發過的token再次被使用, 視為外洩並撤銷整個session
+        if token.used_at.is_some() || !self.session_repo.use_refresh_token(token.id).await? {
+            tracing::warn!(
+                "refresh token reuse detected, revoke session {} of {}",
+                session.sid,
+                session.user_account
+            );
+            self.session_repo.revoke_session(session.sid).await?;
+            return Err(AppError::unauthorized("Refresh token reused").into());
+        }
+
+        if token.expired_at < Local::now() {
+            return Err(AppError::unauthorized("Refresh token expired").into());
+        }
+
+        //停用或已刪除的用戶不能換發
+        let user_data = match self.get_by_account(session.user_account.clone()).await? {
+            Some(user_data) if user_data.state != 0 && user_data.deleted_at.is_none() => user_data,
+            _ => {
+                self.session_repo.revoke_session(session.sid).await?;
+                return Err(AppError::unauthorized("Account disabled").into());
+            }
+        };
+
+        self.issue_tokens(user_data, session.sid).await
+    }
+
+    /**
+     * 登出: 撤銷session, 該session的access / refresh token都會失效
+     */
+    async fn logout(&self, sid: String) -> anyhow::Result<()> {
+        self.session_repo.revoke_session(sid).await
+    }
+
     /**
      * 建立用戶
      */
@@ -79,7 +210,246 @@ impl UserUsecase for UserUcase {
             role: Set(body.role),
             ..Default::default()
         };
-        let res = self.user_repo.create(active_model).await?;
+        //同時建立相同帳號時由唯一索引擋下
+        match self.user_repo.create(active_model).await {
+            Err(e) if db::is_duplicate_key(&e) => {
+                Err(AppError::duplicate("Account already exist").into())
+            }
+            res => res,
+        }
+    }
+
+    /**
+     * 向交易所確認api key可用
+     */
+    async fn verify_api_key(&self, body: &SetApiKey) -> anyhow::Result<()> {
+        let client = self.exchange.client(&body.api_key, &body.secret_key);
+        client.verify_credentials().await
+    }
+
+    /**
+     * 確認已儲存的api key可用
+     */
+    async fn verify_saved_api_key(&self, model: &users::Model) -> anyhow::Result<()> {
+        if model.api_key.is_empty() || model.secret_key.is_empty() {
+            return Err(anyhow!("api key not configured"));
+        }
+        let secret_key = if crypto::is_sealed(&model.secret_key) {
+            crypto::decrypt(&model.secret_key, &model.account)?
+        } else {
+            //加密上線前保存的明文, 改存密文
+            let sealed = crypto::encrypt(&model.secret_key, &model.account)?;
+            self.user_repo
+                .save_api_key(model.clone(), model.api_key.clone(), sealed)
+                .await?;
+            model.secret_key.clone()
+        };
+        let client = self.exchange.client(&model.api_key, &secret_key);
+        client.verify_credentials().await
+    }
+
+    /**
+     * 設定或更換api key, secret以帳號為aad加密後儲存
+     */
+    async fn set_api_key(
+        &self,
+        model: users::Model,
+        body: SetApiKey,
+    ) -> anyhow::Result<ApiKeyInfo> {
+        let secret_key = crypto::encrypt(&body.secret_key, &model.account)?;
+        let res = self
+            .user_repo
+            .save_api_key(model, body.api_key, secret_key)
+            .await?;
+        Ok(ApiKeyInfo::from(&res))
+    }
+
+    /**
+     * 清除api key
+     */
+    async fn clear_api_key(&self, model: users::Model) -> anyhow::Result<ApiKeyInfo> {
+        let res = self
+            .user_repo
+            .save_api_key(model, String::new(), String::new())
+            .await?;
+        Ok(ApiKeyInfo::from(&res))
+    }
+
+    /**
+     * 建立或重設admin, 已是可用的admin且密碼相同時不做任何變更
+     */
+    async fn ensure_admin(
+        &self,
+        account: String,
+        password: String,
+    ) -> anyhow::Result<AdminBootstrap> {
+        check_password_strength(&account, &password).map_err(AppError::validation)?;
+
+        //已刪除的admin也要找出來還原, 不可重複建立
+        let model = match self.user_repo.find_any(account.clone()).await? {
+            Some(model) => model,
+            None => {
+                let active = users::ActiveModel {
+                    account: Set(account.clone()),
+                    password: Set(hash(password, DEFAULT_COST)?),
+                    name: Set(account),
+                    role: Set(Role::Admin.to_i8()),
+                    state: Set(1),
+                    ..Default::default()
+                };
+                self.user_repo.create(active).await?;
+                return Ok(AdminBootstrap::Created);
+            }
+        };
+
+        let password_matches = verify(&password, &model.password)?;
+        if password_matches
+            && model.role == Role::Admin.to_i8()
+            && model.state == 1
+            && model.deleted_at.is_none()
+        {
+            return Ok(AdminBootstrap::Unchanged);
+        }
+
+        let mut active: users::ActiveModel = model.into();
+        if !password_matches {
+            active.password = Set(hash(password, DEFAULT_COST)?);
+        }
+        active.role = Set(Role::Admin.to_i8());
+        active.state = Set(1);
+        active.deleted_at = Set(None);
+        self.user_repo.update(active).await?;
+
+        Ok(AdminBootstrap::Reset)
+    }
+
+    /**
+     * 用戶列表(不含已刪除)
+     */
+    async fn list(
+        &self,
+        query: UserQuery,
+        page: PageQuery<UserSort>,
+    ) -> anyhow::Result<Page<UserInfo>> {
+        let res = self.user_repo.list(query, page).await?;
+        Ok(res.map(UserInfo::from))
+    }
+
+    /**
+     * 取得已刪除的用戶
+     */
+    async fn get_deleted(&self, account: String) -> anyhow::Result<Option<users::Model>> {
+        let res = self.user_repo.get_deleted(account).await?;
+        Ok(res)
+    }
+
+    /**
+     * 更新用戶資料
+     */
+    async fn update(&self, model: users::Model, body: UpdateUser) -> anyhow::Result<users::Model> {
+        let mut active: users::ActiveModel = model.into();
+        if let Some(name) = body.name {
+            active.name = Set(name);
+        }
+        if let Some(role) = body.role {
+            active.role = Set(role);
+        }
+        if let Some(state) = body.state {
+            active.state = Set(state);
+        }
+        let res = self.user_repo.update(active).await?;
+        Ok(res)
+    }
+
+    /**
+     * 啟用/停用用戶
+     */
+    async fn set_state(&self, model: users::Model, state: i8) -> anyhow::Result<users::Model> {
+        let mut active: users::ActiveModel = model.into();
+        active.state = Set(state);
+        let res = self.user_repo.update(active).await?;
+        Ok(res)
+    }
+
+    /**
+     * 軟刪除用戶
+     */
+    async fn delete(&self, model: users::Model) -> anyhow::Result<users::Model> {
+        let mut active: users::ActiveModel = model.into();
+        active.deleted_at = Set(Some(Local::now()));
+        let res = self.user_repo.update(active).await?;
         Ok(res)
     }
+
+    /**
+     * 還原已刪除的用戶
+     */
+    async fn restore(&self, model: users::Model) -> anyhow::Result<users::Model> {
+        let mut active: users::ActiveModel = model.into();
+        active.deleted_at = Set(None);
+        let res = self.user_repo.update(active).await?;
+        Ok(res)
+    }
+
+    /**
+     * 更新個人資料
+     */
+    async fn update_profile(
+        &self,
+        model: users::Model,
+        body: UpdateProfile,
+    ) -> anyhow::Result<users::Model> {
+        let mut active: users::ActiveModel = model.into();
+        if let Some(name) = body.name {
+            active.name = Set(name);
+        }
+        let res = self.user_repo.update(active).await?;
+        Ok(res)
+    }
+
+    /**
+     * 變更密碼: 驗證目前密碼, 不可與最近使用過的密碼相同, 變更後撤銷目前以外的session
+     */
+    async fn change_password(
+        &self,
+        model: users::Model,
+        sid: String,
+        body: ChangePassword,
+    ) -> anyhow::Result<()> {
+        if !verify(&body.current_password, &model.password)? {
+            return Err(AppError::validation("Password Verify error").into());
+        }
+
+        //目前密碼也算在歷史內
+        let mut recent = vec![model.password.clone()];
+        let history_size = password_history_size();
+        if history_size > 1 {
+            recent.extend(
+                self.user_repo
+                    .recent_passwords(model.account.clone(), history_size - 1)
+                    .await?,
+            );
+        }
+        for old in recent.iter() {
+            if verify(&body.new_password, old)? {
+                return Err(AppError::validation("Password was used recently").into());
+            }
+        }
+
+        let account = model.account.clone();
+        let password = hash(body.new_password, DEFAULT_COST)?;
+        self.user_repo.change_password(model, password).await?;
+
+        let revoked = self
+            .session_repo
+            .revoke_other_sessions(account.clone(), sid)
+            .await?;
+        tracing::info!(
+            "password changed for {}, {} other sessions revoked",
+            account,
+            revoked
+        );
+
+        Ok(())
+    }
 }